@@ -1,26 +1,54 @@
 use crate::DiGraph;
 use std::hash::Hash;
 use graphviz_rust::dot_structures::Graph;
+use crate::analyzer::deepening::DeepeningSearch;
+use crate::analyzer::dfs_visitor::{Control, DfsVisitor};
+use crate::analyzer::diff::GraphDiff;
+use crate::analyzer::fs::{BidirectionalBFS, BFS, DFS};
+use crate::analyzer::topo::TopologicalSort;
 use crate::analyzer::isomorphism::IsomorphismAnalyzer;
 use crate::analyzer::mst::MinimumSpanningArborescence;
 
+pub mod all_pairs;
+pub mod all_shortest;
 pub mod astar;
+pub mod bellman_ford;
+pub mod bidirectional;
+pub mod contraction;
+pub mod cpm;
+pub mod dag_path;
+pub mod deepening;
+pub mod dfs_visitor;
+pub mod diff;
 pub mod dijkstra;
 pub mod disjoint;
 pub mod dom;
+pub mod float;
 pub mod fs;
+pub mod implicit;
+pub mod k_shortest;
+pub mod landmarks;
 pub mod min_weight;
 pub mod mst;
 pub mod predecessors;
 pub mod scc;
+pub mod semiring;
+pub mod topo;
+pub mod trace;
 pub mod visit;
 mod bipartite;
 mod isomorphism;
 
-enum SearchRes {
+/// The decision of a search predicate about the current node.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SearchRes {
+    /// Not a match, the successors are explored.
     Next,
+    /// A match. If all matches are requested, the successors are explored as well.
     Find,
+    /// Stops the search.
     Stop,
+    /// Not a match, the successors are not explored(the branch is pruned).
     Skip,
 }
 
@@ -47,8 +75,6 @@ impl<'a, NodeId, NL, EL> GraphAnalyzer<'a, NodeId, NL, EL>
     pub fn min_spanning_arborescence(&self) -> MinimumSpanningArborescence<'a, NodeId, NL, EL>
         where
             NodeId: Clone,
-            EL: Ord,
-
     {
         return MinimumSpanningArborescence::new(self.graph);
     }
@@ -57,3 +83,61 @@ impl<'a, NodeId, NL, EL> GraphAnalyzer<'a, NodeId, NL, EL>
         IsomorphismAnalyzer::new(&self.graph, another).test()
     }
 }
+
+impl<'a, NodeId, NL, EL> GraphAnalyzer<'a, NodeId, NL, EL>
+    where
+        NodeId: Eq + Hash,
+        EL: PartialEq,
+{
+    /// Compares the graph(as old one) with another graph(as new one).
+    pub fn diff(&self, another: &'a DiGraph<NodeId, NL, EL>) -> GraphDiff<'a, NodeId, NL, EL> {
+        GraphDiff::new(self.graph, another)
+    }
+}
+
+impl<'a, NodeId, NL, EL> GraphAnalyzer<'a, NodeId, NL, EL>
+    where
+        NodeId: Eq + Hash,
+{
+    /// Runs the event-driven dfs from the roots(see `dfs_visitor::depth_first_search`).
+    pub fn depth_first_search<V>(&self, roots: &[NodeId], visitor: &mut V) -> Control
+        where
+            V: DfsVisitor<'a, NodeId>,
+    {
+        dfs_visitor::depth_first_search(self.graph, roots, visitor)
+    }
+
+    /// Breadth-first search(see `fs::BFS`).
+    pub fn bfs(&self) -> BFS<'a, NodeId, NL, EL>
+        where
+            NodeId: Clone,
+    {
+        BFS::new(self.graph)
+    }
+
+    /// Depth-first search(see `fs::DFS`).
+    pub fn dfs(&self) -> DFS<'a, NodeId, NL, EL>
+        where
+            NodeId: Clone,
+    {
+        DFS::new(self.graph)
+    }
+
+    /// Bidirectional breadth-first search for the s-t queries(see `fs::BidirectionalBFS`).
+    pub fn bidirectional_bfs(&self) -> BidirectionalBFS<'a, NodeId, NL, EL>
+        where
+            NodeId: Clone,
+    {
+        BidirectionalBFS::new(self.graph)
+    }
+
+    /// Depth-limited, iterative-deepening and IDA* searches(see `deepening::DeepeningSearch`).
+    pub fn deepening(&self) -> DeepeningSearch<'a, NodeId, NL, EL> {
+        DeepeningSearch::new(self.graph)
+    }
+
+    /// Topological sort(see `topo::TopologicalSort`).
+    pub fn topological_sort(&self) -> TopologicalSort<'a, NodeId, NL, EL> {
+        TopologicalSort::new(self.graph)
+    }
+}
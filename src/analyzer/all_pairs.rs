@@ -0,0 +1,475 @@
+//! All-pairs shortest paths.
+//!
+//! Floyd-Warshall takes `O(n^3)` and suits the dense graphs.
+//! Johnson's algorithm reweights the edges with the potentials found by Bellman-Ford
+//! (thus the weights become non-negative) and runs Dijkstra from every node,
+//! it takes `O(nm log n)` and suits the sparse graphs.
+//! Both accept negative weights and return the negative cycle if there is one.
+//!
+//! The same as for Bellman-Ford, the default value of the weight type is used as zero.
+//! The edges leading to the nodes that do not exist in the graph are ignored.
+use crate::analyzer::bellman_ford::{bellman_ford, NegativeCycle};
+use crate::analyzer::float::{check_weights, Float, FloatError, FloatWeight};
+use crate::analyzer::min_weight::{MinWeight, Score};
+use crate::DiGraph;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::convert::identity;
+use std::hash::Hash;
+use std::ops::{Add, Sub};
+
+/// The distances and the paths between all pairs of nodes.
+/// The nodes are indexed in the order of `nodes()`.
+#[derive(Debug, Clone)]
+pub struct AllPairsPath<NId, ScoreV>
+where
+    NId: Eq + Hash + Clone,
+{
+    nodes: Vec<NId>,
+    index: HashMap<NId, usize>,
+    distance: Vec<Vec<Option<ScoreV>>>,
+    /// The last but one node of the path from the row node to the column node.
+    parent: Vec<Vec<Option<usize>>>,
+}
+
+impl<NId, ScoreV> AllPairsPath<NId, ScoreV>
+where
+    NId: Eq + Hash + Clone,
+    ScoreV: Clone,
+{
+    fn new(nodes: Vec<NId>) -> Self {
+        let n = nodes.len();
+        let index = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (id.clone(), i))
+            .collect();
+        Self {
+            nodes,
+            index,
+            distance: vec![vec![None; n]; n],
+            parent: vec![vec![None; n]; n],
+        }
+    }
+
+    /// The order of the nodes in the matrix.
+    pub fn nodes(&self) -> &Vec<NId> {
+        &self.nodes
+    }
+
+    /// The distance is `Zero` from a node to itself and `Inf` if there is no path
+    /// or one of the nodes does not exist.
+    pub fn distance(&self, from: &NId, to: &NId) -> Score<ScoreV> {
+        match (self.index.get(from), self.index.get(to)) {
+            (Some(f), Some(t)) if f == t => Score::Zero,
+            (Some(f), Some(t)) => self.score(*f, *t),
+            _ => Score::Inf,
+        }
+    }
+
+    /// The path from one node to another(both ends are included).
+    pub fn trail(&self, from: &NId, to: &NId) -> Option<Vec<NId>> {
+        let f = *self.index.get(from)?;
+        let mut cur = *self.index.get(to)?;
+        let mut trail = vec![self.nodes[cur].clone()];
+        while cur != f {
+            cur = self.parent[f][cur]?;
+            trail.push(self.nodes[cur].clone());
+        }
+        trail.reverse();
+        Some(trail)
+    }
+
+    /// The distance matrix: the rows are the sources and the columns are the targets
+    /// in the order of `nodes()`.
+    pub fn matrix(&self) -> Vec<Vec<Score<ScoreV>>> {
+        let n = self.nodes.len();
+        (0..n)
+            .map(|f| {
+                (0..n)
+                    .map(|t| {
+                        if f == t {
+                            Score::Zero
+                        } else {
+                            self.score(f, t)
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// The distance matrix in the given order of nodes.
+    pub fn matrix_for(&self, nodes: &[NId]) -> Vec<Vec<Score<ScoreV>>> {
+        nodes
+            .iter()
+            .map(|f| nodes.iter().map(|t| self.distance(f, t)).collect())
+            .collect()
+    }
+
+    fn score(&self, from: usize, to: usize) -> Score<ScoreV> {
+        match &self.distance[from][to] {
+            Some(d) => Score::Value(d.clone()),
+            None => Score::Inf,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct FloydWarshall<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+}
+
+impl<'a, NId, NL, EL> FloydWarshall<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self { graph }
+    }
+}
+
+impl<'a, NId, NL, EL> FloydWarshall<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Ord + Add<Output = EL> + Clone + Default,
+{
+    pub fn on_edge(&self) -> Result<AllPairsPath<NId, EL>, NegativeCycle<NId>> {
+        self.on_edge_custom(identity)
+    }
+}
+
+impl<'a, NId, NL, EL> FloydWarshall<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: FloatWeight,
+{
+    /// The float weights version of `on_edge`(see `crate::analyzer::float`).
+    pub fn on_float_edge(
+        &self,
+    ) -> Result<AllPairsPath<NId, Float<EL>>, FloatError<NId, NegativeCycle<NId>>> {
+        check_weights(self.graph)?;
+        self.on_edge_custom(Float::unchecked)
+            .map_err(FloatError::Failed)
+    }
+}
+
+impl<'a, NId, NL, EL> FloydWarshall<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Clone,
+{
+    pub fn on_edge_custom<ScoreV, F>(
+        &self,
+        to_score: F,
+    ) -> Result<AllPairsPath<NId, ScoreV>, NegativeCycle<NId>>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone + Default,
+    {
+        let mut res = AllPairsPath::new(self.graph.nodes.keys().cloned().collect());
+        let n = res.nodes.len();
+        let zero = ScoreV::default();
+        for i in 0..n {
+            res.distance[i][i] = Some(zero.clone());
+        }
+        for (from, tos) in self.graph.edges.iter() {
+            let Some(&f) = res.index.get(from) else {
+                continue;
+            };
+            for (to, el) in tos.iter() {
+                let Some(&t) = res.index.get(to) else {
+                    continue;
+                };
+                let w = to_score(el.clone());
+                if res.distance[f][t].as_ref().map(|d| &w < d).unwrap_or(true) {
+                    if f == t {
+                        self.negative_cycle(&res.nodes[f], &to_score)?;
+                    }
+                    res.distance[f][t] = Some(w);
+                    res.parent[f][t] = Some(f);
+                }
+            }
+        }
+
+        for k in 0..n {
+            for i in 0..n {
+                let Some(ik) = res.distance[i][k].clone() else {
+                    continue;
+                };
+                for j in 0..n {
+                    let Some(kj) = res.distance[k][j].clone() else {
+                        continue;
+                    };
+                    let alt = ik.clone() + kj;
+                    if res.distance[i][j]
+                        .as_ref()
+                        .map(|d| &alt < d)
+                        .unwrap_or(true)
+                    {
+                        // the distances keep decreasing around a negative cycle(and can overflow),
+                        // thus the search stops as soon as the cycle shows up
+                        if i == j && alt < zero {
+                            self.negative_cycle(&res.nodes[i], &to_score)?;
+                        }
+                        res.distance[i][j] = Some(alt);
+                        res.parent[i][j] = res.parent[k][j];
+                    }
+                }
+            }
+        }
+        Ok(res)
+    }
+
+    /// The node with the negative distance to itself is on a negative cycle,
+    /// Bellman-Ford from it finds the cycle.
+    fn negative_cycle<ScoreV, F>(&self, id: &NId, to_score: &F) -> Result<(), NegativeCycle<NId>>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone + Default,
+    {
+        let (source, _) = self.graph.nodes.get_key_value(id).expect("the node exists");
+        bellman_ford(self.graph, &[source], to_score)?;
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub struct Johnson<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+}
+
+impl<'a, NId, NL, EL> Johnson<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self { graph }
+    }
+}
+
+impl<'a, NId, NL, EL> Johnson<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Ord + Add<Output = EL> + Sub<Output = EL> + Clone + Default,
+{
+    pub fn on_edge(&self) -> Result<AllPairsPath<NId, EL>, NegativeCycle<NId>> {
+        self.on_edge_custom(identity)
+    }
+}
+
+impl<'a, NId, NL, EL> Johnson<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: FloatWeight,
+{
+    /// The float weights version of `on_edge`(see `crate::analyzer::float`).
+    pub fn on_float_edge(
+        &self,
+    ) -> Result<AllPairsPath<NId, Float<EL>>, FloatError<NId, NegativeCycle<NId>>> {
+        check_weights(self.graph)?;
+        self.on_edge_custom(Float::unchecked)
+            .map_err(FloatError::Failed)
+    }
+}
+
+impl<'a, NId, NL, EL> Johnson<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Clone,
+{
+    pub fn on_edge_custom<ScoreV, F>(
+        &self,
+        to_score: F,
+    ) -> Result<AllPairsPath<NId, ScoreV>, NegativeCycle<NId>>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Sub<Output = ScoreV> + Clone + Default,
+    {
+        // all nodes start with zero that is the same as an extra node with zero edges to all nodes
+        let sources: Vec<&NId> = self.graph.nodes.keys().collect();
+        let potential: HashMap<&NId, ScoreV> = bellman_ford(self.graph, &sources, &to_score)?
+            .distance
+            .into_iter()
+            .map(|(id, d)| (id, d.unwrap_or_default()))
+            .collect();
+        let reweight = |from: &NId, to: &NId, el: &EL| {
+            to_score(el.clone()) + potential[from].clone() - potential[to].clone()
+        };
+
+        let mut res = AllPairsPath::new(self.graph.nodes.keys().cloned().collect());
+        for source in sources.iter() {
+            let (distance, parents) = dijkstra(self.graph, source, &reweight);
+            let f = res.index[*source];
+            for (to, d) in distance {
+                let t = res.index[to];
+                let d = match d {
+                    Score::Value(d) => d,
+                    _ => ScoreV::default(),
+                };
+                res.distance[f][t] = Some(d - potential[source].clone() + potential[to].clone());
+            }
+            for (to, from) in parents {
+                res.parent[f][res.index[to]] = Some(res.index[from]);
+            }
+        }
+        Ok(res)
+    }
+}
+
+/// The distances of the reachable nodes and the parents.
+type Tree<'a, NId, ScoreV> = (HashMap<&'a NId, Score<ScoreV>>, HashMap<&'a NId, &'a NId>);
+
+/// Dijkstra over the non-negative weights given for the edges.
+fn dijkstra<'a, NId, NL, EL, ScoreV, W>(
+    graph: &'a DiGraph<NId, NL, EL>,
+    source: &'a NId,
+    weight: &W,
+) -> Tree<'a, NId, ScoreV>
+where
+    NId: Eq + Hash,
+    W: Fn(&NId, &NId, &EL) -> ScoreV,
+    ScoreV: Ord + Add<Output = ScoreV> + Clone,
+{
+    let mut distance: HashMap<&NId, Score<ScoreV>> = HashMap::from([(source, Score::Zero)]);
+    let mut parents = HashMap::new();
+    let mut settled = HashSet::new();
+    let mut queue = BinaryHeap::from([MinWeight(source, Score::Zero)]);
+
+    while let Some(MinWeight(from, _)) = queue.pop() {
+        if !settled.insert(from) {
+            continue;
+        }
+        let d_from = distance[from].clone();
+        for (to, el) in graph.edges.get(from).into_iter().flatten() {
+            if !graph.nodes.contains_key(to) || settled.contains(to) {
+                continue;
+            }
+            let alt = d_from.add_score_v(weight(from, to, el));
+            if distance.get(to).map(|d| &alt < d).unwrap_or(true) {
+                distance.insert(to, alt.clone());
+                parents.insert(to, from);
+                queue.push(MinWeight(to, alt));
+            }
+        }
+    }
+    (distance, parents)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::all_pairs::{FloydWarshall, Johnson};
+    use crate::analyzer::bellman_ford::BellmanFordPath;
+    use crate::analyzer::float::Float;
+    use crate::analyzer::min_weight::Score;
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+
+    #[test]
+    fn all_pairs_test() {
+        let graph = digraph!((usize,_,i64) => [1,2,3,4,5] => {
+           1 => [(2,3),(3,8),(5,-4)];
+           2 => [(4,1),(5,7)];
+           3 => (2,4);
+           4 => [(1,2),(3,-5)];
+           5 => (4,6);
+        });
+        let fw = FloydWarshall::new(&graph).on_edge().unwrap();
+        let johnson = Johnson::new(&graph).on_edge().unwrap();
+        let order = [1, 2, 3, 4, 5];
+        let expected = [
+            [0, 1, -3, 2, -4],
+            [3, 0, -4, 1, -1],
+            [7, 4, 0, 5, 3],
+            [2, -1, -5, 0, -2],
+            [8, 5, 1, 6, 0],
+        ];
+        for res in [&fw, &johnson] {
+            let matrix = res.matrix_for(&order);
+            for (i, row) in expected.iter().enumerate() {
+                for (j, d) in row.iter().enumerate() {
+                    let expected = if i == j {
+                        Score::Zero
+                    } else {
+                        Score::Value(*d)
+                    };
+                    assert_eq!(matrix[i][j], expected);
+                }
+            }
+            assert_eq!(res.trail(&1, &2), Some(vec![1, 5, 4, 3, 2]));
+            assert_eq!(res.trail(&3, &3), Some(vec![3]));
+            for from in order.iter() {
+                let bf = BellmanFordPath::new(&graph).on_edge(*from).unwrap();
+                for to in order.iter().filter(|to| *to != from) {
+                    assert_eq!(res.distance(from, to), bf.score(to));
+                    assert_eq!(res.trail(from, to), bf.trail(to));
+                }
+            }
+        }
+        let matrix = fw.matrix();
+        let i = fw.nodes().iter().position(|n| *n == 4).unwrap();
+        let j = fw.nodes().iter().position(|n| *n == 3).unwrap();
+        assert_eq!(matrix[i][j], Score::Value(-5));
+    }
+
+    #[test]
+    fn unreachable_and_cycle_test() {
+        let graph = digraph!((usize,_,usize) => [1,2,3] => {
+           1 => (2,1);
+        });
+        let res = Johnson::new(&graph).on_edge_custom(|w| w as i64).unwrap();
+        assert_eq!(res.distance(&1, &2), Score::Value(1));
+        assert_eq!(res.distance(&2, &1), Score::Inf);
+        assert_eq!(res.distance(&1, &42), Score::Inf);
+        assert_eq!(res.trail(&2, &1), None);
+
+        let graph = digraph!((usize,_,i64) => [1,2,3,4] => {
+           1 => (2,1);
+           2 => (3,-2);
+           3 => [(2,1),(4,1)];
+        });
+        let cycle = FloydWarshall::new(&graph).on_edge().unwrap_err();
+        assert_eq!(cycle.nodes().len(), 2);
+        let cycle = Johnson::new(&graph).on_edge().unwrap_err();
+        assert_eq!(cycle.nodes().len(), 2);
+    }
+
+    #[test]
+    fn early_negative_cycle_test() {
+        // the distances around the cycles double every round,
+        // they would overflow long before the last one
+        let mut graph: DiGraph<usize, EmptyPayload, i32> = DiGraph::new();
+        for id in 0..40 {
+            graph.add_bare_node(id);
+        }
+        for from in 0..40 {
+            for to in 0..40 {
+                if from != to {
+                    graph.add_edge(from, to, -1);
+                }
+            }
+        }
+        let cycle = FloydWarshall::new(&graph).on_edge().unwrap_err();
+        assert!(cycle.nodes().len() >= 2);
+    }
+
+    #[test]
+    fn float_negative_test() {
+        let graph = digraph!((usize,_,f64) => [1,2,3,4] => {
+           1 => [(2,0.5),(3,2.0)];
+           2 => [(3,-1.25),(4,3.0)];
+           3 => (4,0.25);
+        });
+        let floyd = FloydWarshall::new(&graph).on_float_edge().unwrap();
+        let johnson = Johnson::new(&graph).on_float_edge().unwrap();
+        assert_eq!(floyd.matrix(), johnson.matrix());
+        assert_eq!(floyd.distance(&1, &4), Score::Value(Float::unchecked(-0.5)));
+        assert_eq!(johnson.trail(&1, &4), Some(vec![1, 2, 3, 4]));
+    }
+}
@@ -0,0 +1,446 @@
+//! All shortest paths from a source.
+//!
+//! `MinPath` keeps one predecessor per node, while here every predecessor giving the optimal distance
+//! is kept. Together they form the shortest path DAG that allows counting and enumerating all shortest paths,
+//! the settle order(non-decreasing distance) is kept as well, that is what Brandes-like betweenness needs.
+//!
+//! Every edge giving the optimal distance is a predecessor edge except the ones closing a cycle of zero weight:
+//! inside such a cycle only the edges leading to the nodes needing more edges from the source are kept.
+//! Thus the result is acyclic and does not depend on the order the nodes are visited
+//! (the paths going around such a cycle are not counted).
+//! The weights need to be non-negative.
+//!
+//! The sums of floats are rounded, thus the paths of the same length can get different float distances.
+//! `on_float_edge_with_tolerance` takes the distances within the tolerance as ties.
+use crate::analyzer::float::{check_weights, Float, FloatWeight, NaNWeight};
+use crate::analyzer::min_weight::{MinWeight, Score};
+use crate::{DiGraph, EmptyPayload};
+use std::collections::hash_map::Entry::Vacant;
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::convert::identity;
+use std::hash::Hash;
+use std::ops::Add;
+
+/// The distances and all optimal predecessors of the nodes reachable from the source.
+#[derive(Debug, Clone)]
+pub struct ShortestPathDag<NId, ScoreV>
+where
+    NId: Eq + Hash,
+{
+    source: NId,
+    distance: HashMap<NId, Score<ScoreV>>,
+    predecessors: HashMap<NId, Vec<(NId, ScoreV)>>,
+    order: Vec<NId>,
+}
+
+impl<NId, ScoreV> ShortestPathDag<NId, ScoreV>
+where
+    NId: Eq + Hash + Clone,
+    ScoreV: Clone,
+{
+    pub fn source(&self) -> &NId {
+        &self.source
+    }
+
+    /// The distance from the source, `Score::Inf` if the node is not reachable.
+    pub fn score(&self, to: &NId) -> Score<ScoreV> {
+        self.distance.get(to).cloned().unwrap_or(Score::Inf)
+    }
+
+    /// The predecessors of the node on the shortest paths with the weights of the edges.
+    pub fn predecessors(&self, to: &NId) -> Vec<&(NId, ScoreV)> {
+        self.predecessors
+            .get(to)
+            .map(|ps| ps.iter().collect())
+            .unwrap_or_default()
+    }
+
+    /// The reachable nodes in the order they are settled(the distances are non-decreasing).
+    pub fn order(&self) -> &Vec<NId> {
+        &self.order
+    }
+
+    /// The number of the shortest paths from the source to every reachable node.
+    /// The counts saturate at `usize::MAX`.
+    pub fn counts(&self) -> HashMap<&NId, usize> {
+        let mut counts: HashMap<&NId, usize> = HashMap::new();
+        for id in self.order.iter() {
+            let count = if id == &self.source {
+                1
+            } else {
+                self.predecessors(id)
+                    .iter()
+                    .map(|(p, _)| counts.get(p).cloned().unwrap_or_default())
+                    .fold(0, usize::saturating_add)
+            };
+            counts.insert(id, count);
+        }
+        counts
+    }
+
+    /// The number of the shortest paths from the source to the node.
+    pub fn count(&self, to: &NId) -> usize {
+        self.counts().get(to).cloned().unwrap_or_default()
+    }
+
+    /// All shortest paths from the source to the node(both ends are included).
+    /// The number of the paths can grow exponentially, see `count`.
+    pub fn paths(&self, to: &NId) -> Vec<Vec<NId>> {
+        if !self.distance.contains_key(to) {
+            return vec![];
+        }
+        let mut paths = vec![];
+        let mut stack = vec![vec![to.clone()]];
+        while let Some(reversed) = stack.pop() {
+            let last = reversed.last().expect("the path is not empty");
+            if last == &self.source {
+                paths.push(reversed.into_iter().rev().collect());
+                continue;
+            }
+            for (p, _) in self.predecessors(last).into_iter().rev() {
+                let mut next = reversed.clone();
+                next.push(p.clone());
+                stack.push(next);
+            }
+        }
+        paths
+    }
+
+    /// The shortest path DAG: the reachable nodes with the distances as payloads
+    /// and the edges of the shortest paths with the weights. The source is the start of the graph.
+    pub fn dag(&self) -> DiGraph<NId, Score<ScoreV>, ScoreV> {
+        let mut dag = DiGraph::new();
+        for id in self.order.iter() {
+            dag.add_node(id.clone(), self.score(id));
+        }
+        for (to, ps) in self.predecessors.iter() {
+            for (from, w) in ps.iter() {
+                dag.add_edge(from.clone(), to.clone(), w.clone());
+            }
+        }
+        dag
+    }
+}
+
+#[derive(Debug)]
+pub struct AllShortestPaths<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+}
+
+impl<'a, NId, NL, EL> AllShortestPaths<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self { graph }
+    }
+}
+
+impl<'a, NId, NL, EL> AllShortestPaths<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Ord + Add<Output = EL> + Clone,
+{
+    pub fn on_edge(&self, start: NId) -> ShortestPathDag<NId, EL> {
+        self.on_edge_custom(start, identity)
+    }
+}
+
+impl<'a, NId, NL, EL> AllShortestPaths<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: FloatWeight,
+{
+    /// The float weights version of `on_edge`(see `crate::analyzer::float`).
+    /// The paths are equal only if their rounded sums are equal, see `on_float_edge_with_tolerance`.
+    pub fn on_float_edge(
+        &self,
+        start: NId,
+    ) -> Result<ShortestPathDag<NId, Float<EL>>, NaNWeight<NId>> {
+        check_weights(self.graph)?;
+        Ok(self.on_edge_custom(start, Float::unchecked))
+    }
+
+    /// The same as `on_float_edge` but the paths are equal
+    /// if their sums are within the tolerance(see `Float::approx_eq`).
+    pub fn on_float_edge_with_tolerance(
+        &self,
+        start: NId,
+        tolerance: EL,
+    ) -> Result<ShortestPathDag<NId, Float<EL>>, NaNWeight<NId>> {
+        check_weights(self.graph)?;
+        Ok(self.process(start, Float::unchecked, |lhs, rhs| {
+            lhs.approx_eq(rhs, tolerance)
+        }))
+    }
+}
+
+impl<'a, NId, NL, EL> AllShortestPaths<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Clone,
+{
+    pub fn on_edge_custom<ScoreV, F>(&self, start: NId, to_score: F) -> ShortestPathDag<NId, ScoreV>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone,
+    {
+        self.process(start, to_score, |lhs, rhs| lhs == rhs)
+    }
+
+    /// `tie` tells if the length of the path through the edge is the distance of the node.
+    fn process<ScoreV, F, T>(&self, start: NId, to_score: F, tie: T) -> ShortestPathDag<NId, ScoreV>
+    where
+        F: Fn(EL) -> ScoreV,
+        T: Fn(&Score<ScoreV>, &Score<ScoreV>) -> bool,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone,
+    {
+        let mut dag = ShortestPathDag {
+            source: start.clone(),
+            distance: HashMap::new(),
+            predecessors: HashMap::new(),
+            order: vec![],
+        };
+        let Some((start, _)) = self.graph.nodes.get_key_value(&start) else {
+            return dag;
+        };
+        let mut distance: HashMap<&NId, Score<ScoreV>> = HashMap::from([(start, Score::Zero)]);
+        let mut settled: HashSet<&NId> = HashSet::new();
+        let mut queue = BinaryHeap::from([MinWeight(start, Score::Zero)]);
+        while let Some(MinWeight(from, _)) = queue.pop() {
+            if !settled.insert(from) {
+                continue;
+            }
+            let dist_from = distance[from].clone();
+            for (to, el) in self.graph.edges.get(from).into_iter().flatten() {
+                let Some((to, _)) = self.graph.nodes.get_key_value(to) else {
+                    continue;
+                };
+                let alt = dist_from.add_score_v(to_score(el.clone()));
+                if distance.get(to).map(|d| &alt < d).unwrap_or(true) {
+                    distance.insert(to, alt.clone());
+                    queue.push(MinWeight(to, alt));
+                }
+            }
+        }
+
+        // the edges lying on the shortest paths
+        let mut tight: Vec<(&NId, &NId, ScoreV)> = vec![];
+        for (from, dist_from) in distance.iter() {
+            for (to, el) in self.graph.edges.get(*from).into_iter().flatten() {
+                let Some((to, dist_to)) = distance.get_key_value(to) else {
+                    continue;
+                };
+                let w = to_score(el.clone());
+                if tie(&dist_from.add_score_v(w.clone()), dist_to) {
+                    tight.push((*from, *to, w));
+                }
+            }
+        }
+        let hops = min_hops(start, &tight);
+
+        // the weights are non-negative, thus the cycles of the edges on the shortest paths
+        // have zero weight(or are within the tolerance), they are the components of these edges.
+        // Inside them only the edges leading to the nodes with more edges from the source are kept
+        let mut tight_graph: DiGraph<&NId, EmptyPayload, EmptyPayload> = DiGraph::new();
+        for (from, to, _) in tight.iter() {
+            tight_graph.add_node(*from, EmptyPayload);
+            tight_graph.add_node(*to, EmptyPayload);
+            tight_graph.add_edge(*from, *to, EmptyPayload);
+        }
+        let component: HashMap<&NId, usize> = tight_graph
+            .scc()
+            .into_iter()
+            .enumerate()
+            .flat_map(|(idx, ids)| ids.into_iter().map(move |id| (*id, idx)))
+            .collect();
+        let same_component = |from: &NId, to: &NId| match (component.get(from), component.get(to)) {
+            (Some(lhs), Some(rhs)) => lhs == rhs,
+            _ => false,
+        };
+
+        let mut predecessors: HashMap<&NId, Vec<(&NId, ScoreV)>> = HashMap::new();
+        let mut successors: HashMap<&NId, Vec<&NId>> = HashMap::new();
+        for (from, to, w) in tight {
+            if same_component(from, to) && hops[from] >= hops[to] {
+                continue;
+            }
+            predecessors.entry(to).or_default().push((from, w));
+            successors.entry(from).or_default().push(to);
+        }
+
+        // the topological order of the predecessors, the nearest ready node goes first
+        let mut waiting: HashMap<&NId, usize> = predecessors
+            .iter()
+            .map(|(id, ps)| (*id, ps.len()))
+            .collect();
+        let mut ready = BinaryHeap::from([MinWeight(start, Score::Zero)]);
+        while let Some(MinWeight(from, _)) = ready.pop() {
+            dag.order.push(from.clone());
+            for to in successors.get(from).into_iter().flatten() {
+                let w = waiting.get_mut(to).expect("the successor has predecessors");
+                *w -= 1;
+                if *w == 0 {
+                    ready.push(MinWeight(*to, distance[to].clone()));
+                }
+            }
+        }
+
+        dag.distance = distance
+            .into_iter()
+            .map(|(id, d)| (id.clone(), d))
+            .collect();
+        dag.predecessors = predecessors
+            .into_iter()
+            .map(|(id, ps)| {
+                let ps = ps.into_iter().map(|(p, w)| (p.clone(), w)).collect();
+                (id.clone(), ps)
+            })
+            .collect();
+        dag
+    }
+}
+
+/// The number of edges of the shortest path with the fewest edges(BFS over the edges of the shortest paths).
+fn min_hops<'a, NId, ScoreV>(
+    start: &'a NId,
+    tight: &[(&'a NId, &'a NId, ScoreV)],
+) -> HashMap<&'a NId, usize>
+where
+    NId: Eq + Hash,
+{
+    let mut successors: HashMap<&NId, Vec<&NId>> = HashMap::new();
+    for (from, to, _) in tight.iter() {
+        successors.entry(*from).or_default().push(*to);
+    }
+    let mut hops = HashMap::from([(start, 0)]);
+    let mut queue = VecDeque::from([start]);
+    while let Some(from) = queue.pop_front() {
+        let next = hops[from] + 1;
+        for to in successors.get(from).into_iter().flatten() {
+            if let Vacant(e) = hops.entry(*to) {
+                e.insert(next);
+                queue.push_back(*to);
+            }
+        }
+    }
+    hops
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::all_shortest::AllShortestPaths;
+    use crate::analyzer::float::Float;
+    use crate::analyzer::min_weight::Score;
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+    use std::collections::HashSet;
+
+    #[test]
+    fn ties_test() {
+        // two ways to 4 and two ways from 4 to 6
+        let graph = digraph!((usize,_,usize) => [1,2,3,4,5,6,7,8] => {
+           1 => [(2,1),(3,1),(7,5)];
+           [2,3] => (4,1);
+           4 => [(5,1),(6,2)];
+           5 => (6,1);
+           7 => (6,1);
+        });
+        let dag = AllShortestPaths::new(&graph).on_edge(1);
+        assert_eq!(dag.score(&6), Score::Value(4));
+        assert_eq!(dag.score(&8), Score::Inf);
+        assert_eq!(dag.count(&6), 4);
+        assert_eq!(dag.count(&4), 2);
+        assert_eq!(dag.count(&1), 1);
+        assert_eq!(dag.count(&8), 0);
+
+        let paths: HashSet<Vec<usize>> = dag.paths(&6).into_iter().collect();
+        assert_eq!(
+            paths,
+            HashSet::from([
+                vec![1, 2, 4, 6],
+                vec![1, 3, 4, 6],
+                vec![1, 2, 4, 5, 6],
+                vec![1, 3, 4, 5, 6],
+            ])
+        );
+        assert_eq!(dag.paths(&1), vec![vec![1]]);
+        assert!(dag.paths(&8).is_empty());
+
+        let distances: Vec<Score<usize>> = dag.order().iter().map(|id| dag.score(id)).collect();
+        assert!(distances.windows(2).all(|w| w[0] <= w[1]));
+    }
+
+    #[test]
+    fn dag_test() {
+        let graph = digraph!((usize,_,usize) => [1,2,3,4] => {
+           1 => [(2,1),(3,1)];
+           [2,3] => (4,1);
+           4 => (1,1);
+        });
+        let dag = AllShortestPaths::new(&graph).on_edge(1).dag();
+        assert_eq!(dag.start(), &Some(1));
+        assert_eq!(dag.node_by_id(&4), Some((&4, &Score::Value(2))));
+        assert_eq!(dag.edge(&2, &4), Some(&1));
+        assert_eq!(dag.edge(&3, &4), Some(&1));
+        assert_eq!(dag.edge(&4, &1), None);
+        assert_eq!(dag.successor_ids(&1).len(), 2);
+
+        // the cycle of zero weight does not make the counts infinite
+        let graph = digraph!((usize,_,usize) => [1,2,3,4] => {
+           1 => [(2,0),(3,0)];
+           2 => [(3,0),(4,1)];
+           3 => [(2,0),(4,1)];
+        });
+        let dag = AllShortestPaths::new(&graph).on_edge(1);
+        assert_eq!(dag.count(&2), 1);
+        assert_eq!(dag.count(&3), 1);
+        assert_eq!(dag.count(&4), 2);
+        assert!(dag.dag().scc().iter().all(|c| c.len() == 1));
+
+        // the cycle entered only once keeps the edges going away from the entry
+        let graph = digraph!((usize,_,usize) => [1,2,3] => {
+           1 => (2,0);
+           2 => (3,0);
+           3 => (2,0);
+        });
+        let dag = AllShortestPaths::new(&graph).on_edge(1);
+        assert_eq!(dag.paths(&3), vec![vec![1, 2, 3]]);
+        assert_eq!(dag.order(), &vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn zero_weight_ties_test() {
+        // the result does not depend on the order the nodes with equal distances are settled
+        for _ in 0..20 {
+            let graph = digraph!((usize,_,usize) => [1,2,3] => {
+               1 => [(2,0),(3,0)];
+               3 => (2,0);
+            });
+            let dag = AllShortestPaths::new(&graph).on_edge(1);
+            assert_eq!(dag.count(&2), 2);
+            assert_eq!(dag.count(&3), 1);
+            assert_eq!(dag.order(), &vec![1, 3, 2]);
+        }
+    }
+
+    #[test]
+    fn float_tolerance_test() {
+        // 0.1 + 0.2 is not 0.3 after rounding
+        let graph = digraph!((usize,_,f64) => [1,2,3,4] => {
+           1 => [(2,0.1),(3,0.3)];
+           2 => (4,0.2);
+           3 => (4,0.0);
+        });
+        let paths = AllShortestPaths::new(&graph);
+        assert_eq!(paths.on_float_edge(1).unwrap().count(&4), 1);
+        let dag = paths.on_float_edge_with_tolerance(1, 1e-9).unwrap();
+        assert_eq!(dag.count(&4), 2);
+        assert_eq!(dag.score(&4), Score::Value(Float::unchecked(0.3)));
+        assert_eq!(dag.order().len(), 4);
+    }
+}
@@ -1,6 +1,8 @@
 use crate::DiGraph;
 
+use super::float::{check_heuristic, check_weights, Float, FloatError, FloatWeight, NaNHeuristic};
 use super::min_weight::{MinWeight, Score};
+use super::trace::{emit, NoTrace, TraceEvent, Tracer};
 use std::collections::hash_map::Entry::Occupied;
 use std::collections::hash_map::Entry::Vacant;
 use std::convert::identity;
@@ -10,38 +12,60 @@ use std::{
     ops::Add,
 };
 
+/// The path from the start to the target with the cost.
 #[derive(Debug)]
-pub struct MinPathStrict<NId>
+pub struct MinPathStrict<NId, ScoreV>
 where
     NId: Eq + Hash + Clone,
 {
     path: HashMap<NId, NId>,
     start: NId,
     target: NId,
+    cost: Score<ScoreV>,
 }
 
-impl<NId> MinPathStrict<NId>
+impl<NId, ScoreV> MinPathStrict<NId, ScoreV>
 where
     NId: Eq + Hash + Clone,
 {
-    fn path(&self) -> Vec<NId> {
-        if self.path.is_empty() {
-            vec![]
-        } else {
-            let mut path = Vec::new();
-            let mut step = Some(self.target.clone());
-
-            while let Some(s) = step {
-                path.push(s.clone());
-                if s == self.start {
-                    break;
-                }
-                step = self.path.get(&s).cloned();
-            }
+    pub(crate) fn new(
+        path: HashMap<NId, NId>,
+        start: NId,
+        target: NId,
+        cost: Score<ScoreV>,
+    ) -> Self {
+        Self {
+            path,
+            start,
+            target,
+            cost,
+        }
+    }
 
-            path.reverse();
-            path
+    /// The nodes from the start to the target(both ends are included)
+    /// or an empty vec if the target is not reachable.
+    pub fn path(&self) -> Vec<NId> {
+        if let Score::Inf = self.cost {
+            return vec![];
         }
+        let mut path = Vec::new();
+        let mut step = Some(self.target.clone());
+
+        while let Some(s) = step {
+            path.push(s.clone());
+            if s == self.start {
+                break;
+            }
+            step = self.path.get(&s).cloned();
+        }
+
+        path.reverse();
+        path
+    }
+
+    /// The total score of the path, `Score::Inf` if the target is not reachable.
+    pub fn cost(&self) -> &Score<ScoreV> {
+        &self.cost
     }
 }
 
@@ -63,12 +87,53 @@ where
         target: NId,
         heuristic: H,
         edge_w: E,
-    ) -> MinPathStrict<NId>
+    ) -> MinPathStrict<NId, ScoreV>
+    where
+        H: Fn(&NId) -> ScoreV,
+        E: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone,
+        EL: Clone,
+    {
+        self.process(start, target, heuristic, edge_w, &mut NoTrace, |_| {
+            String::new()
+        })
+    }
+
+    /// The same as `on_edge_custom` but reports pop, visit, relax and push events to the tracer.
+    pub fn on_edge_custom_traced<H, E, ScoreV, T>(
+        &self,
+        start: NId,
+        target: NId,
+        heuristic: H,
+        edge_w: E,
+        tracer: &mut T,
+    ) -> MinPathStrict<NId, ScoreV>
+    where
+        H: Fn(&NId) -> ScoreV,
+        E: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone + ToString,
+        EL: Clone,
+        T: Tracer<NId>,
+    {
+        self.process(start, target, heuristic, edge_w, tracer, Score::to_string)
+    }
+
+    fn process<H, E, ScoreV, T, D>(
+        &self,
+        start: NId,
+        target: NId,
+        heuristic: H,
+        edge_w: E,
+        tracer: &mut T,
+        describe: D,
+    ) -> MinPathStrict<NId, ScoreV>
     where
         H: Fn(&NId) -> ScoreV,
         E: Fn(EL) -> ScoreV,
         ScoreV: Ord + Add<Output = ScoreV> + Clone,
         EL: Clone,
+        T: Tracer<NId>,
+        D: Fn(&Score<ScoreV>) -> String,
     {
         let mut traverse: BinaryHeap<MinWeight<NId, ScoreV>> = BinaryHeap::new();
         let mut path: HashMap<NId, NId> = HashMap::new();
@@ -78,14 +143,13 @@ where
 
         scores.insert(&start, Score::Zero);
         traverse.push(MinWeight(&start, Score::Value(heuristic(&start))));
+        emit(tracer, || TraceEvent::Push(start.clone()));
 
         while let Some(MinWeight(current, curr_est_score)) = traverse.pop() {
+            emit(tracer, || TraceEvent::Pop(current.clone()));
             if current == &target {
-                return MinPathStrict {
-                    path,
-                    start,
-                    target,
-                };
+                let cost = scores.get(current).cloned().unwrap_or(Score::Zero);
+                return MinPathStrict::new(path, start, target, cost);
             }
 
             match est_scores.entry(current) {
@@ -101,28 +165,31 @@ where
                 }
             }
 
+            emit(tracer, || TraceEvent::Visit(current.clone()));
             if let Some(ss) = self.graph.edges.get(current) {
                 let current_score = scores.get(current).unwrap().clone();
                 for (to, el) in ss {
                     let next_score = scores.get(to).unwrap().clone();
                     let tentative_score = current_score.clone() + Score::Value(edge_w(el.clone()));
                     if tentative_score < next_score {
+                        emit(tracer, || TraceEvent::Relax {
+                            from: current.clone(),
+                            to: to.clone(),
+                            score: describe(&tentative_score),
+                        });
                         path.insert(to.clone(), current.clone());
                         scores.insert(to, tentative_score.clone());
                         traverse.push(MinWeight(
                             to,
                             tentative_score + Score::Value(heuristic(&to)),
-                        ))
+                        ));
+                        emit(tracer, || TraceEvent::Push(to.clone()));
                     }
                 }
             }
         }
 
-        MinPathStrict {
-            path,
-            start,
-            target,
-        }
+        MinPathStrict::new(path, start, target, Score::Inf)
     }
 }
 
@@ -131,7 +198,7 @@ where
     NId: Eq + Hash + Clone,
     EL: Ord + Add<Output = EL> + Clone,
 {
-    pub fn on_edge<H>(&self, start: NId, target: NId, heuristic: H) -> MinPathStrict<NId>
+    pub fn on_edge<H>(&self, start: NId, target: NId, heuristic: H) -> MinPathStrict<NId, EL>
     where
         H: Fn(&NId) -> EL,
     {
@@ -139,6 +206,29 @@ where
     }
 }
 
+impl<'a, NId, NL, EL> AStarPath<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: FloatWeight,
+{
+    /// The float weights version of `on_edge`, the heuristic is checked for NaN as well
+    /// (see `crate::analyzer::float`).
+    pub fn on_float_edge<H>(
+        &self,
+        start: NId,
+        target: NId,
+        heuristic: H,
+    ) -> Result<MinPathStrict<NId, Float<EL>>, FloatError<NId, NaNHeuristic<NId>>>
+    where
+        H: Fn(&NId) -> EL,
+    {
+        check_weights(self.graph)?;
+        check_heuristic(self.graph, &heuristic).map_err(FloatError::Failed)?;
+        let heuristic = |id: &NId| Float::unchecked(heuristic(id));
+        Ok(self.on_edge_custom(start, target, heuristic, Float::unchecked))
+    }
+}
+
 impl<'a, NId, NL, EL> AStarPath<'a, NId, NL, EL>
 where
     NId: Eq + Hash + Clone,
@@ -153,6 +243,7 @@ mod tests {
     use super::AStarPath;
     use crate::analyzer::dijkstra::DijkstraPath;
     use crate::analyzer::dijkstra::MinPathProcessor;
+    use crate::analyzer::trace::{Trace, TraceEvent};
     use crate::DiGraph;
     use crate::EmptyPayload;
     use crate::{digraph, extend_edges, extend_nodes};
@@ -179,4 +270,33 @@ mod tests {
 
         assert_eq!(astar_res, dijkstra_res);
     }
+
+    #[test]
+    fn traced_test() {
+        let graph = digraph!((_,_,usize) => [1,2,3] => {
+           1 => [(2,1),(3,4)];
+           2 => (3,1);
+        });
+        let mut trace = Trace::new();
+        let res = AStarPath::new(&graph).on_edge_custom_traced(1, 3, |_| 0, identity, &mut trace);
+        assert_eq!(res.path(), vec![1, 2, 3]);
+        assert_eq!(trace.events().last(), Some(&TraceEvent::Pop(3)));
+        assert!(trace.events().contains(&TraceEvent::Relax {
+            from: 2,
+            to: 3,
+            score: "2".to_string()
+        }));
+    }
+
+    #[test]
+    fn nan_heuristic_test() {
+        let graph = digraph!((_,_,f32) => [1,2,3] => {
+           1 => (2,1.0);
+           2 => (3,1.0);
+        });
+        let err = AStarPath::new(&graph)
+            .on_float_edge(1, 3, |id| if *id == 2 { f32::NAN } else { 0.0 })
+            .unwrap_err();
+        assert_eq!(err.to_string(), "the heuristic of 2 is NaN");
+    }
 }
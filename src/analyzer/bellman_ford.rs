@@ -0,0 +1,381 @@
+//! Shortest paths with negative weights.
+//!
+//! Bellman-Ford relaxes all edges round by round until nothing changes,
+//! SPFA(the queue-based variant) relaxes only the edges of the nodes whose distance has changed.
+//! If a negative cycle is reachable from the start the shortest paths do not exist
+//! and the cycle is returned instead.
+//!
+//! `Score::Zero` is less than any value, thus it cannot be compared with negative weights
+//! and the default value of the weight type is used as zero.
+//! The edges leading to the nodes that do not exist in the graph are ignored.
+use crate::analyzer::dijkstra::MinPath;
+use crate::analyzer::float::{check_weights, Float, FloatError, FloatWeight};
+use crate::analyzer::min_weight::Score;
+use crate::DiGraph;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::convert::identity;
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+use std::ops::Add;
+
+/// The cycle with the negative total weight: every node has an edge to the next one
+/// and the last node has an edge to the first one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NegativeCycle<NId> {
+    nodes: Vec<NId>,
+}
+
+impl<NId> NegativeCycle<NId> {
+    pub fn nodes(&self) -> &Vec<NId> {
+        &self.nodes
+    }
+}
+
+impl<NId: ToString> Display for NegativeCycle<NId> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let nodes: Vec<String> = self.nodes.iter().map(|n| n.to_string()).collect();
+        write!(f, "the graph has a negative cycle: {}", nodes.join(" -> "))
+    }
+}
+
+/// The distances and the parents found so far(`None` is the infinity).
+pub(crate) struct Relaxed<'a, NId, ScoreV> {
+    pub(crate) distance: HashMap<&'a NId, Option<ScoreV>>,
+    pub(crate) parents: HashMap<&'a NId, &'a NId>,
+}
+
+#[derive(Debug)]
+pub struct BellmanFordPath<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+    queue: bool,
+}
+
+impl<'a, NId, NL, EL> BellmanFordPath<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self {
+            graph,
+            queue: false,
+        }
+    }
+
+    /// Switches to SPFA: usually much faster on sparse graphs, the same in the worst case.
+    pub fn spfa(self) -> Self {
+        Self {
+            queue: true,
+            ..self
+        }
+    }
+}
+
+impl<'a, NId, NL, EL> BellmanFordPath<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Ord + Add<Output = EL> + Clone + Default,
+{
+    pub fn on_edge(&self, start: NId) -> Result<MinPath<NId, EL>, NegativeCycle<NId>> {
+        self.on_edge_custom(start, identity)
+    }
+}
+
+impl<'a, NId, NL, EL> BellmanFordPath<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: FloatWeight,
+{
+    /// The float weights version of `on_edge`(see `crate::analyzer::float`).
+    pub fn on_float_edge(
+        &self,
+        start: NId,
+    ) -> Result<MinPath<NId, Float<EL>>, FloatError<NId, NegativeCycle<NId>>> {
+        check_weights(self.graph)?;
+        self.on_edge_custom(start, Float::unchecked)
+            .map_err(FloatError::Failed)
+    }
+}
+
+impl<'a, NId, NL, EL> BellmanFordPath<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Clone,
+{
+    pub fn on_edge_custom<ScoreV, F>(
+        &self,
+        start: NId,
+        to_score: F,
+    ) -> Result<MinPath<NId, ScoreV>, NegativeCycle<NId>>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone + Default,
+    {
+        let Some((start_ref, _)) = self.graph.nodes.get_key_value(&start) else {
+            return Ok(MinPath::new(start, HashMap::new(), HashMap::new()));
+        };
+        let relaxed = if self.queue {
+            spfa(self.graph, &[start_ref], &to_score)?
+        } else {
+            bellman_ford(self.graph, &[start_ref], &to_score)?
+        };
+        let distance = relaxed
+            .distance
+            .into_iter()
+            .map(|(id, d)| {
+                let score = match d {
+                    _ if id == &start => Score::Zero,
+                    Some(d) => Score::Value(d),
+                    None => Score::Inf,
+                };
+                (id.clone(), score)
+            })
+            .collect();
+        let path = relaxed
+            .parents
+            .into_iter()
+            .map(|(to, from)| (to.clone(), from.clone()))
+            .collect();
+        Ok(MinPath::new(start, distance, path))
+    }
+}
+
+/// Relaxes all edges round by round. All sources start with the zero distance.
+pub(crate) fn bellman_ford<'a, NId, NL, EL, ScoreV, F>(
+    graph: &'a DiGraph<NId, NL, EL>,
+    sources: &[&'a NId],
+    to_score: &F,
+) -> Result<Relaxed<'a, NId, ScoreV>, NegativeCycle<NId>>
+where
+    NId: Eq + Hash + Clone,
+    EL: Clone,
+    F: Fn(EL) -> ScoreV,
+    ScoreV: Ord + Add<Output = ScoreV> + Clone + Default,
+{
+    let mut relaxed = Relaxed::new(graph, sources);
+    let mut round = 0;
+    loop {
+        round += 1;
+        let mut changed = vec![];
+        for (from, tos) in graph.edges.iter() {
+            for (to, el) in tos.iter() {
+                if relaxed.relax(graph, from, to, to_score(el.clone())) {
+                    changed.push(to);
+                }
+            }
+        }
+        if changed.is_empty() {
+            return Ok(relaxed);
+        }
+        // after n - 1 rounds the distances can change only because of a negative cycle,
+        // it shows up in the parents sooner or later
+        if round >= graph.nodes.len() {
+            for id in changed {
+                if let Some(cycle) = relaxed.parent_cycle(id) {
+                    return Err(cycle);
+                }
+            }
+        }
+    }
+}
+
+/// Relaxes the edges of the nodes whose distance has changed.
+/// A node is suspected to be on a negative cycle when its path has at least n edges.
+pub(crate) fn spfa<'a, NId, NL, EL, ScoreV, F>(
+    graph: &'a DiGraph<NId, NL, EL>,
+    sources: &[&'a NId],
+    to_score: &F,
+) -> Result<Relaxed<'a, NId, ScoreV>, NegativeCycle<NId>>
+where
+    NId: Eq + Hash + Clone,
+    EL: Clone,
+    F: Fn(EL) -> ScoreV,
+    ScoreV: Ord + Add<Output = ScoreV> + Clone + Default,
+{
+    let mut relaxed = Relaxed::new(graph, sources);
+    let mut edges: HashMap<&NId, usize> = sources.iter().map(|id| (*id, 0)).collect();
+    let mut queue: VecDeque<&NId> = sources.iter().cloned().collect();
+    let mut in_queue: HashSet<&NId> = sources.iter().cloned().collect();
+
+    while let Some(from) = queue.pop_front() {
+        in_queue.remove(from);
+        for (to, el) in graph.edges.get(from).into_iter().flatten() {
+            if !relaxed.relax(graph, from, to, to_score(el.clone())) {
+                continue;
+            }
+            let len = edges[from] + 1;
+            edges.insert(to, len);
+            if len >= graph.nodes.len() {
+                if let Some(cycle) = relaxed.parent_cycle(to) {
+                    return Err(cycle);
+                }
+            }
+            if in_queue.insert(to) {
+                queue.push_back(to);
+            }
+        }
+    }
+    Ok(relaxed)
+}
+
+impl<'a, NId, ScoreV> Relaxed<'a, NId, ScoreV>
+where
+    NId: Eq + Hash + Clone,
+    ScoreV: Ord + Add<Output = ScoreV> + Clone + Default,
+{
+    fn new<NL, EL>(graph: &'a DiGraph<NId, NL, EL>, sources: &[&'a NId]) -> Self {
+        let mut distance: HashMap<&NId, Option<ScoreV>> =
+            graph.nodes.keys().map(|id| (id, None)).collect();
+        for s in sources {
+            distance.insert(s, Some(ScoreV::default()));
+        }
+        Self {
+            distance,
+            parents: HashMap::new(),
+        }
+    }
+
+    /// Returns true if the edge improves the distance of the target.
+    fn relax<NL, EL>(
+        &mut self,
+        graph: &'a DiGraph<NId, NL, EL>,
+        from: &'a NId,
+        to: &'a NId,
+        w: ScoreV,
+    ) -> bool {
+        let Some(Some(d_from)) = self.distance.get(from) else {
+            return false;
+        };
+        if !graph.nodes.contains_key(to) {
+            return false;
+        }
+        let alt = d_from.clone() + w;
+        match self.distance.get(to) {
+            Some(Some(d_to)) if d_to <= &alt => false,
+            _ => {
+                self.distance.insert(to, Some(alt));
+                self.parents.insert(to, from);
+                true
+            }
+        }
+    }
+
+    /// Follows the parents from the node and returns the cycle if the parents have one.
+    fn parent_cycle(&self, id: &'a NId) -> Option<NegativeCycle<NId>> {
+        let mut seen = HashSet::new();
+        let mut cur = id;
+        while seen.insert(cur) {
+            cur = self.parents.get(cur)?;
+        }
+        let mut nodes = vec![cur.clone()];
+        let mut next = self.parents[cur];
+        while next != cur {
+            nodes.push(next.clone());
+            next = self.parents[next];
+        }
+        nodes.reverse();
+        Some(NegativeCycle { nodes })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::bellman_ford::{BellmanFordPath, NegativeCycle};
+    use crate::analyzer::dijkstra::DijkstraPath;
+    use crate::analyzer::float::{Float, FloatError};
+    use crate::analyzer::min_weight::Score;
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+
+    fn assert_cycle(graph: &DiGraph<usize, EmptyPayload, i64>, cycle: &NegativeCycle<usize>) {
+        let nodes = cycle.nodes();
+        let mut total = 0;
+        for i in 0..nodes.len() {
+            total += graph
+                .edge(&nodes[i], &nodes[(i + 1) % nodes.len()])
+                .unwrap();
+        }
+        assert!(total < 0);
+    }
+
+    #[test]
+    fn negative_weights_test() {
+        let graph = digraph!((usize,_,i64) => [1,2,3,4,5,6] => {
+           1 => [(2,4),(3,2)];
+           2 => (4,-3);
+           3 => [(2,1),(4,5)];
+           4 => (5,2);
+        });
+        for bf in [
+            BellmanFordPath::new(&graph),
+            BellmanFordPath::new(&graph).spfa(),
+        ] {
+            let res = bf.on_edge(1).unwrap();
+            assert_eq!(res.score(&1), Score::Zero);
+            assert_eq!(res.score(&4), Score::Value(0));
+            assert_eq!(res.score(&5), Score::Value(2));
+            assert_eq!(res.score(&6), Score::Inf);
+            assert_eq!(res.trail(&5), Some(vec![1, 3, 2, 4, 5]));
+        }
+
+        let graph = digraph!((usize,_,usize) => [1,2,3,4] => {
+           1 => [(2,1),(3,4)];
+           2 => (3,1);
+           3 => (4,2);
+        });
+        let res = BellmanFordPath::new(&graph)
+            .on_edge_custom(1, |w| w as i64)
+            .unwrap();
+        let dijkstra = DijkstraPath::new(&graph).on_edge_custom(1, |w| w as i64);
+        assert_eq!(res.trail(&4), dijkstra.trail(&4));
+        assert_eq!(res.score(&4), dijkstra.score(&4));
+    }
+
+    #[test]
+    fn negative_cycle_test() {
+        let graph = digraph!((usize,_,i64) => [1,2,3,4,5,6] => {
+           1 => (2,1);
+           2 => (3,1);
+           3 => [(4,-2),(6,1)];
+           4 => [(2,-1),(5,1)];
+           6 => (1,1);
+        });
+        for bf in [
+            BellmanFordPath::new(&graph),
+            BellmanFordPath::new(&graph).spfa(),
+        ] {
+            let cycle = bf.on_edge(1).unwrap_err();
+            assert_eq!(cycle.nodes().len(), 3);
+            assert_cycle(&graph, &cycle);
+        }
+        // the cycle is not reachable from 5
+        let res = BellmanFordPath::new(&graph).on_edge(5).unwrap();
+        assert_eq!(res.score(&1), Score::Inf);
+
+        let graph = digraph!((usize,_,i64) => [1,2] => {
+           1 => (2,1);
+           2 => (2,-1);
+        });
+        let cycle = BellmanFordPath::new(&graph).on_edge(1).unwrap_err();
+        assert_eq!(cycle.nodes(), &vec![2]);
+        assert_eq!(cycle.to_string(), "the graph has a negative cycle: 2");
+    }
+
+    #[test]
+    fn float_negative_cycle_test() {
+        let mut graph = digraph!((usize,_,f64) => [1,2,3] => {
+           1 => [(2,1.5),(3,4.0)];
+           2 => (3,-2.25);
+        });
+        let path = BellmanFordPath::new(&graph).on_float_edge(1).unwrap();
+        assert_eq!(path.score(&3), Score::Value(Float::unchecked(-0.75)));
+        assert_eq!(path.trail(&3), Some(vec![1, 2, 3]));
+
+        graph.add_edge(3, 1, 0.5);
+        let err = BellmanFordPath::new(&graph).on_float_edge(1).unwrap_err();
+        assert!(matches!(err, FloatError::Failed(_)));
+    }
+}
@@ -0,0 +1,285 @@
+//! Bidirectional Dijkstra for point-to-point queries.
+//!
+//! Two searches run at the same time: the forward one from the start along the edges
+//! and the backward one from the target along the reversed edges(see `ReverseIndex`).
+//! Every scanned edge that reaches a node labelled by the other search gives a candidate path.
+//! The search stops when the sum of the two minimal queued scores is not less than the best candidate,
+//! thus usually both searches settle a ball of about half of the radius.
+//!
+//! The weights need to be non-negative.
+use crate::analyzer::astar::MinPathStrict;
+use crate::analyzer::float::{check_weights, Float, FloatWeight, NaNWeight};
+use crate::analyzer::min_weight::{MinWeight, Score};
+use crate::analyzer::predecessors::ReverseIndex;
+use crate::DiGraph;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::convert::identity;
+use std::hash::Hash;
+use std::ops::Add;
+
+#[derive(Debug)]
+pub struct BidirectionalDijkstra<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+}
+
+impl<'a, NId, NL, EL> BidirectionalDijkstra<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self { graph }
+    }
+}
+
+impl<'a, NId, NL, EL> BidirectionalDijkstra<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Ord + Add<Output = EL> + Clone,
+{
+    pub fn on_edge(&self, start: NId, target: NId) -> MinPathStrict<NId, EL> {
+        self.on_edge_custom(start, target, identity)
+    }
+}
+
+impl<'a, NId, NL, EL> BidirectionalDijkstra<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: FloatWeight,
+{
+    /// The float weights version of `on_edge`(see `crate::analyzer::float`).
+    pub fn on_float_edge(
+        &self,
+        start: NId,
+        target: NId,
+    ) -> Result<MinPathStrict<NId, Float<EL>>, NaNWeight<NId>> {
+        check_weights(self.graph)?;
+        Ok(self.on_edge_custom(start, target, Float::unchecked))
+    }
+}
+
+impl<'a, NId, NL, EL> BidirectionalDijkstra<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Clone,
+{
+    pub fn on_edge_custom<ScoreV, F>(
+        &self,
+        start: NId,
+        target: NId,
+        to_score: F,
+    ) -> MinPathStrict<NId, ScoreV>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone,
+    {
+        let (Some((s, _)), Some((t, _))) = (
+            self.graph.nodes.get_key_value(&start),
+            self.graph.nodes.get_key_value(&target),
+        ) else {
+            return MinPathStrict::new(HashMap::new(), start, target, Score::Inf);
+        };
+        if s == t {
+            return MinPathStrict::new(HashMap::new(), start, target, Score::Zero);
+        }
+
+        let reverse = ReverseIndex::new(self.graph);
+        let mut forward = Side::new(s);
+        let mut backward = Side::new(t);
+        let mut best: Score<ScoreV> = Score::Inf;
+        let mut meet = None;
+
+        loop {
+            let (top_f, top_b) = (forward.top(), backward.top());
+            if let (Score::Inf, _) | (_, Score::Inf) = (&top_f, &top_b) {
+                break;
+            }
+            if top_f.clone() + top_b.clone() >= best {
+                break;
+            }
+            let (this, other, next): (_, _, Vec<(&NId, &EL)>) = if top_f <= top_b {
+                let id = forward.settle();
+                let next = self
+                    .graph
+                    .edges
+                    .get(id)
+                    .into_iter()
+                    .flatten()
+                    .filter(|(to, _)| self.graph.nodes.contains_key(to))
+                    .collect();
+                (&mut forward, &backward, next)
+            } else {
+                let id = backward.settle();
+                let next = reverse
+                    .by_node(id)
+                    .into_iter()
+                    .flatten()
+                    .map(|(from, el)| (*from, *el))
+                    .collect();
+                (&mut backward, &forward, next)
+            };
+            let from = this.last.expect("the node is settled");
+            for (to, el) in next {
+                let alt = this.relax(from, to, to_score(el.clone()));
+                if let Some(rest) = other.distance.get(to) {
+                    let candidate = alt + rest.clone();
+                    if candidate < best {
+                        best = candidate;
+                        meet = Some(to);
+                    }
+                }
+            }
+        }
+
+        let mut path = HashMap::new();
+        if let Some(meet) = meet {
+            let mut cur = meet;
+            while let Some(prev) = forward.parents.get(cur) {
+                path.insert(cur.clone(), (*prev).clone());
+                cur = prev;
+            }
+            let mut cur = meet;
+            while let Some(next) = backward.parents.get(cur) {
+                path.insert((*next).clone(), cur.clone());
+                cur = next;
+            }
+        }
+        MinPathStrict::new(path, start, target, best)
+    }
+}
+
+/// The state of one direction of the search.
+struct Side<'a, NId, ScoreV> {
+    distance: HashMap<&'a NId, Score<ScoreV>>,
+    parents: HashMap<&'a NId, &'a NId>,
+    settled: HashSet<&'a NId>,
+    heap: BinaryHeap<MinWeight<'a, NId, ScoreV>>,
+    last: Option<&'a NId>,
+}
+
+impl<'a, NId, ScoreV> Side<'a, NId, ScoreV>
+where
+    NId: Eq + Hash,
+    ScoreV: Ord + Add<Output = ScoreV> + Clone,
+{
+    fn new(source: &'a NId) -> Self {
+        Self {
+            distance: HashMap::from([(source, Score::Zero)]),
+            parents: HashMap::new(),
+            settled: HashSet::new(),
+            heap: BinaryHeap::from([MinWeight(source, Score::Zero)]),
+            last: None,
+        }
+    }
+
+    /// The minimal score in the queue skipping the stale entries, `Score::Inf` if the queue is empty.
+    fn top(&mut self) -> Score<ScoreV> {
+        while let Some(MinWeight(id, score)) = self.heap.peek() {
+            if self.settled.contains(id) {
+                self.heap.pop();
+            } else {
+                return score.clone();
+            }
+        }
+        Score::Inf
+    }
+
+    /// Settles the node on the top of the queue. `top` needs to be called before.
+    fn settle(&mut self) -> &'a NId {
+        let MinWeight(id, _) = self.heap.pop().expect("the queue is not empty");
+        self.settled.insert(id);
+        self.last = Some(id);
+        id
+    }
+
+    /// Relaxes the edge and returns the score of the path through it.
+    fn relax(&mut self, from: &'a NId, to: &'a NId, w: ScoreV) -> Score<ScoreV> {
+        let alt = self.distance[from].add_score_v(w);
+        let improves = match self.distance.get(to) {
+            Some(d) => &alt < d,
+            None => true,
+        };
+        if improves && !self.settled.contains(to) {
+            self.distance.insert(to, alt.clone());
+            self.parents.insert(to, from);
+            self.heap.push(MinWeight(to, alt.clone()));
+        }
+        alt
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use crate::analyzer::bidirectional::BidirectionalDijkstra;
+    use crate::analyzer::dijkstra::DijkstraPath;
+    use crate::analyzer::min_weight::Score;
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+
+    #[test]
+    fn simple_test() {
+        let graph = digraph!((usize,_,usize) => [1,2,3,4,5,6,7] => {
+           1 => [(2,7),(3,9),(6,14)];
+           2 => [(3,10),(4,15)];
+           3 => [(4,11),(6,2)];
+           4 => (5,6);
+           6 => (5,9);
+        });
+        let res = BidirectionalDijkstra::new(&graph).on_edge(1, 5);
+        assert_eq!(res.path(), vec![1, 3, 6, 5]);
+        assert_eq!(res.cost(), &Score::Value(20));
+
+        let res = BidirectionalDijkstra::new(&graph).on_edge(1, 7);
+        assert_eq!(res.path(), Vec::<usize>::new());
+        assert_eq!(res.cost(), &Score::Inf);
+
+        let res = BidirectionalDijkstra::new(&graph).on_edge(3, 3);
+        assert_eq!(res.path(), vec![3]);
+        assert_eq!(res.cost(), &Score::Zero);
+
+        let res = BidirectionalDijkstra::new(&graph).on_edge_custom(1, 5, |_| 1);
+        assert_eq!(res.cost(), &Score::Value(2));
+        assert_eq!(res.path().len(), 3);
+    }
+
+    /// The grid of 6x6 nodes with the edges to the right and down in both directions
+    /// and the weights depending on the direction.
+    pub(crate) fn grid() -> DiGraph<usize, EmptyPayload, usize> {
+        let mut graph = DiGraph::new();
+        for id in 0..36 {
+            graph.add_node(id, EmptyPayload);
+        }
+        for id in 0..36 {
+            let neighbours = [(id % 6 < 5, id + 1), (id < 30, id + 6)];
+            for (_, n) in neighbours.into_iter().filter(|(ok, _)| *ok) {
+                graph.add_edge(id, n, (id * 7 + n * 3) % 9 + 1);
+                graph.add_edge(n, id, (id * 5 + n) % 4 + 1);
+            }
+        }
+        graph
+    }
+
+    #[test]
+    fn same_as_dijkstra_test() {
+        let graph = grid();
+        let bd = BidirectionalDijkstra::new(&graph);
+        for start in 0..36 {
+            let dijkstra = DijkstraPath::new(&graph).on_edge(start);
+            for target in 0..36 {
+                let res = bd.on_edge(start, target);
+                assert_eq!(res.cost(), &dijkstra.score(&target));
+                let path = res.path();
+                if let Score::Value(cost) = res.cost() {
+                    let total: usize = path
+                        .windows(2)
+                        .map(|w| graph.edge(&w[0], &w[1]).unwrap())
+                        .sum();
+                    assert_eq!(&total, cost);
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,531 @@
+//! Contraction hierarchies for repeated shortest path queries.
+//!
+//! The preprocessing contracts the nodes one by one in the order of importance:
+//! a contracted node is removed and the shortcuts are added between its neighbours
+//! unless a witness path(a path that is not longer and avoids the node) exists.
+//! The order is chosen by the edge difference(the added shortcuts minus the removed edges)
+//! and the number of the contracted neighbours, the priorities are updated lazily.
+//!
+//! A query runs the bidirectional Dijkstra that goes only upward(to the nodes contracted later):
+//! the forward search from the start over the outgoing edges and the backward search from the target
+//! over the incoming edges. The shortcuts of the found path are unpacked into the original edges.
+//!
+//! `ContractionIndex` does not borrow the graph and with the `serde` feature it can be serialized,
+//! thus it can be built once and reused. The weights need to be non-negative.
+use crate::analyzer::astar::MinPathStrict;
+use crate::analyzer::float::{check_weights, Float, FloatWeight, NaNWeight};
+use crate::analyzer::min_weight::{MinIndexed, Score};
+use crate::DiGraph;
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::convert::identity;
+use std::hash::Hash;
+use std::ops::Add;
+
+/// The number of the settled nodes after which the witness search gives up
+/// and the shortcut is added(it is always safe to add a shortcut).
+const WITNESS_LIMIT: usize = 64;
+
+/// The edge of the hierarchy. The shortcut keeps the contracted node it goes through.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+struct Link<ScoreV> {
+    node: usize,
+    weight: ScoreV,
+    via: Option<usize>,
+}
+
+/// The result of the preprocessing that answers the shortest path queries.
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct ContractionIndex<NId, ScoreV>
+where
+    NId: Eq + Hash,
+{
+    nodes: Vec<NId>,
+    index: HashMap<NId, usize>,
+    /// The outgoing edges to the nodes contracted later.
+    up: Vec<Vec<Link<ScoreV>>>,
+    /// The incoming edges from the nodes contracted later.
+    down: Vec<Vec<Link<ScoreV>>>,
+}
+
+/// The edges of the graph that is being contracted: the weight and the node of the shortcut.
+type Adjacency<ScoreV> = Vec<HashMap<usize, (ScoreV, Option<usize>)>>;
+
+#[derive(Debug)]
+pub struct ContractionHierarchy<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+}
+
+impl<'a, NId, NL, EL> ContractionHierarchy<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self { graph }
+    }
+}
+
+impl<'a, NId, NL, EL> ContractionHierarchy<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Ord + Add<Output = EL> + Clone + Default,
+{
+    pub fn on_edge(&self) -> ContractionIndex<NId, EL> {
+        self.on_edge_custom(identity)
+    }
+}
+
+impl<'a, NId, NL, EL> ContractionHierarchy<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: FloatWeight,
+{
+    /// The float weights version of `on_edge`(see `crate::analyzer::float`).
+    pub fn on_float_edge(&self) -> Result<ContractionIndex<NId, Float<EL>>, NaNWeight<NId>> {
+        check_weights(self.graph)?;
+        Ok(self.on_edge_custom(Float::unchecked))
+    }
+}
+
+impl<'a, NId, NL, EL> ContractionHierarchy<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Clone,
+{
+    /// Builds the index. The default value of the weight type is used as zero.
+    pub fn on_edge_custom<ScoreV, F>(&self, to_score: F) -> ContractionIndex<NId, ScoreV>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone + Default,
+    {
+        let nodes: Vec<NId> = self.graph.nodes.keys().cloned().collect();
+        let index: HashMap<NId, usize> = nodes
+            .iter()
+            .enumerate()
+            .map(|(i, id)| (id.clone(), i))
+            .collect();
+
+        let mut out: Adjacency<ScoreV> = vec![HashMap::new(); nodes.len()];
+        let mut inc: Adjacency<ScoreV> = vec![HashMap::new(); nodes.len()];
+        for (from, tos) in self.graph.edges.iter() {
+            let Some(&f) = index.get(from) else {
+                continue;
+            };
+            for (to, el) in tos.iter() {
+                match index.get(to) {
+                    Some(&t) if t != f => {
+                        insert(&mut out, &mut inc, f, t, to_score(el.clone()), None)
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        let mut contracted_neighbours = vec![0; nodes.len()];
+        let mut up = vec![vec![]; nodes.len()];
+        let mut down = vec![vec![]; nodes.len()];
+        let mut queue: BinaryHeap<Reverse<(i64, usize)>> = (0..nodes.len())
+            .map(|v| Reverse((priority(&out, &inc, &contracted_neighbours, v), v)))
+            .collect();
+
+        while let Some(Reverse((p, v))) = queue.pop() {
+            // the priorities are updated lazily: the node goes back if it is not the best anymore
+            let actual = priority(&out, &inc, &contracted_neighbours, v);
+            if actual > p {
+                if let Some(Reverse((next, _))) = queue.peek() {
+                    if actual > *next {
+                        queue.push(Reverse((actual, v)));
+                        continue;
+                    }
+                }
+            }
+            for (u, x, weight) in shortcuts(&out, &inc, v) {
+                insert(&mut out, &mut inc, u, x, weight, Some(v));
+            }
+            up[v] = links(&out[v]);
+            down[v] = links(&inc[v]);
+            for &x in out[v].keys() {
+                inc[x].remove(&v);
+                contracted_neighbours[x] += 1;
+            }
+            for &u in inc[v].keys() {
+                out[u].remove(&v);
+                contracted_neighbours[u] += 1;
+            }
+            out[v].clear();
+            inc[v].clear();
+        }
+
+        ContractionIndex {
+            nodes,
+            index,
+            up,
+            down,
+        }
+    }
+}
+
+impl<NId, ScoreV> ContractionIndex<NId, ScoreV>
+where
+    NId: Eq + Hash + Clone,
+    ScoreV: Ord + Add<Output = ScoreV> + Clone,
+{
+    /// The number of the edges in the hierarchy(the original edges and the shortcuts).
+    pub fn edges(&self) -> usize {
+        self.up.iter().chain(self.down.iter()).map(Vec::len).sum()
+    }
+
+    /// The length of the shortest path from one node to another.
+    pub fn distance(&self, from: &NId, to: &NId) -> Score<ScoreV> {
+        match (self.index.get(from), self.index.get(to)) {
+            (Some(&s), Some(&t)) => self
+                .search(s, t)
+                .map(|(d, _, _, _)| d)
+                .unwrap_or(Score::Inf),
+            _ => Score::Inf,
+        }
+    }
+
+    /// The shortest path with the cost. The shortcuts are unpacked into the original edges.
+    pub fn query(&self, from: NId, to: NId) -> MinPathStrict<NId, ScoreV> {
+        let (Some(&s), Some(&t)) = (self.index.get(&from), self.index.get(&to)) else {
+            return MinPathStrict::new(HashMap::new(), from, to, Score::Inf);
+        };
+        let Some((cost, meet, forward, backward)) = self.search(s, t) else {
+            return MinPathStrict::new(HashMap::new(), from, to, Score::Inf);
+        };
+
+        let mut line = vec![meet];
+        let mut cur = meet;
+        while let Some(&(prev, via)) = forward.get(&cur) {
+            self.unpack(prev, cur, via, &mut line);
+            cur = prev;
+        }
+        line.reverse();
+        let mut cur = meet;
+        while let Some(&(next, via)) = backward.get(&cur) {
+            let mut part = vec![next];
+            self.unpack(cur, next, via, &mut part);
+            // the part ends with the current node that is already in the line
+            line.extend(part.into_iter().rev().skip(1));
+            cur = next;
+        }
+
+        let path = line
+            .windows(2)
+            .map(|w| (self.nodes[w[1]].clone(), self.nodes[w[0]].clone()))
+            .collect();
+        MinPathStrict::new(path, from, to, cost)
+    }
+
+    /// Pushes the inner nodes of the edge from the end to the beginning followed by the beginning.
+    fn unpack(&self, from: usize, to: usize, via: Option<usize>, line: &mut Vec<usize>) {
+        match via {
+            Some(m) => {
+                self.unpack(m, to, self.via(m, to), line);
+                self.unpack(from, m, self.via(from, m), line);
+            }
+            None => line.push(from),
+        }
+    }
+
+    /// The node of the shortcut between the nodes(the edge goes upward from one of them).
+    fn via(&self, from: usize, to: usize) -> Option<usize> {
+        self.up[from]
+            .iter()
+            .find(|l| l.node == to)
+            .or_else(|| self.down[to].iter().find(|l| l.node == from))
+            .and_then(|l| l.via)
+    }
+
+    /// The upward bidirectional search. Returns the distance, the meeting node
+    /// and the parents of both directions with the nodes of the shortcuts.
+    fn search(&self, s: usize, t: usize) -> Option<(Score<ScoreV>, usize, Parents, Parents)> {
+        let mut forward = Upward::new(s);
+        let mut backward = Upward::new(t);
+        let mut best: Option<(Score<ScoreV>, usize)> = None;
+
+        loop {
+            let bound = best.as_ref().map(|(d, _)| d);
+            let f = forward.next(&self.up, bound);
+            let b = backward.next(&self.down, bound);
+            for v in f.into_iter().chain(b) {
+                if let (Some(df), Some(db)) = (forward.distance.get(&v), backward.distance.get(&v))
+                {
+                    let d = df.clone() + db.clone();
+                    match &best {
+                        Some((bd, _)) if bd <= &d => (),
+                        _ => best = Some((d, v)),
+                    }
+                }
+            }
+            if f.is_none() && b.is_none() {
+                break;
+            }
+        }
+        best.map(|(d, v)| (d, v, forward.parents, backward.parents))
+    }
+}
+
+/// The parents of the search: the previous node and the node of the shortcut.
+type Parents = HashMap<usize, (usize, Option<usize>)>;
+
+/// One direction of the upward search.
+struct Upward<ScoreV> {
+    distance: HashMap<usize, Score<ScoreV>>,
+    parents: Parents,
+    heap: BinaryHeap<MinIndexed<ScoreV>>,
+    settled: HashSet<usize>,
+}
+
+impl<ScoreV> Upward<ScoreV>
+where
+    ScoreV: Ord + Add<Output = ScoreV> + Clone,
+{
+    fn new(source: usize) -> Self {
+        Self {
+            distance: HashMap::from([(source, Score::Zero)]),
+            parents: HashMap::new(),
+            heap: BinaryHeap::from([MinIndexed(source, Score::Zero)]),
+            settled: HashSet::new(),
+        }
+    }
+
+    /// Settles the next node unless the queue is empty or the minimal score is not less than the bound.
+    fn next(
+        &mut self,
+        links: &[Vec<Link<ScoreV>>],
+        bound: Option<&Score<ScoreV>>,
+    ) -> Option<usize> {
+        while let Some(MinIndexed(v, score)) = self.heap.pop() {
+            if self.settled.contains(&v) {
+                continue;
+            }
+            if let Some(bound) = bound {
+                if &score >= bound {
+                    self.heap.clear();
+                    return None;
+                }
+            }
+            self.settled.insert(v);
+            for l in links[v].iter() {
+                let alt = score.add_score_v(l.weight.clone());
+                match self.distance.get(&l.node) {
+                    Some(d) if d <= &alt => (),
+                    _ => {
+                        self.distance.insert(l.node, alt.clone());
+                        self.parents.insert(l.node, (v, l.via));
+                        self.heap.push(MinIndexed(l.node, alt));
+                    }
+                }
+            }
+            return Some(v);
+        }
+        None
+    }
+}
+
+/// Adds the edge or decreases the weight of the existing one.
+fn insert<ScoreV>(
+    out: &mut Adjacency<ScoreV>,
+    inc: &mut Adjacency<ScoreV>,
+    from: usize,
+    to: usize,
+    weight: ScoreV,
+    via: Option<usize>,
+) where
+    ScoreV: Ord + Clone,
+{
+    match out[from].get(&to) {
+        Some((w, _)) if w <= &weight => (),
+        _ => {
+            out[from].insert(to, (weight.clone(), via));
+            inc[to].insert(from, (weight, via));
+        }
+    }
+}
+
+fn links<ScoreV: Clone>(edges: &HashMap<usize, (ScoreV, Option<usize>)>) -> Vec<Link<ScoreV>> {
+    edges
+        .iter()
+        .map(|(&node, (weight, via))| Link {
+            node,
+            weight: weight.clone(),
+            via: *via,
+        })
+        .collect()
+}
+
+/// The edge difference plus the number of the contracted neighbours.
+fn priority<ScoreV>(
+    out: &Adjacency<ScoreV>,
+    inc: &Adjacency<ScoreV>,
+    contracted_neighbours: &[i64],
+    v: usize,
+) -> i64
+where
+    ScoreV: Ord + Add<Output = ScoreV> + Clone + Default,
+{
+    let added = shortcuts(out, inc, v).len() as i64;
+    let removed = (out[v].len() + inc[v].len()) as i64;
+    added - removed + contracted_neighbours[v]
+}
+
+/// The shortcuts that are needed if the node is contracted.
+fn shortcuts<ScoreV>(
+    out: &Adjacency<ScoreV>,
+    inc: &Adjacency<ScoreV>,
+    v: usize,
+) -> Vec<(usize, usize, ScoreV)>
+where
+    ScoreV: Ord + Add<Output = ScoreV> + Clone + Default,
+{
+    let mut res = vec![];
+    for (&u, (w_in, _)) in inc[v].iter() {
+        let max = out[v]
+            .iter()
+            .filter(|(x, _)| **x != u)
+            .map(|(_, (w_out, _))| w_in.clone() + w_out.clone())
+            .max();
+        let Some(max) = max else {
+            continue;
+        };
+        let witness = witness_search(out, u, v, &max);
+        for (&x, (w_out, _)) in out[v].iter().filter(|(x, _)| **x != u) {
+            let via_v = w_in.clone() + w_out.clone();
+            match witness.get(&x) {
+                Some(d) if d <= &via_v => (),
+                _ => res.push((u, x, via_v)),
+            }
+        }
+    }
+    res
+}
+
+/// Dijkstra from the node avoiding the contracted one, limited by the distance and the settled nodes.
+fn witness_search<ScoreV>(
+    out: &Adjacency<ScoreV>,
+    source: usize,
+    avoid: usize,
+    max: &ScoreV,
+) -> HashMap<usize, ScoreV>
+where
+    ScoreV: Ord + Add<Output = ScoreV> + Clone + Default,
+{
+    let mut distance = HashMap::from([(source, ScoreV::default())]);
+    let mut heap = BinaryHeap::from([Reverse((ScoreV::default(), source))]);
+    let mut settled = 0;
+    while let Some(Reverse((d, v))) = heap.pop() {
+        if &d > max || settled >= WITNESS_LIMIT {
+            break;
+        }
+        if distance.get(&v).map(|best| best < &d).unwrap_or(false) {
+            continue;
+        }
+        settled += 1;
+        for (&x, (w, _)) in out[v].iter() {
+            if x == avoid {
+                continue;
+            }
+            let alt = d.clone() + w.clone();
+            match distance.get(&x) {
+                Some(best) if best <= &alt => (),
+                _ => {
+                    distance.insert(x, alt.clone());
+                    heap.push(Reverse((alt, x)));
+                }
+            }
+        }
+    }
+    distance
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::bidirectional::tests::grid;
+    use crate::analyzer::contraction::ContractionHierarchy;
+    use crate::analyzer::dijkstra::DijkstraPath;
+    use crate::analyzer::min_weight::Score;
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+
+    #[test]
+    fn simple_test() {
+        let graph = digraph!((usize,_,usize) => [1,2,3,4,5,6,7] => {
+           1 => [(2,7),(3,9),(6,14)];
+           2 => [(3,10),(4,15)];
+           3 => [(4,11),(6,2)];
+           4 => (5,6);
+           6 => (5,9);
+        });
+        let index = ContractionHierarchy::new(&graph).on_edge();
+        let res = index.query(1, 5);
+        assert_eq!(res.path(), vec![1, 3, 6, 5]);
+        assert_eq!(res.cost(), &Score::Value(20));
+        assert_eq!(index.distance(&2, &5), Score::Value(21));
+        assert_eq!(index.distance(&5, &1), Score::Inf);
+        assert_eq!(index.distance(&1, &7), Score::Inf);
+        assert_eq!(index.distance(&1, &42), Score::Inf);
+        assert_eq!(index.query(4, 4).path(), vec![4]);
+        assert_eq!(index.query(4, 4).cost(), &Score::Zero);
+    }
+
+    #[test]
+    fn same_as_dijkstra_test() {
+        let graph = grid();
+        let index = ContractionHierarchy::new(&graph).on_edge_custom(|w| w as u64);
+        for start in 0..36 {
+            let dijkstra = DijkstraPath::new(&graph).on_edge_custom(start, |w| w as u64);
+            for target in 0..36 {
+                let res = index.query(start, target);
+                assert_eq!(res.cost(), &dijkstra.score(&target));
+                let path = res.path();
+                assert_eq!(path.first(), Some(&start));
+                assert_eq!(path.last(), Some(&target));
+                let total: u64 = path
+                    .windows(2)
+                    .map(|w| *graph.edge(&w[0], &w[1]).unwrap() as u64)
+                    .sum();
+                match res.cost() {
+                    Score::Value(cost) => assert_eq!(&total, cost),
+                    _ => assert_eq!(total, 0),
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn serde_test() {
+        use crate::analyzer::contraction::ContractionIndex;
+        let graph = grid();
+        let index = ContractionHierarchy::new(&graph).on_edge();
+        let json = serde_json::to_string(&index).unwrap();
+        let restored: ContractionIndex<usize, usize> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.edges(), index.edges());
+        for (s, t) in [(0, 35), (35, 0), (7, 28)] {
+            assert_eq!(restored.query(s, t).path(), index.query(s, t).path());
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn float_serde_test() {
+        use crate::analyzer::contraction::ContractionIndex;
+        use crate::analyzer::float::Float;
+        let graph = digraph!((usize,_,f64) => [1,2,3] => {
+           1 => [(2,0.5),(3,2.0)];
+           2 => (3,0.25);
+        });
+        let index = ContractionHierarchy::new(&graph).on_float_edge().unwrap();
+        let json = serde_json::to_string(&index).unwrap();
+        let restored: ContractionIndex<usize, Float<f64>> = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.distance(&1, &3), index.distance(&1, &3));
+    }
+}
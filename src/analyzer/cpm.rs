@@ -0,0 +1,411 @@
+//! Critical path method(CPM/PERT) over a DAG of tasks.
+//!
+//! The edges are the dependencies: the task can start when all its predecessors are finished.
+//! The durations are given either for the nodes(the tasks) or for the edges(the lags between the tasks),
+//! both are combined as `earliest_start(v) = max(earliest_start(u) + duration(u) + lag(u, v))`.
+//! The latest start is computed backward from the length of the schedule,
+//! the slack is the difference between the latest and the earliest start.
+//! The tasks with zero slack are critical: any delay of them delays the whole schedule.
+use crate::analyzer::float::{check_weights, Float, FloatError, FloatWeight};
+use crate::analyzer::topo::{Cycle, TopologicalSort};
+use crate::visualizer::dot::{DotProcessor, ToStringProcessor};
+use crate::DiGraph;
+use graphviz_rust::attributes::{color_name, EdgeAttributes, NodeAttributes};
+use graphviz_rust::dot_structures::Stmt;
+use std::collections::{HashMap, HashSet};
+use std::convert::identity;
+use std::hash::Hash;
+use std::ops::{Add, Sub};
+
+/// The timing of a task.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Timing<ScoreV> {
+    earliest_start: ScoreV,
+    latest_start: ScoreV,
+    duration: ScoreV,
+}
+
+impl<ScoreV> Timing<ScoreV>
+where
+    ScoreV: Add<Output = ScoreV> + Sub<Output = ScoreV> + Clone,
+{
+    pub fn earliest_start(&self) -> &ScoreV {
+        &self.earliest_start
+    }
+    pub fn latest_start(&self) -> &ScoreV {
+        &self.latest_start
+    }
+    pub fn earliest_finish(&self) -> ScoreV {
+        self.earliest_start.clone() + self.duration.clone()
+    }
+    pub fn latest_finish(&self) -> ScoreV {
+        self.latest_start.clone() + self.duration.clone()
+    }
+    pub fn slack(&self) -> ScoreV {
+        self.latest_start.clone() - self.earliest_start.clone()
+    }
+}
+
+/// The result of CPM: the timings of all tasks and one of the critical paths.
+#[derive(Debug, Clone)]
+pub struct Schedule<NId, ScoreV>
+where
+    NId: Eq + Hash,
+{
+    timings: HashMap<NId, Timing<ScoreV>>,
+    length: ScoreV,
+    critical_path: Vec<NId>,
+}
+
+impl<NId, ScoreV> Schedule<NId, ScoreV>
+where
+    NId: Eq + Hash,
+    ScoreV: Ord + Add<Output = ScoreV> + Sub<Output = ScoreV> + Clone + Default,
+{
+    /// The time when all tasks are finished.
+    pub fn length(&self) -> &ScoreV {
+        &self.length
+    }
+    pub fn timing(&self, id: &NId) -> Option<&Timing<ScoreV>> {
+        self.timings.get(id)
+    }
+    pub fn timings(&self) -> &HashMap<NId, Timing<ScoreV>> {
+        &self.timings
+    }
+    pub fn is_critical(&self, id: &NId) -> bool {
+        self.timings
+            .get(id)
+            .map(|t| t.slack() == ScoreV::default())
+            .unwrap_or(false)
+    }
+    /// The chain of the critical tasks from a task without dependencies
+    /// to a task finishing at the end of the schedule(the longest path).
+    pub fn critical_path(&self) -> &Vec<NId> {
+        &self.critical_path
+    }
+}
+
+impl<NId, F> Schedule<NId, Float<F>>
+where
+    NId: Eq + Hash,
+    F: FloatWeight,
+{
+    /// The same as `is_critical` but the slack within the tolerance is zero(see `Float::approx_eq`),
+    /// the float sums are rounded thus the slack of a critical task can be slightly off the zero.
+    pub fn is_critical_with_tolerance(&self, id: &NId, tolerance: F) -> bool {
+        self.timings
+            .get(id)
+            .map(|t| t.slack().approx_eq(&Float::default(), tolerance))
+            .unwrap_or(false)
+    }
+}
+
+#[derive(Debug)]
+pub struct CriticalPathMethod<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+}
+
+impl<'a, NId, NL, EL> CriticalPathMethod<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self { graph }
+    }
+
+    /// The durations of the tasks are given by the node payloads, the edges have no lags.
+    pub fn on_node_custom<ScoreV, F>(
+        &self,
+        duration: F,
+    ) -> Result<Schedule<NId, ScoreV>, Cycle<'a, NId>>
+    where
+        F: Fn(&NL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Sub<Output = ScoreV> + Clone + Default,
+    {
+        self.process(|id| duration(&self.graph.nodes[id]), |_| ScoreV::default())
+    }
+}
+
+impl<'a, NId, NL, EL> CriticalPathMethod<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Ord + Add<Output = EL> + Sub<Output = EL> + Clone + Default,
+{
+    pub fn on_edge(&self) -> Result<Schedule<NId, EL>, Cycle<'a, NId>> {
+        self.on_edge_custom(identity)
+    }
+}
+
+impl<'a, NId, NL, EL> CriticalPathMethod<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: FloatWeight,
+{
+    /// The float durations version of `on_edge`(see `crate::analyzer::float`).
+    pub fn on_float_edge(
+        &self,
+    ) -> Result<Schedule<NId, Float<EL>>, FloatError<NId, Cycle<'a, NId>>> {
+        check_weights(self.graph)?;
+        self.on_edge_custom(Float::unchecked)
+            .map_err(FloatError::Failed)
+    }
+}
+
+impl<'a, NId, NL, EL> CriticalPathMethod<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Clone,
+{
+    /// The durations are given by the edge payloads(the nodes are the events with zero duration).
+    pub fn on_edge_custom<ScoreV, F>(
+        &self,
+        to_score: F,
+    ) -> Result<Schedule<NId, ScoreV>, Cycle<'a, NId>>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Sub<Output = ScoreV> + Clone + Default,
+    {
+        self.process(|_| ScoreV::default(), |el: &EL| to_score(el.clone()))
+    }
+}
+
+impl<'a, NId, NL, EL> CriticalPathMethod<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    fn process<ScoreV, D, L>(
+        &self,
+        duration: D,
+        lag: L,
+    ) -> Result<Schedule<NId, ScoreV>, Cycle<'a, NId>>
+    where
+        D: Fn(&NId) -> ScoreV,
+        L: Fn(&EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Sub<Output = ScoreV> + Clone + Default,
+    {
+        let order = TopologicalSort::new(self.graph).kahn()?;
+        let durations: HashMap<&NId, ScoreV> = order.iter().map(|id| (*id, duration(id))).collect();
+        let successors = |id: &'a NId| {
+            self.graph
+                .edges
+                .get(id)
+                .into_iter()
+                .flatten()
+                .filter(|(to, _)| self.graph.nodes.contains_key(to))
+        };
+
+        // forward: the earliest start and the predecessor defining it
+        let mut earliest: HashMap<&NId, ScoreV> = HashMap::new();
+        let mut parents: HashMap<&NId, &NId> = HashMap::new();
+        for from in order.iter() {
+            let es = earliest.entry(from).or_default().clone();
+            let ef = es + durations[from].clone();
+            for (to, el) in successors(from) {
+                let alt = ef.clone() + lag(el);
+                match earliest.get(to) {
+                    Some(current) if current >= &alt => (),
+                    _ => {
+                        earliest.insert(to, alt);
+                        parents.insert(to, from);
+                    }
+                }
+            }
+        }
+
+        let finish = |id: &NId| earliest[id].clone() + durations[id].clone();
+        let last = order
+            .iter()
+            .fold(None, |last: Option<&&NId>, id| match last {
+                Some(l) if finish(l) >= finish(id) => Some(l),
+                _ => Some(id),
+            });
+        let length = last.map(|id| finish(id)).unwrap_or_default();
+
+        // backward: the latest start that does not delay the successors
+        let mut latest: HashMap<&NId, ScoreV> = HashMap::new();
+        for from in order.iter().rev() {
+            let lf = successors(from)
+                .map(|(to, el)| latest[to].clone() - lag(el))
+                .min()
+                .unwrap_or_else(|| length.clone());
+            latest.insert(from, lf - durations[from].clone());
+        }
+
+        let mut critical_path = vec![];
+        let mut cur = last;
+        while let Some(id) = cur {
+            critical_path.push((*id).clone());
+            cur = parents.get(id);
+        }
+        critical_path.reverse();
+
+        let timings = order
+            .into_iter()
+            .map(|id| {
+                let timing = Timing {
+                    earliest_start: earliest[id].clone(),
+                    latest_start: latest[id].clone(),
+                    duration: durations[id].clone(),
+                };
+                (id.clone(), timing)
+            })
+            .collect();
+        Ok(Schedule {
+            timings,
+            length,
+            critical_path,
+        })
+    }
+}
+
+/// Renders the schedule: every node gets the label `es/ls/slack`,
+/// the critical tasks are red and the edges of the critical path are red and bold.
+pub struct ScheduleHighlighter<'s, NId, ScoreV>
+where
+    NId: Eq + Hash,
+{
+    schedule: &'s Schedule<NId, ScoreV>,
+    critical_edges: HashSet<(&'s NId, &'s NId)>,
+    delegate: ToStringProcessor,
+}
+
+impl<'s, NId, ScoreV> ScheduleHighlighter<'s, NId, ScoreV>
+where
+    NId: Eq + Hash,
+{
+    pub fn new(schedule: &'s Schedule<NId, ScoreV>) -> Self {
+        let critical_edges = schedule
+            .critical_path
+            .windows(2)
+            .map(|w| (&w[0], &w[1]))
+            .collect();
+        Self {
+            schedule,
+            critical_edges,
+            delegate: ToStringProcessor {},
+        }
+    }
+}
+
+impl<'a, 's, NId, NL, EL, ScoreV> DotProcessor<'a, NId, NL, EL>
+    for ScheduleHighlighter<'s, NId, ScoreV>
+where
+    NId: Eq + Hash + ToString,
+    NL: ToString,
+    EL: ToString,
+    ScoreV: Ord + Add<Output = ScoreV> + Sub<Output = ScoreV> + Clone + Default + ToString,
+{
+    fn node(&self, id: &'a NId, nl: &'a NL) -> Stmt {
+        let Some(timing) = self.schedule.timing(id) else {
+            return (&self.delegate as &dyn DotProcessor<NId, NL, EL>).node(id, nl);
+        };
+        let xlabel = format!(
+            "\"{}/{}/{}\"",
+            timing.earliest_start().to_string(),
+            timing.latest_start().to_string(),
+            timing.slack().to_string()
+        );
+        let mut attrs = vec![NodeAttributes::xlabel(xlabel)];
+        if self.schedule.is_critical(id) {
+            attrs.push(NodeAttributes::color(color_name::red));
+        }
+        self.delegate.node_with_attrs(id, nl, attrs)
+    }
+
+    fn edge(&self, from: &'a NId, to: &'a NId, el: &'a EL) -> Stmt {
+        if self.critical_edges.contains(&(from, to)) {
+            let red = EdgeAttributes::color(color_name::red);
+            let bold = EdgeAttributes::penwidth(2.0);
+            self.delegate.edge_with_attrs(from, to, el, vec![red, bold])
+        } else {
+            (&self.delegate as &dyn DotProcessor<NId, NL, EL>).edge(from, to, el)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::cpm::{CriticalPathMethod, ScheduleHighlighter};
+    use crate::visualizer::vis;
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+
+    #[test]
+    fn on_node_test() {
+        // the tasks with the durations: a build pipeline
+        let graph = digraph!((&str,usize,EmptyPayload) => [("fetch",2),("deps",5),("lint",1),("build",4),("test",3),("pack",1)] => {
+           "fetch" => ["deps","lint"];
+           "deps" => ["build"];
+           "lint" => ["pack"];
+           "build" => ["test","pack"];
+           "test" => ["pack"];
+        });
+        let schedule = CriticalPathMethod::new(&graph)
+            .on_node_custom(|d: &usize| *d)
+            .unwrap();
+        assert_eq!(schedule.length(), &15);
+        assert_eq!(
+            schedule.critical_path(),
+            &vec!["fetch", "deps", "build", "test", "pack"]
+        );
+        let lint = schedule.timing(&"lint").unwrap();
+        assert_eq!(lint.earliest_start(), &2);
+        assert_eq!(lint.latest_start(), &13);
+        assert_eq!(lint.slack(), 11);
+        assert_eq!(lint.earliest_finish(), 3);
+        let test = schedule.timing(&"test").unwrap();
+        assert_eq!((test.earliest_start(), test.slack()), (&11, 0));
+        assert!(schedule.is_critical(&"build"));
+        assert!(!schedule.is_critical(&"lint"));
+    }
+
+    #[test]
+    fn on_edge_test() {
+        let graph = digraph!((usize,_,i64) => [1,2,3,4] => {
+           1 => [(2,3),(3,2)];
+           2 => (4,4);
+           3 => (4,1);
+        });
+        let schedule = CriticalPathMethod::new(&graph).on_edge().unwrap();
+        assert_eq!(schedule.length(), &7);
+        assert_eq!(schedule.critical_path(), &vec![1, 2, 4]);
+        assert_eq!(schedule.timing(&3).unwrap().slack(), 4);
+        assert_eq!(schedule.timing(&4).unwrap().latest_start(), &7);
+
+        let dot = vis(graph
+            .visualize()
+            .to_dot(ScheduleHighlighter::new(&schedule)));
+        assert!(dot.contains("1 -> 2 [color=red,penwidth=2,label=3]"));
+        assert!(dot.contains("1 -> 3 [label=2]"));
+        assert!(dot.contains("3[xlabel=\"2/6/4\",label=\"3\"]"));
+        assert!(dot.contains("4[xlabel=\"7/7/0\",color=red,label=\"4\"]"));
+
+        let graph = digraph!((usize,_,i64) => [1,2] => {
+           1 => (2,1);
+           2 => (1,1);
+        });
+        assert!(CriticalPathMethod::new(&graph).on_edge().is_err());
+    }
+
+    #[test]
+    fn float_tolerance_test() {
+        // both ways take 0.3 but 0.1 + 0.2 is not 0.3 after rounding(nor 0.1 + 0.2 - 0.2 is 0.1),
+        // thus neither a nor b has the exact zero slack
+        let graph = digraph!((&str,EmptyPayload,f64) => ["start","a","b","end"] => {
+           "start" => [("a",0.1),("b",0.3)];
+           "a" => ("end",0.2);
+           "b" => ("end",0.0);
+        });
+        let schedule = CriticalPathMethod::new(&graph).on_float_edge().unwrap();
+        assert!(schedule.is_critical(&"end"));
+        assert!(!schedule.is_critical(&"a"));
+        assert!(!schedule.is_critical(&"b"));
+        assert!(schedule.is_critical_with_tolerance(&"a", 1e-9));
+        assert!(schedule.is_critical_with_tolerance(&"b", 1e-9));
+        assert!(!schedule.is_critical_with_tolerance(&"missing", 1e-9));
+    }
+}
@@ -0,0 +1,223 @@
+//! Shortest and longest paths in a directed acyclic graph.
+//!
+//! The nodes are relaxed once in the topological order, thus it takes linear time
+//! and the weights can be negative. The longest path is the critical path of a schedule(see `cpm`).
+//! If the graph has a cycle the error `Cycle` is returned(even if the cycle is not reachable from the start).
+//!
+//! `Score::Zero` is less than any value, thus it cannot be compared with negative weights
+//! and the default value of the weight type is used as zero(as in `bellman_ford`).
+use crate::analyzer::dijkstra::MinPath;
+use crate::analyzer::float::{check_weights, Float, FloatError, FloatWeight};
+use crate::analyzer::min_weight::Score;
+use crate::analyzer::topo::{Cycle, TopologicalSort};
+use crate::DiGraph;
+use std::collections::HashMap;
+use std::convert::identity;
+use std::hash::Hash;
+use std::ops::Add;
+
+#[derive(Debug)]
+pub struct DagPath<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+}
+
+impl<'a, NId, NL, EL> DagPath<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self { graph }
+    }
+}
+
+impl<'a, NId, NL, EL> DagPath<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Ord + Add<Output = EL> + Clone + Default,
+{
+    pub fn shortest(&self, start: NId) -> Result<MinPath<NId, EL>, Cycle<'a, NId>> {
+        self.shortest_custom(start, identity)
+    }
+
+    pub fn longest(&self, start: NId) -> Result<MinPath<NId, EL>, Cycle<'a, NId>> {
+        self.longest_custom(start, identity)
+    }
+}
+
+impl<'a, NId, NL, EL> DagPath<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: FloatWeight,
+{
+    /// The float weights version of `shortest`(see `crate::analyzer::float`).
+    pub fn shortest_float(
+        &self,
+        start: NId,
+    ) -> Result<MinPath<NId, Float<EL>>, FloatError<NId, Cycle<'a, NId>>> {
+        check_weights(self.graph)?;
+        self.shortest_custom(start, Float::unchecked)
+            .map_err(FloatError::Failed)
+    }
+
+    /// The float weights version of `longest`(see `crate::analyzer::float`).
+    pub fn longest_float(
+        &self,
+        start: NId,
+    ) -> Result<MinPath<NId, Float<EL>>, FloatError<NId, Cycle<'a, NId>>> {
+        check_weights(self.graph)?;
+        self.longest_custom(start, Float::unchecked)
+            .map_err(FloatError::Failed)
+    }
+}
+
+impl<'a, NId, NL, EL> DagPath<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Clone,
+{
+    pub fn shortest_custom<ScoreV, F>(
+        &self,
+        start: NId,
+        to_score: F,
+    ) -> Result<MinPath<NId, ScoreV>, Cycle<'a, NId>>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone + Default,
+    {
+        self.process(start, to_score, |alt, current| alt < current)
+    }
+
+    /// The same as `shortest_custom` but the path with the maximal total weight is taken,
+    /// `MinPath::score` is the length of the longest path.
+    pub fn longest_custom<ScoreV, F>(
+        &self,
+        start: NId,
+        to_score: F,
+    ) -> Result<MinPath<NId, ScoreV>, Cycle<'a, NId>>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone + Default,
+    {
+        self.process(start, to_score, |alt, current| alt > current)
+    }
+
+    fn process<ScoreV, F, B>(
+        &self,
+        start: NId,
+        to_score: F,
+        better: B,
+    ) -> Result<MinPath<NId, ScoreV>, Cycle<'a, NId>>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone + Default,
+        B: Fn(&ScoreV, &ScoreV) -> bool,
+    {
+        let order = TopologicalSort::new(self.graph).kahn()?;
+        let mut distance: HashMap<&NId, ScoreV> = HashMap::new();
+        let mut path: HashMap<NId, NId> = HashMap::new();
+        if let Some((start, _)) = self.graph.nodes.get_key_value(&start) {
+            distance.insert(start, ScoreV::default());
+        }
+
+        for from in order {
+            let Some(d_from) = distance.get(from).cloned() else {
+                continue;
+            };
+            for (to, el) in self.graph.edges.get(from).into_iter().flatten() {
+                if !self.graph.nodes.contains_key(to) {
+                    continue;
+                }
+                let alt = d_from.clone() + to_score(el.clone());
+                let improves = match distance.get(to) {
+                    Some(current) => better(&alt, current),
+                    None => true,
+                };
+                if improves {
+                    distance.insert(to, alt);
+                    path.insert(to.clone(), from.clone());
+                }
+            }
+        }
+
+        let distance = self
+            .graph
+            .nodes
+            .keys()
+            .map(|id| {
+                let score = match distance.remove(id) {
+                    _ if id == &start => Score::Zero,
+                    Some(d) => Score::Value(d),
+                    None => Score::Inf,
+                };
+                (id.clone(), score)
+            })
+            .collect();
+        Ok(MinPath::new(start, distance, path))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::dag_path::DagPath;
+    use crate::analyzer::float::{Float, FloatError};
+    use crate::analyzer::min_weight::Score;
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+
+    #[test]
+    fn shortest_longest_test() {
+        let graph = digraph!((usize,_,i64) => [1,2,3,4,5,6] => {
+           1 => [(2,5),(3,3)];
+           2 => [(3,2),(4,6)];
+           3 => [(4,7),(5,4),(6,2)];
+           4 => [(5,-1),(6,1)];
+           5 => (6,-2);
+        });
+        let shortest = DagPath::new(&graph).shortest(2).unwrap();
+        assert_eq!(shortest.score(&2), Score::Zero);
+        assert_eq!(shortest.score(&6), Score::Value(3));
+        assert_eq!(shortest.trail(&6), Some(vec![2, 4, 5, 6]));
+        assert_eq!(shortest.score(&1), Score::Inf);
+
+        let longest = DagPath::new(&graph).longest(1).unwrap();
+        assert_eq!(longest.score(&4), Score::Value(14));
+        assert_eq!(longest.score(&6), Score::Value(15));
+        assert_eq!(longest.trail(&6), Some(vec![1, 2, 3, 4, 6]));
+
+        let hops = DagPath::new(&graph).longest_custom(1, |_| 1).unwrap();
+        assert_eq!(hops.score(&6), Score::Value(5));
+    }
+
+    #[test]
+    fn cycle_test() {
+        let graph = digraph!((usize,_,i64) => [1,2,3,4] => {
+           1 => (2,1);
+           3 => (4,1);
+           4 => (3,1);
+        });
+        let cycle = DagPath::new(&graph).longest(1).unwrap_err();
+        assert_eq!(cycle.nodes().len(), 2);
+    }
+
+    #[test]
+    fn float_negative_test() {
+        let mut graph = digraph!((usize,_,f64) => [1,2,3,4] => {
+           1 => [(2,1.5),(3,0.5)];
+           2 => (4,-0.25);
+           3 => (4,0.5);
+        });
+        let shortest = DagPath::new(&graph).shortest_float(1).unwrap();
+        assert_eq!(shortest.score(&4), Score::Value(Float::unchecked(1.0)));
+        assert_eq!(shortest.trail(&4), Some(vec![1, 3, 4]));
+        let longest = DagPath::new(&graph).longest_float(1).unwrap();
+        assert_eq!(longest.score(&4), Score::Value(Float::unchecked(1.25)));
+
+        graph.add_edge(4, 1, 1.0);
+        let err = DagPath::new(&graph).longest_float(1).unwrap_err();
+        assert!(matches!(err, FloatError::Failed(_)));
+    }
+}
@@ -0,0 +1,396 @@
+//! Iterative-deepening searches.
+//!
+//! The searches keep only the current path in memory(`O(depth)`) instead of the whole frontier
+//! like `fs::BFS`, at the cost of expanding the shallow nodes again on every iteration.
+//! The nodes are not marked as visited globally, only the nodes of the current path are skipped
+//! to avoid cycles, thus the nodes reachable in several ways are expanded several times.
+//! Every search reports the found path and the number of expanded nodes(summed over the iterations).
+use crate::analyzer::fs::Found;
+use crate::analyzer::min_weight::Score;
+use crate::analyzer::SearchRes;
+use crate::DiGraph;
+use std::collections::hash_map::{Iter, Keys};
+use std::collections::HashSet;
+use std::convert::identity;
+use std::hash::Hash;
+use std::ops::Add;
+
+/// The result of the search.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Deepened<'a, NId> {
+    found: Option<Found<'a, NId>>,
+    expanded: usize,
+}
+
+impl<'a, NId> Deepened<'a, NId> {
+    pub fn found(&self) -> Option<&Found<'a, NId>> {
+        self.found.as_ref()
+    }
+    /// The path from the start to the found node(both ends are included).
+    pub fn path(&self) -> Option<&Vec<&'a NId>> {
+        self.found.as_ref().map(|f| f.path())
+    }
+    /// The number of nodes whose successors were generated.
+    pub fn expanded(&self) -> usize {
+        self.expanded
+    }
+}
+
+/// The path of IDA* with the costs from the start and the successors that are not explored yet.
+type CostStack<'a, NId, EL, ScoreV> = Vec<(&'a NId, Score<ScoreV>, Option<Iter<'a, NId, EL>>)>;
+
+/// The way a bounded iteration has ended.
+enum Outcome<'a, NId> {
+    Found(Vec<&'a NId>),
+    /// Some nodes were not expanded because of the bound.
+    Cutoff,
+    /// The whole search space has been explored.
+    Exhausted,
+    Stopped,
+}
+
+pub struct DeepeningSearch<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+    max_depth: Option<usize>,
+}
+
+impl<'a, NId, NL, EL> DeepeningSearch<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+{
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self {
+            graph,
+            max_depth: None,
+        }
+    }
+
+    /// Iterative deepening stops after the given depth(the number of edges from the start).
+    /// Without it the search goes on until every simple path from the start is explored.
+    pub fn with_max_depth(self, max_depth: usize) -> Self {
+        Self {
+            max_depth: Some(max_depth),
+            ..self
+        }
+    }
+
+    /// Depth-first search that does not go deeper than the limit.
+    pub fn depth_limited<S>(&self, start: &'a NId, limit: usize, target: S) -> Deepened<'a, NId>
+    where
+        S: Fn(&'a NId) -> SearchRes,
+    {
+        let mut expanded = 0;
+        let found = match self.limited(start, limit, &target, &mut expanded) {
+            Outcome::Found(path) => Some(Found::new(path)),
+            _ => None,
+        };
+        Deepened { found, expanded }
+    }
+
+    /// Iterative-deepening depth-first search: the depth-limited search with the limits 0, 1, 2...
+    /// The found path is the shortest one(in terms of the number of edges).
+    pub fn iddfs<S>(&self, start: &'a NId, target: S) -> Deepened<'a, NId>
+    where
+        S: Fn(&'a NId) -> SearchRes,
+    {
+        let mut expanded = 0;
+        let mut limit = 0;
+        let found = loop {
+            match self.limited(start, limit, &target, &mut expanded) {
+                Outcome::Found(path) => break Some(Found::new(path)),
+                Outcome::Cutoff if self.max_depth.map(|max| limit < max).unwrap_or(true) => {
+                    limit += 1
+                }
+                _ => break None,
+            }
+        };
+        Deepened { found, expanded }
+    }
+
+    pub fn iddfs_by_eq(&self, start: &'a NId, target: &'a NId) -> Deepened<'a, NId> {
+        self.iddfs(start, |n| {
+            if target == n {
+                SearchRes::Find
+            } else {
+                SearchRes::Next
+            }
+        })
+    }
+
+    /// IDA*: the iterative deepening over the estimated cost `g + h` instead of the depth.
+    /// The heuristic is the same as for `AStarPath` and it needs to be admissible
+    /// to get the path with the minimal cost.
+    pub fn ida_star_custom<H, E, ScoreV>(
+        &self,
+        start: &'a NId,
+        target: &'a NId,
+        heuristic: H,
+        edge_w: E,
+    ) -> Deepened<'a, NId>
+    where
+        H: Fn(&NId) -> ScoreV,
+        E: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone,
+        EL: Clone,
+    {
+        let mut expanded = 0;
+        let mut bound = Score::Value(heuristic(start));
+        let found = loop {
+            match self.bounded(start, target, &bound, &heuristic, &edge_w, &mut expanded) {
+                (Outcome::Found(path), _) => break Some(Found::new(path)),
+                (Outcome::Cutoff, Some(next)) => bound = next,
+                _ => break None,
+            }
+        };
+        Deepened { found, expanded }
+    }
+
+    /// One iteration of iddfs. The target is checked when a node is generated.
+    fn limited<S>(
+        &self,
+        start: &'a NId,
+        limit: usize,
+        target: &S,
+        expanded: &mut usize,
+    ) -> Outcome<'a, NId>
+    where
+        S: Fn(&'a NId) -> SearchRes,
+    {
+        match target(start) {
+            SearchRes::Find => return Outcome::Found(vec![start]),
+            SearchRes::Stop => return Outcome::Stopped,
+            SearchRes::Skip => return Outcome::Exhausted,
+            SearchRes::Next => (),
+        }
+        let mut on_path = HashSet::from([start]);
+        let mut cutoff = false;
+        let mut stack: Vec<(&NId, Option<Keys<NId, EL>>)> = vec![];
+        if limit > 0 {
+            *expanded += 1;
+            stack.push((start, self.graph.edges.get(start).map(|ss| ss.keys())));
+        } else {
+            cutoff = self.has_successors(start, &on_path);
+        }
+
+        while let Some((_, succ)) = stack.last_mut() {
+            let Some(s) = succ.as_mut().and_then(|ss| ss.next()) else {
+                let (node, _) = stack.pop().expect("the stack is not empty");
+                on_path.remove(node);
+                continue;
+            };
+            if on_path.contains(s) {
+                continue;
+            }
+            match target(s) {
+                SearchRes::Find => {
+                    let mut path: Vec<&NId> = stack.iter().map(|(n, _)| *n).collect();
+                    path.push(s);
+                    return Outcome::Found(path);
+                }
+                SearchRes::Stop => return Outcome::Stopped,
+                SearchRes::Skip => (),
+                SearchRes::Next if stack.len() < limit => {
+                    *expanded += 1;
+                    on_path.insert(s);
+                    stack.push((s, self.graph.edges.get(s).map(|ss| ss.keys())));
+                }
+                SearchRes::Next => cutoff = cutoff || self.has_successors(s, &on_path),
+            }
+        }
+        if cutoff {
+            Outcome::Cutoff
+        } else {
+            Outcome::Exhausted
+        }
+    }
+
+    /// One iteration of IDA*. Returns the minimal estimation exceeding the bound as well.
+    fn bounded<H, E, ScoreV>(
+        &self,
+        start: &'a NId,
+        target: &'a NId,
+        bound: &Score<ScoreV>,
+        heuristic: &H,
+        edge_w: &E,
+        expanded: &mut usize,
+    ) -> (Outcome<'a, NId>, Option<Score<ScoreV>>)
+    where
+        H: Fn(&NId) -> ScoreV,
+        E: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone,
+        EL: Clone,
+    {
+        if start == target {
+            return (Outcome::Found(vec![start]), None);
+        }
+        let mut next_bound: Option<Score<ScoreV>> = None;
+        let mut on_path = HashSet::from([start]);
+        let mut stack: CostStack<NId, EL, ScoreV> = vec![];
+        *expanded += 1;
+        stack.push((
+            start,
+            Score::Zero,
+            self.graph.edges.get(start).map(|ss| ss.iter()),
+        ));
+
+        while let Some((_, score, succ)) = stack.last_mut() {
+            let Some((s, el)) = succ.as_mut().and_then(|ss| ss.next()) else {
+                let (node, _, _) = stack.pop().expect("the stack is not empty");
+                on_path.remove(node);
+                continue;
+            };
+            if on_path.contains(s) {
+                continue;
+            }
+            let score = score.clone().add_score_v(edge_w(el.clone()));
+            let estimation = score.clone().add_score_v(heuristic(s));
+            if &estimation > bound {
+                if next_bound.as_ref().map(|b| &estimation < b).unwrap_or(true) {
+                    next_bound = Some(estimation);
+                }
+                continue;
+            }
+            if s == target {
+                let mut path: Vec<&NId> = stack.iter().map(|(n, _, _)| *n).collect();
+                path.push(s);
+                return (Outcome::Found(path), None);
+            }
+            *expanded += 1;
+            on_path.insert(s);
+            stack.push((s, score, self.graph.edges.get(s).map(|ss| ss.iter())));
+        }
+        match next_bound {
+            Some(_) => (Outcome::Cutoff, next_bound),
+            None => (Outcome::Exhausted, None),
+        }
+    }
+
+    fn has_successors(&self, id: &NId, on_path: &HashSet<&NId>) -> bool {
+        self.graph
+            .edges
+            .get(id)
+            .map(|ss| ss.keys().any(|s| s != id && !on_path.contains(s)))
+            .unwrap_or(false)
+    }
+}
+
+impl<'a, NId, NL, EL> DeepeningSearch<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+    EL: Ord + Add<Output = EL> + Clone,
+{
+    /// IDA* where the payloads of the edges are the weights.
+    pub fn ida_star<H>(&self, start: &'a NId, target: &'a NId, heuristic: H) -> Deepened<'a, NId>
+    where
+        H: Fn(&NId) -> EL,
+    {
+        self.ida_star_custom(start, target, heuristic, identity)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::deepening::DeepeningSearch;
+    use crate::analyzer::dijkstra::DijkstraPath;
+    use crate::analyzer::SearchRes;
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+
+    #[test]
+    fn iddfs_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3,4,5,6,7] => {
+            1 => [2,3];
+            2 => [4,1];
+            3 => 5;
+            4 => 6;
+            5 => 6;
+            6 => 7;
+        });
+        let search = DeepeningSearch::new(&graph);
+        let res = search.iddfs_by_eq(&1, &7);
+        assert_eq!(res.found().unwrap().depth(), 4);
+        assert_eq!(res.path().unwrap()[0], &1);
+        assert_eq!(res.path().unwrap()[4], &7);
+        // the limits 1, 2 and 3 expand 1, 3 and 5 nodes
+        assert!(res.expanded() > 9);
+
+        let res = search.iddfs_by_eq(&1, &1);
+        assert_eq!(res.path(), Some(&vec![&1]));
+        assert_eq!(res.expanded(), 0);
+
+        // the search space is exhausted: 1 -> 2 -> 1 is not followed
+        let res = search.iddfs_by_eq(&2, &42);
+        assert_eq!(res.found(), None);
+        let res = search.iddfs(&1, |n| {
+            if *n == 3 {
+                SearchRes::Skip
+            } else if *n == 5 {
+                SearchRes::Find
+            } else {
+                SearchRes::Next
+            }
+        });
+        assert_eq!(res.found(), None);
+
+        let res = search.with_max_depth(3).iddfs_by_eq(&1, &7);
+        assert_eq!(res.found(), None);
+    }
+
+    #[test]
+    fn depth_limited_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3,4] => {
+            1 => 2;
+            2 => 3;
+            3 => 4;
+        });
+        let search = graph.analyze().deepening();
+        let eq = |t: usize| {
+            move |n: &usize| {
+                if *n == t {
+                    SearchRes::Find
+                } else {
+                    SearchRes::Next
+                }
+            }
+        };
+        let res = search.depth_limited(&1, 2, eq(4));
+        assert_eq!(res.found(), None);
+        assert_eq!(res.expanded(), 2);
+
+        let res = search.depth_limited(&1, 3, eq(4));
+        assert_eq!(res.path(), Some(&vec![&1, &2, &3, &4]));
+        assert_eq!(res.expanded(), 3);
+    }
+
+    #[test]
+    fn ida_star_test() {
+        let graph = digraph!((usize,_,usize) => [1,2,3,4,5,6,7,8,9,10,11] => {
+           1 => [(2,1),(3,1)];
+           2 => (4,2);
+           3 => (5,3);
+           [4,5] => (6,1);
+           5 => (11,4);
+           6 => [(7,1),(1,1)];
+           7 => [(8,1),(9,2),(10,3)];
+           [8,9,10] => (11,1)
+        });
+        let search = DeepeningSearch::new(&graph);
+        let res = search.ida_star(&1, &11, |_| 0);
+        let dijkstra = DijkstraPath::new(&graph).on_edge(1).trail(&11).unwrap();
+        let cost = |path: &Vec<&usize>| -> usize {
+            path.windows(2)
+                .map(|w| *graph.edge(w[0], w[1]).unwrap())
+                .sum()
+        };
+        let path = res.path().unwrap();
+        assert_eq!(cost(path), 7);
+        assert_eq!(cost(path), cost(&dijkstra.iter().collect::<Vec<&usize>>()));
+
+        let res = search.ida_star_custom(&11, &1, |_| 0, |w| w);
+        assert_eq!(res.found(), None);
+    }
+}
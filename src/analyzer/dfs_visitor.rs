@@ -0,0 +1,239 @@
+//! Event-driven depth-first search.
+//!
+//! The search reports the events to a visitor(any `FnMut(DfsEvent) -> Control` works as well):
+//!  - `Discover` when a node is entered for the first time
+//!  - `TreeEdge` when an edge leads to an undiscovered node
+//!  - `BackEdge` when an edge leads to a node on the current path(a cycle)
+//!  - `ForwardOrCrossEdge` when an edge leads to a finished node
+//!  - `Finish` when all successors of a node are processed
+//!
+//! The discovery and finish times share one counter, thus a node `u` is an ancestor of `v`
+//! if and only if `d[u] < d[v] < f[v] < f[u]`.
+use crate::DiGraph;
+use std::collections::hash_map::Keys;
+use std::collections::HashMap;
+use std::hash::Hash;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DfsEvent<'a, NId> {
+    Discover { id: &'a NId, time: usize },
+    TreeEdge { from: &'a NId, to: &'a NId },
+    BackEdge { from: &'a NId, to: &'a NId },
+    ForwardOrCrossEdge { from: &'a NId, to: &'a NId },
+    Finish { id: &'a NId, time: usize },
+}
+
+/// The result of the visitor that controls the walk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Control {
+    Continue,
+    /// On `Discover` the successors of the node are not explored(the node is finished right away),
+    /// on `TreeEdge` the edge is not followed. For other events it is the same as `Continue`.
+    Prune,
+    /// Stops the search.
+    Stop,
+}
+
+pub trait DfsVisitor<'a, NId: 'a> {
+    fn visit(&mut self, event: DfsEvent<'a, NId>) -> Control;
+}
+
+impl<'a, NId: 'a, F> DfsVisitor<'a, NId> for F
+where
+    F: FnMut(DfsEvent<'a, NId>) -> Control,
+{
+    fn visit(&mut self, event: DfsEvent<'a, NId>) -> Control {
+        self(event)
+    }
+}
+
+/// The path from the root with the successors that are not explored yet.
+type Stack<'a, NId, EL> = Vec<(&'a NId, Option<Keys<'a, NId, EL>>)>;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Color {
+    Gray,
+    Black,
+}
+
+/// Runs the depth-first search from the roots one after another(a root discovered earlier is skipped).
+/// To cover the whole graph pass all nodes as roots.
+/// The roots that do not exist in the graph are ignored.
+///
+/// The search is iterative, thus deep graphs do not overflow the stack.
+/// Returns `Control::Stop` if the visitor has stopped the search and `Control::Continue` otherwise.
+pub fn depth_first_search<'a, NId, NL, EL, V>(
+    graph: &'a DiGraph<NId, NL, EL>,
+    roots: &[NId],
+    visitor: &mut V,
+) -> Control
+where
+    NId: Eq + Hash,
+    V: DfsVisitor<'a, NId>,
+{
+    let mut color: HashMap<&NId, Color> = HashMap::new();
+    let mut time = 0;
+    let mut stack: Stack<NId, EL> = vec![];
+
+    for root in roots {
+        let Some((root, _)) = graph.nodes.get_key_value(root) else {
+            continue;
+        };
+        if color.contains_key(root) {
+            continue;
+        }
+        if discover(graph, root, &mut color, &mut time, visitor, &mut stack) == Control::Stop {
+            return Control::Stop;
+        }
+
+        while let Some((id, succ)) = stack.last_mut() {
+            let id = *id;
+            let next = succ.as_mut().and_then(|s| s.next());
+            match next {
+                Some(to) => {
+                    let event = match color.get(to) {
+                        None => DfsEvent::TreeEdge { from: id, to },
+                        Some(Color::Gray) => DfsEvent::BackEdge { from: id, to },
+                        Some(Color::Black) => DfsEvent::ForwardOrCrossEdge { from: id, to },
+                    };
+                    let is_tree = matches!(event, DfsEvent::TreeEdge { .. });
+                    match visitor.visit(event) {
+                        Control::Stop => return Control::Stop,
+                        Control::Continue if is_tree => {
+                            let res =
+                                discover(graph, to, &mut color, &mut time, visitor, &mut stack);
+                            if res == Control::Stop {
+                                return Control::Stop;
+                            }
+                        }
+                        _ => (),
+                    }
+                }
+                None => {
+                    stack.pop();
+                    color.insert(id, Color::Black);
+                    time += 1;
+                    if visitor.visit(DfsEvent::Finish { id, time }) == Control::Stop {
+                        return Control::Stop;
+                    }
+                }
+            }
+        }
+    }
+    Control::Continue
+}
+
+/// Marks the node as discovered and puts it onto the stack
+/// (without successors if the visitor has pruned it).
+fn discover<'a, NId, NL, EL, V>(
+    graph: &'a DiGraph<NId, NL, EL>,
+    id: &'a NId,
+    color: &mut HashMap<&'a NId, Color>,
+    time: &mut usize,
+    visitor: &mut V,
+    stack: &mut Stack<'a, NId, EL>,
+) -> Control
+where
+    NId: Eq + Hash,
+    V: DfsVisitor<'a, NId>,
+{
+    color.insert(id, Color::Gray);
+    *time += 1;
+    let res = visitor.visit(DfsEvent::Discover { id, time: *time });
+    match res {
+        Control::Stop => (),
+        Control::Prune => stack.push((id, None)),
+        Control::Continue => stack.push((id, graph.edges.get(id).map(|succ| succ.keys()))),
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::dfs_visitor::{depth_first_search, Control, DfsEvent};
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+    use std::collections::HashMap;
+
+    #[test]
+    fn events_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3,4,5] => {
+           1 => [2,3];
+           2 => 3;
+           3 => 1;
+           4 => 3;
+        });
+        let mut discover = HashMap::new();
+        let mut finish = HashMap::new();
+        let mut back = vec![];
+        let mut tree = 0;
+        let mut other = vec![];
+        let res = depth_first_search(&graph, &[1, 4, 5], &mut |e| {
+            match e {
+                DfsEvent::Discover { id, time } => {
+                    discover.insert(*id, time);
+                }
+                DfsEvent::Finish { id, time } => {
+                    finish.insert(*id, time);
+                }
+                DfsEvent::TreeEdge { .. } => tree += 1,
+                DfsEvent::BackEdge { from, to } => back.push((*from, *to)),
+                DfsEvent::ForwardOrCrossEdge { from, to } => other.push((*from, *to)),
+            }
+            Control::Continue
+        });
+        assert_eq!(res, Control::Continue);
+        assert_eq!(discover.len(), 5);
+        assert_eq!(finish.len(), 5);
+        assert_eq!(tree, 2);
+        assert_eq!(back, vec![(3, 1)]);
+        // 1 -> 3 is either a tree edge or a forward edge, 2 -> 3 is either a tree edge or a cross one
+        assert_eq!(other.len(), 2);
+        assert!(other.contains(&(4, 3)));
+
+        assert_eq!(discover[&1], 1);
+        assert_eq!(finish[&1], 6);
+        for id in [2, 3] {
+            assert!(discover[&1] < discover[&id] && finish[&id] < finish[&1]);
+        }
+        assert_eq!((discover[&4], finish[&4]), (7, 8));
+        assert_eq!((discover[&5], finish[&5]), (9, 10));
+    }
+
+    #[test]
+    fn control_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3,4] => {
+           1 => 2;
+           2 => 3;
+           3 => 4;
+        });
+        let mut discovered = vec![];
+        depth_first_search(&graph, &[1], &mut |e| match e {
+            DfsEvent::Discover { id, .. } => {
+                discovered.push(*id);
+                if *id == 2 {
+                    Control::Prune
+                } else {
+                    Control::Continue
+                }
+            }
+            _ => Control::Continue,
+        });
+        assert_eq!(discovered, vec![1, 2]);
+
+        let mut discovered = vec![];
+        let res = graph
+            .analyze()
+            .depth_first_search(&[1, 4], &mut |e| match e {
+                DfsEvent::Discover { id, .. } => {
+                    discovered.push(*id);
+                    Control::Continue
+                }
+                DfsEvent::TreeEdge { to, .. } if *to == 3 => Control::Stop,
+                _ => Control::Continue,
+            });
+        assert_eq!(res, Control::Stop);
+        assert_eq!(discovered, vec![1, 2]);
+    }
+}
@@ -0,0 +1,324 @@
+use crate::visualizer::dot::{dot_string, DotProcessor, ToStringProcessor};
+use crate::DiGraph;
+use graphviz_rust::attributes::{color_name, EdgeAttributes, NodeAttributes};
+use graphviz_rust::dot_structures::Stmt;
+use std::collections::{HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+
+/// The difference between two graphs: what should be done to get the new graph(rhs)
+/// from the old one(lhs).
+///  - added nodes and edges exist only in rhs
+///  - removed nodes and edges exist only in lhs
+///  - changed edges exist in both but have different payloads
+#[derive(Debug)]
+pub struct GraphDiff<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+{
+    lhs: &'a DiGraph<NId, NL, EL>,
+    rhs: &'a DiGraph<NId, NL, EL>,
+    added_nodes: HashSet<&'a NId>,
+    removed_nodes: HashSet<&'a NId>,
+    added_edges: HashSet<(&'a NId, &'a NId)>,
+    removed_edges: HashSet<(&'a NId, &'a NId)>,
+    changed_edges: HashSet<(&'a NId, &'a NId)>,
+}
+
+impl<'a, NId, NL, EL> GraphDiff<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+    EL: PartialEq,
+{
+    pub fn new(lhs: &'a DiGraph<NId, NL, EL>, rhs: &'a DiGraph<NId, NL, EL>) -> Self {
+        let added_nodes = rhs
+            .nodes
+            .keys()
+            .filter(|id| !lhs.nodes.contains_key(*id))
+            .collect();
+        let removed_nodes = lhs
+            .nodes
+            .keys()
+            .filter(|id| !rhs.nodes.contains_key(*id))
+            .collect();
+
+        let mut added_edges = HashSet::new();
+        let mut changed_edges = HashSet::new();
+        for (from, tos) in rhs.edges.iter() {
+            for (to, el) in tos.iter() {
+                match edge(lhs, from, to) {
+                    None => {
+                        added_edges.insert((from, to));
+                    }
+                    Some(old) if old != el => {
+                        changed_edges.insert((from, to));
+                    }
+                    _ => (),
+                }
+            }
+        }
+
+        let mut removed_edges = HashSet::new();
+        for (from, tos) in lhs.edges.iter() {
+            for to in tos.keys() {
+                if edge(rhs, from, to).is_none() {
+                    removed_edges.insert((from, to));
+                }
+            }
+        }
+
+        Self {
+            lhs,
+            rhs,
+            added_nodes,
+            removed_nodes,
+            added_edges,
+            removed_edges,
+            changed_edges,
+        }
+    }
+}
+
+impl<'a, NId, NL, EL> GraphDiff<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+{
+    pub fn added_nodes(&self) -> &HashSet<&'a NId> {
+        &self.added_nodes
+    }
+    pub fn removed_nodes(&self) -> &HashSet<&'a NId> {
+        &self.removed_nodes
+    }
+    pub fn added_edges(&self) -> &HashSet<(&'a NId, &'a NId)> {
+        &self.added_edges
+    }
+    pub fn removed_edges(&self) -> &HashSet<(&'a NId, &'a NId)> {
+        &self.removed_edges
+    }
+    pub fn changed_edges(&self) -> &HashSet<(&'a NId, &'a NId)> {
+        &self.changed_edges
+    }
+
+    /// Returns true if the graphs have the same nodes and edges with the same payloads on edges.
+    pub fn is_empty(&self) -> bool {
+        self.added_nodes.is_empty()
+            && self.removed_nodes.is_empty()
+            && self.added_edges.is_empty()
+            && self.removed_edges.is_empty()
+            && self.changed_edges.is_empty()
+    }
+}
+
+impl<'a, NId, NL, EL> GraphDiff<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    NL: Clone,
+    EL: Clone,
+{
+    /// Returns the union of both graphs(the payloads are taken from the new graph if they exist there).
+    /// The union is the graph to render with `GraphDiffHighlighter`.
+    pub fn merged(&self) -> DiGraph<NId, NL, EL> {
+        let mut graph = DiGraph::new();
+        for (id, nl) in self.rhs.nodes.iter() {
+            graph.add_node(id.clone(), nl.clone());
+        }
+        for id in self.removed_nodes.iter() {
+            if let Some(nl) = self.lhs.nodes.get(*id) {
+                graph.add_node((*id).clone(), nl.clone());
+            }
+        }
+        for (from, tos) in self.rhs.edges.iter() {
+            for (to, el) in tos.iter() {
+                graph.add_edge(from.clone(), to.clone(), el.clone());
+            }
+        }
+        for (from, to) in self.removed_edges.iter() {
+            if let Some(el) = self.lhs.edge(from, to) {
+                graph.add_edge((*from).clone(), (*to).clone(), el.clone());
+            }
+        }
+        graph
+    }
+}
+
+/// Highlights the diff on the merged graph(`GraphDiff::merged`):
+/// added nodes and edges are green, removed ones are red(dashed)
+/// and the edges with changed payloads are orange with the label `old -> new`.
+pub struct GraphDiffHighlighter<'d, NId>
+where
+    NId: Eq + Hash,
+{
+    added_nodes: HashSet<&'d NId>,
+    removed_nodes: HashSet<&'d NId>,
+    added_edges: HashSet<(&'d NId, &'d NId)>,
+    removed_edges: HashSet<(&'d NId, &'d NId)>,
+    changed_edges: HashMap<(&'d NId, &'d NId), String>,
+    delegate: ToStringProcessor,
+}
+
+impl<'d, NId> GraphDiffHighlighter<'d, NId>
+where
+    NId: Eq + Hash,
+{
+    pub fn new<NL, EL>(diff: &GraphDiff<'d, NId, NL, EL>) -> Self
+    where
+        EL: ToString,
+    {
+        let changed_edges = diff
+            .changed_edges
+            .iter()
+            .map(|(from, to)| {
+                let old = edge(diff.lhs, from, to)
+                    .map(|el| el.to_string())
+                    .unwrap_or_default();
+                ((*from, *to), old)
+            })
+            .collect();
+        Self {
+            added_nodes: diff.added_nodes.clone(),
+            removed_nodes: diff.removed_nodes.clone(),
+            added_edges: diff.added_edges.clone(),
+            removed_edges: diff.removed_edges.clone(),
+            changed_edges,
+            delegate: ToStringProcessor {},
+        }
+    }
+}
+
+impl<'a, 'd, NId, NL, EL> DotProcessor<'a, NId, NL, EL> for GraphDiffHighlighter<'d, NId>
+where
+    NId: Eq + Hash + ToString,
+    NL: ToString,
+    EL: ToString,
+{
+    fn node(&self, id: &'a NId, nl: &'a NL) -> Stmt {
+        if self.added_nodes.contains(id) {
+            let green = NodeAttributes::color(color_name::green);
+            let bold = NodeAttributes::style("bold".to_string());
+            self.delegate.node_with_attrs(id, nl, vec![green, bold])
+        } else if self.removed_nodes.contains(id) {
+            let red = NodeAttributes::color(color_name::red);
+            let dashed = NodeAttributes::style("dashed".to_string());
+            self.delegate.node_with_attrs(id, nl, vec![red, dashed])
+        } else {
+            (&self.delegate as &dyn DotProcessor<NId, NL, EL>).node(id, nl)
+        }
+    }
+
+    fn edge(&self, from: &'a NId, to: &'a NId, el: &'a EL) -> Stmt {
+        let key = (from, to);
+        if self.added_edges.contains(&key) {
+            let green = EdgeAttributes::color(color_name::green);
+            let bold = EdgeAttributes::penwidth(2.0);
+            self.delegate
+                .edge_with_attrs(from, to, el, vec![green, bold])
+        } else if self.removed_edges.contains(&key) {
+            let red = EdgeAttributes::color(color_name::red);
+            let dashed = EdgeAttributes::style("dashed".to_string());
+            self.delegate
+                .edge_with_attrs(from, to, el, vec![red, dashed])
+        } else if let Some(old) = self.changed_edges.get(&key) {
+            let orange = EdgeAttributes::color(color_name::orange);
+            let bold = EdgeAttributes::penwidth(2.0);
+            let label =
+                EdgeAttributes::label(dot_string(&format!("{} -> {}", old, el.to_string())));
+            self.delegate
+                .edge_with_attrs(from, to, &EmptyLabel, vec![orange, bold, label])
+        } else {
+            (&self.delegate as &dyn DotProcessor<NId, NL, EL>).edge(from, to, el)
+        }
+    }
+}
+
+fn edge<'a, NId, NL, EL>(graph: &'a DiGraph<NId, NL, EL>, from: &NId, to: &NId) -> Option<&'a EL>
+where
+    NId: Eq + Hash,
+{
+    graph.edges.get(from).and_then(|tos| tos.get(to))
+}
+
+/// The payload for the edges that have the label set explicitly.
+struct EmptyLabel;
+
+impl Display for EmptyLabel {
+    fn fmt(&self, _f: &mut Formatter<'_>) -> std::fmt::Result {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::diff::{GraphDiff, GraphDiffHighlighter};
+    use crate::visualizer::vis;
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+    use std::collections::HashSet;
+
+    #[test]
+    fn diff_test() {
+        let old = digraph!((&str,_,usize) => ["a","b","c","d"] => {
+            "a" => [("b",1),("c",1)];
+            "b" => ("d",2);
+            "c" => ("d",3);
+        });
+        let new = digraph!((&str,_,usize) => ["a","b","c","e"] => {
+            "a" => [("b",1),("c",5)];
+            "b" => ("e",2);
+            "c" => ("e",3);
+        });
+
+        let diff = old.analyze().diff(&new);
+        assert!(!diff.is_empty());
+        assert_eq!(diff.added_nodes(), &HashSet::from([&"e"]));
+        assert_eq!(diff.removed_nodes(), &HashSet::from([&"d"]));
+        assert_eq!(
+            diff.added_edges(),
+            &HashSet::from([(&"b", &"e"), (&"c", &"e")])
+        );
+        assert_eq!(
+            diff.removed_edges(),
+            &HashSet::from([(&"b", &"d"), (&"c", &"d")])
+        );
+        assert_eq!(diff.changed_edges(), &HashSet::from([(&"a", &"c")]));
+
+        assert!(old.analyze().diff(&old).is_empty());
+    }
+
+    #[test]
+    fn diff_viz_test() {
+        let old = digraph!((usize,_,usize) => [1,2,3] => {
+            1 => [(2,1),(3,1)];
+        });
+        let new = digraph!((usize,_,usize) => [1,2,4] => {
+            1 => [(2,7),(4,1)];
+        });
+
+        let diff = GraphDiff::new(&old, &new);
+        let merged = diff.merged();
+        assert_eq!(merged.iter().count(), 4);
+        assert_eq!(merged.edges().len(), 3);
+
+        let dot = vis(merged.visualize().to_dot(GraphDiffHighlighter::new(&diff)));
+        assert!(dot.contains("1 -> 2 [color=orange,penwidth=2,label=\"1 -> 7\"]"));
+        assert!(dot.contains("1 -> 3 [color=red,style=dashed,label=1]"));
+        assert!(dot.contains("1 -> 4 [color=green,penwidth=2,label=1]"));
+        assert!(dot.contains("3[color=red,style=dashed,label=\"3\"]"));
+        assert!(dot.contains("4[color=green,style=bold,label=\"4\"]"));
+    }
+
+    #[test]
+    fn quoted_payload_test() {
+        let old = digraph!((usize,_,&str) => [1,2] => {
+            1 => (2,"say \"hi\"");
+        });
+        let new = digraph!((usize,_,&str) => [1,2] => {
+            1 => (2,"C:\\tmp");
+        });
+
+        let diff = GraphDiff::new(&old, &new);
+        let merged = diff.merged();
+        let dot = vis(merged.visualize().to_dot(GraphDiffHighlighter::new(&diff)));
+        assert!(dot.contains(r#"label="say \"hi\" -> C:\\tmp""#));
+    }
+}
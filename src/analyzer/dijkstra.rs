@@ -1,4 +1,6 @@
-use crate::analyzer::min_weight::{MinWeight, Score};
+use crate::analyzer::float::{check_weights, Float, FloatWeight, NaNWeight};
+use crate::analyzer::min_weight::{MinIndexed, MinWeight, Score};
+use crate::analyzer::trace::{emit, NoTrace, TraceEvent, Tracer};
 use crate::visualizer::dot::{DotProcessor, ToStringProcessor};
 use crate::DiGraph;
 use graphviz_rust::attributes::*;
@@ -6,7 +8,8 @@ use graphviz_rust::dot_generator::*;
 use graphviz_rust::dot_structures::Stmt;
 use graphviz_rust::dot_structures::*;
 use std::borrow::Borrow;
-use std::collections::{BinaryHeap, HashMap};
+use std::collections::hash_map::Entry::Vacant;
+use std::collections::{BinaryHeap, HashMap, HashSet};
 use std::convert::identity;
 use std::fmt::Debug;
 use std::hash::Hash;
@@ -29,6 +32,18 @@ where
     }
 }
 
+impl<'a, NId, NL, EL> DijkstraPath<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: FloatWeight,
+{
+    /// The float weights version of `on_edge`(see `crate::analyzer::float`).
+    pub fn on_float_edge(&mut self, start: NId) -> Result<MinPath<NId, Float<EL>>, NaNWeight<NId>> {
+        check_weights(self.graph)?;
+        Ok(self.on_edge_custom(start, Float::unchecked))
+    }
+}
+
 impl<'a, NId, NL, EL> DijkstraPath<'a, NId, NL, EL>
 where
     NId: Eq + Hash + Clone,
@@ -39,32 +54,321 @@ where
         F: Fn(EL) -> ScoreV,
         ScoreV: Ord + Add<Output = ScoreV> + Clone,
     {
-        let mut dist = HashMap::from([(start.clone(), Score::Zero)]);
+        self.search_custom(DijkstraQuery::new(start), to_score)
+    }
+
+    /// The same as `on_edge_custom` but only the allowed edges are followed.
+    pub(crate) fn on_edge_filtered<ScoreV, F, A>(
+        &mut self,
+        start: NId,
+        to_score: F,
+        allowed: A,
+    ) -> MinPath<NId, ScoreV>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone,
+        A: Fn(&NId, &NId) -> bool,
+    {
+        self.process(
+            DijkstraQuery::new(start),
+            to_score,
+            allowed,
+            &mut NoTrace,
+            |_| String::new(),
+        )
+    }
+
+    /// The same as `on_edge_custom` but reports pop, relax and push events to the tracer.
+    pub fn on_edge_custom_traced<ScoreV, F, T>(
+        &mut self,
+        start: NId,
+        to_score: F,
+        tracer: &mut T,
+    ) -> MinPath<NId, ScoreV>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone + ToString,
+        T: Tracer<NId>,
+    {
+        self.process(
+            DijkstraQuery::new(start),
+            to_score,
+            |_, _| true,
+            tracer,
+            Score::to_string,
+        )
+    }
+
+    /// Runs the search according to the query(see `DijkstraQuery`).
+    pub fn search_custom<ScoreV, F>(
+        &mut self,
+        query: DijkstraQuery<NId, ScoreV>,
+        to_score: F,
+    ) -> MinPath<NId, ScoreV>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone,
+    {
+        self.process(
+            query,
+            to_score,
+            |_, _| true,
+            &mut NoTrace,
+            |_| String::new(),
+        )
+    }
+
+    fn process<ScoreV, F, A, T, D>(
+        &mut self,
+        query: DijkstraQuery<NId, ScoreV>,
+        to_score: F,
+        allowed: A,
+        tracer: &mut T,
+        describe: D,
+    ) -> MinPath<NId, ScoreV>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone,
+        A: Fn(&NId, &NId) -> bool,
+        T: Tracer<NId>,
+        D: Fn(&Score<ScoreV>) -> String,
+    {
+        if let Some(max_hops) = query.max_hops {
+            return self.process_bounded(query, max_hops, to_score, allowed, tracer, describe);
+        }
+        let mut dist: HashMap<NId, Score<ScoreV>> = self
+            .graph
+            .nodes
+            .keys()
+            .map(|id| (id.clone(), Score::Inf))
+            .collect();
         let mut path = HashMap::new();
         let mut queue = BinaryHeap::new();
-
-        for (id, _) in &self.graph.nodes {
-            if id.ne(&start) {
-                dist.insert(id.clone(), Score::Inf);
+        let mut settled: HashSet<&NId> = HashSet::new();
+        let mut targets: HashSet<&NId> = query.targets.iter().collect();
+        let cutoff = query.cutoff.clone().map(Score::Value);
+
+        for s in query.sources.iter() {
+            if let Some((s, _)) = self.graph.nodes.get_key_value(s) {
+                dist.insert(s.clone(), Score::Zero);
+                queue.push(MinWeight(s, Score::Zero));
             }
-            queue.push(MinWeight(&start, dist[&id].clone()))
         }
 
         while let Some(MinWeight(from, _)) = queue.pop() {
+            // the node can be pushed several times, only the first(the best) entry is processed
+            if !settled.insert(from) {
+                continue;
+            }
+            emit(tracer, || TraceEvent::Pop(from.clone()));
+            if targets.remove(from) && targets.is_empty() {
+                break;
+            }
             if let Some(ss) = self.graph.edges.get(from) {
                 let dist_from = dist[from].clone();
-                for (to, ep) in ss {
+                for (to, ep) in ss.iter().filter(|(to, _)| allowed(from, to)) {
+                    let Some(dist_to) = dist.get(to) else {
+                        continue;
+                    };
                     let alt = dist_from.add_score_v(to_score(ep.clone()));
-                    let dist_to = dist[to].clone();
-                    if alt < dist_to {
+                    if cutoff.as_ref().map(|c| &alt > c).unwrap_or(false) {
+                        continue;
+                    }
+                    if &alt < dist_to {
+                        emit(tracer, || TraceEvent::Relax {
+                            from: from.clone(),
+                            to: to.clone(),
+                            score: describe(&alt),
+                        });
                         dist.insert(to.clone(), alt.clone());
                         path.insert(to.clone(), from.clone());
-                        queue.push(MinWeight(to, alt.clone()))
+                        queue.push(MinWeight(to, alt.clone()));
+                        emit(tracer, || TraceEvent::Push(to.clone()));
                     }
                 }
             }
         }
-        MinPath::new(start, dist, path)
+        let from = query
+            .sources
+            .into_iter()
+            .next()
+            .expect("the query has a source");
+        MinPath::new(from, dist, path)
+    }
+
+    /// The search over the states(the node, the number of edges to it).
+    /// A node is settled again only with fewer edges(and thus a greater distance),
+    /// so the cheapest path within the bound is found even if a cheaper one has more edges.
+    /// Such paths can pass the nodes through their non optimal paths, therefore the trails are kept explicitly.
+    fn process_bounded<ScoreV, F, A, T, D>(
+        &mut self,
+        query: DijkstraQuery<NId, ScoreV>,
+        max_hops: usize,
+        to_score: F,
+        allowed: A,
+        tracer: &mut T,
+        describe: D,
+    ) -> MinPath<NId, ScoreV>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone,
+        A: Fn(&NId, &NId) -> bool,
+        T: Tracer<NId>,
+        D: Fn(&Score<ScoreV>) -> String,
+    {
+        // the node, the number of edges, the parent state and the distance
+        let mut states: Vec<(&NId, usize, Option<usize>, Score<ScoreV>)> = vec![];
+        let mut dist: HashMap<(&NId, usize), Score<ScoreV>> = HashMap::new();
+        let mut fewest: HashMap<&NId, usize> = HashMap::new();
+        let mut best: HashMap<&NId, usize> = HashMap::new();
+        let mut queue = BinaryHeap::new();
+        let mut targets: HashSet<&NId> = query.targets.iter().collect();
+        let cutoff = query.cutoff.clone().map(Score::Value);
+
+        for s in query.sources.iter() {
+            if let Some((s, _)) = self.graph.nodes.get_key_value(s) {
+                dist.insert((s, 0), Score::Zero);
+                queue.push(MinIndexed(states.len(), Score::Zero));
+                states.push((s, 0, None, Score::Zero));
+            }
+        }
+
+        while let Some(MinIndexed(idx, _)) = queue.pop() {
+            let (from, hops, _, dist_from) = states[idx].clone();
+            // the state is dominated by the one settled with fewer edges and not greater distance
+            if fewest.get(from).map(|h| *h <= hops).unwrap_or(false) {
+                continue;
+            }
+            fewest.insert(from, hops);
+            emit(tracer, || TraceEvent::Pop(from.clone()));
+            // the first time the node is settled is with the minimal distance
+            if let Vacant(e) = best.entry(from) {
+                e.insert(idx);
+                if targets.remove(from) && targets.is_empty() {
+                    break;
+                }
+            }
+            if hops == max_hops {
+                continue;
+            }
+            for (to, ep) in self.graph.edges.get(from).into_iter().flatten() {
+                let Some((to, _)) = self.graph.nodes.get_key_value(to) else {
+                    continue;
+                };
+                if !allowed(from, to) {
+                    continue;
+                }
+                let alt = dist_from.add_score_v(to_score(ep.clone()));
+                if cutoff.as_ref().map(|c| &alt > c).unwrap_or(false) {
+                    continue;
+                }
+                let improves = dist.get(&(to, hops + 1)).map(|d| &alt < d).unwrap_or(true);
+                if improves {
+                    emit(tracer, || TraceEvent::Relax {
+                        from: from.clone(),
+                        to: to.clone(),
+                        score: describe(&alt),
+                    });
+                    dist.insert((to, hops + 1), alt.clone());
+                    queue.push(MinIndexed(states.len(), alt.clone()));
+                    states.push((to, hops + 1, Some(idx), alt));
+                    emit(tracer, || TraceEvent::Push(to.clone()));
+                }
+            }
+        }
+
+        let mut distance: HashMap<NId, Score<ScoreV>> = self
+            .graph
+            .nodes
+            .keys()
+            .map(|id| (id.clone(), Score::Inf))
+            .collect();
+        let mut path = HashMap::new();
+        let mut trails = HashMap::new();
+        for (id, idx) in best {
+            distance.insert(id.clone(), states[idx].3.clone());
+            let mut trail = vec![id.clone()];
+            let mut parent = states[idx].2;
+            while let Some(p) = parent {
+                trail.push(states[p].0.clone());
+                parent = states[p].2;
+            }
+            if trail.len() > 1 {
+                trail.reverse();
+                path.insert(id.clone(), trail[trail.len() - 2].clone());
+                trails.insert(id.clone(), trail);
+            }
+        }
+        let from = query
+            .sources
+            .into_iter()
+            .next()
+            .expect("the query has a source");
+        MinPath::new(from, distance, path).with_trails(trails)
+    }
+}
+
+impl<'a, NId, NL, EL> DijkstraPath<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Ord + Add<Output = EL> + Clone,
+{
+    /// Runs the search according to the query where the edge payloads are the weights.
+    pub fn search(&mut self, query: DijkstraQuery<NId, EL>) -> MinPath<NId, EL> {
+        self.search_custom(query, identity)
+    }
+}
+
+/// The query for `DijkstraPath::search`.
+/// By default the search settles the whole graph reachable from the sources.
+#[derive(Debug, Clone)]
+pub struct DijkstraQuery<NId, ScoreV> {
+    sources: Vec<NId>,
+    targets: Vec<NId>,
+    cutoff: Option<ScoreV>,
+    max_hops: Option<usize>,
+}
+
+impl<NId, ScoreV> DijkstraQuery<NId, ScoreV> {
+    pub fn new(source: NId) -> Self {
+        Self {
+            sources: vec![source],
+            targets: vec![],
+            cutoff: None,
+            max_hops: None,
+        }
+    }
+
+    /// Adds the sources. All sources start with the zero distance,
+    /// thus every node gets the distance to the nearest source(see `MinPath::source`).
+    /// The sources that are not in the graph are ignored.
+    pub fn with_sources<I: IntoIterator<Item = NId>>(mut self, sources: I) -> Self {
+        self.sources.extend(sources);
+        self
+    }
+
+    /// The search stops once all targets are settled.
+    /// The distances of the nodes that are not settled yet can be greater than the shortest ones.
+    pub fn with_targets<I: IntoIterator<Item = NId>>(mut self, targets: I) -> Self {
+        self.targets.extend(targets);
+        self
+    }
+
+    /// The nodes farther than the cutoff are not reached(their distance is `Inf`).
+    pub fn with_cutoff(self, cutoff: ScoreV) -> Self {
+        Self {
+            cutoff: Some(cutoff),
+            ..self
+        }
+    }
+
+    /// Only the paths with at most the given number of edges are considered,
+    /// the distance is the cheapest among them(a cheaper path with more edges is ignored).
+    pub fn with_max_hops(self, max_hops: usize) -> Self {
+        Self {
+            max_hops: Some(max_hops),
+            ..self
+        }
     }
 }
 
@@ -86,6 +390,7 @@ where
     from: NId,
     distance: HashMap<NId, Score<ScoreV>>,
     path: HashMap<NId, NId>,
+    trails: HashMap<NId, Vec<NId>>,
 }
 
 impl<NId, ScoreV> MinPath<NId, ScoreV>
@@ -98,19 +403,33 @@ where
             from,
             distance,
             path,
+            trails: HashMap::new(),
         }
     }
 
+    /// The trails that cannot be restored from the parents(see `DijkstraQuery::with_max_hops`).
+    pub(crate) fn with_trails(self, trails: HashMap<NId, Vec<NId>>) -> Self {
+        Self { trails, ..self }
+    }
+
+    /// The (first) source of the search.
+    pub fn start(&self) -> &NId {
+        &self.from
+    }
+    /// The distance from the sources, `Score::Inf` if the node is not reached(or not in the graph).
     pub fn score(&self, to: &NId) -> Score<ScoreV> {
-        self.distance[to].clone()
+        self.distance.get(to).cloned().unwrap_or(Score::Inf)
     }
     pub fn trail(&self, to: &NId) -> Option<Vec<NId>> {
+        if let Some(trail) = self.trails.get(to) {
+            return Some(trail.clone());
+        }
         let mut rhs = to;
         let mut trail = vec![];
         while let Some(start) = self.path.get(rhs) {
             trail.push(rhs.clone());
             rhs = start;
-            if rhs.eq(&self.from) {
+            if self.is_source(rhs) {
                 trail.push(rhs.clone());
                 trail.reverse();
                 return Some(trail);
@@ -118,6 +437,20 @@ where
         }
         None
     }
+
+    /// The source the path to the node starts from(the nearest one for the multi-source search).
+    pub fn source(&self, to: &NId) -> Option<NId> {
+        if self.is_source(to) {
+            Some(to.clone())
+        } else {
+            self.trail(to).and_then(|t| t.first().cloned())
+        }
+    }
+
+    /// The sources are the only nodes with the zero distance.
+    fn is_source(&self, id: &NId) -> bool {
+        matches!(self.distance.get(id), Some(Score::Zero))
+    }
 }
 
 struct MinScorePathProcessor<NId, ScoreV>
@@ -240,14 +573,16 @@ where
 #[cfg(test)]
 mod tests {
     use crate::analyzer::dijkstra::{
-        DijkstraPath, MinPathProcessor, MinScorePathProcessor, MinWeight,
+        DijkstraPath, DijkstraQuery, MinPathProcessor, MinScorePathProcessor, MinWeight,
     };
     use crate::analyzer::min_weight::Score;
     use crate::analyzer::min_weight::Score::*;
+    use crate::analyzer::trace::{Trace, TraceEvent};
     use crate::DiGraph;
     use crate::EmptyPayload;
     use crate::{digraph, extend_edges, extend_nodes};
     use std::collections::BinaryHeap;
+    use std::convert::identity;
     use std::ops::Add;
 
     #[test]
@@ -424,4 +759,127 @@ mod tests {
             .to_dot_file("dots/graph_path.svg", MinPathProcessor::new(trail));
         assert!(r.is_ok());
     }
+
+    #[test]
+    fn traced_dijkstra_test() {
+        let graph = digraph!((_,_,usize) => [1,2,3] => {
+           1 => [(2,1),(3,4)];
+           2 => (3,1);
+        });
+        let mut trace = Trace::new();
+        let path = DijkstraPath::new(&graph).on_edge_custom_traced(1, identity, &mut trace);
+        assert_eq!(path.trail(&3), Some(vec![1, 2, 3]));
+
+        let relaxed: Vec<_> = trace
+            .events()
+            .iter()
+            .filter_map(|e| match e {
+                TraceEvent::Relax { to, score, .. } => Some((*to, score.clone())),
+                _ => None,
+            })
+            .collect();
+        assert!(relaxed.contains(&(2, "1".to_string())));
+        assert!(relaxed.contains(&(3, "2".to_string())));
+        assert_eq!(trace.events().first(), Some(&TraceEvent::Pop(1)));
+    }
+
+    #[test]
+    fn query_test() {
+        let graph = digraph!((_,_,usize) => [1,2,3,4,5,6] => {
+           1 => [(2,1),(3,5)];
+           2 => [(3,1),(4,7)];
+           3 => (4,1);
+           4 => (5,1);
+           6 => (5,1);
+        });
+        let mut d = DijkstraPath::new(&graph);
+
+        let res = d.search(DijkstraQuery::new(1).with_targets([3]));
+        assert_eq!(res.score(&3), Value(2));
+        assert_eq!(res.trail(&3), Some(vec![1, 2, 3]));
+        // 4 is reached but not settled, 5 is not reached at all
+        assert_eq!(res.score(&5), Inf);
+
+        let res = d.search(DijkstraQuery::new(1).with_sources([6]));
+        assert_eq!(res.score(&5), Value(1));
+        assert_eq!(res.source(&5), Some(6));
+        assert_eq!(res.source(&4), Some(1));
+        assert_eq!(res.source(&6), Some(6));
+        assert_eq!(res.start(), &1);
+        assert_eq!(res.trail(&4), Some(vec![1, 2, 3, 4]));
+
+        let res = d.search(DijkstraQuery::new(1).with_cutoff(2));
+        assert_eq!(res.score(&3), Value(2));
+        assert_eq!(res.score(&4), Inf);
+
+        let res = d.search(DijkstraQuery::new(1).with_max_hops(2));
+        assert_eq!(res.score(&3), Value(2));
+        assert_eq!(res.score(&4), Value(6));
+        assert_eq!(res.trail(&4), Some(vec![1, 3, 4]));
+        assert_eq!(res.score(&5), Inf);
+    }
+
+    #[test]
+    fn max_hops_test() {
+        let graph = digraph!((_,_,usize) => [1,2,3,4] => {
+           1 => [(2,1),(3,5)];
+           2 => (3,1);
+           3 => (4,1);
+        });
+        let mut d = DijkstraPath::new(&graph);
+        let res = d.search(DijkstraQuery::new(1).with_max_hops(2));
+        assert_eq!(res.score(&3), Value(2));
+        assert_eq!(res.trail(&3), Some(vec![1, 2, 3]));
+        assert_eq!(res.score(&4), Value(6));
+        assert_eq!(res.trail(&4), Some(vec![1, 3, 4]));
+
+        let res = d.search(DijkstraQuery::new(1).with_max_hops(3));
+        assert_eq!(res.score(&4), Value(3));
+        assert_eq!(res.trail(&4), Some(vec![1, 2, 3, 4]));
+
+        let res = d.search(DijkstraQuery::new(1).with_max_hops(1));
+        assert_eq!(res.score(&3), Value(5));
+        assert_eq!(res.score(&4), Inf);
+        assert_eq!(res.trail(&4), None);
+
+        let res = d.search(DijkstraQuery::new(1).with_max_hops(2).with_targets([4]));
+        assert_eq!(res.score(&4), Value(6));
+    }
+
+    #[test]
+    fn missing_source_test() {
+        let graph = digraph!((_,_,usize) => [1,2,3] => {
+           1 => (2,1);
+           2 => (3,1);
+        });
+        let mut d = DijkstraPath::new(&graph);
+        for query in [
+            DijkstraQuery::new(1).with_sources([42]),
+            DijkstraQuery::new(1).with_sources([42]).with_max_hops(2),
+        ] {
+            let res = d.search(query);
+            assert_eq!(res.score(&42), Inf);
+            assert_eq!(res.source(&42), None);
+            assert_eq!(res.score(&3), Value(2));
+            assert_eq!(res.source(&3), Some(1));
+        }
+    }
+
+    #[test]
+    fn stale_entries_test() {
+        let graph = digraph!((_,_,usize) => [1,2,3,4] => {
+           1 => [(2,1),(3,5),(4,9)];
+           2 => [(3,1),(4,5)];
+           3 => (4,1);
+        });
+        let mut trace = Trace::new();
+        let path = DijkstraPath::new(&graph).on_edge_custom_traced(1, identity, &mut trace);
+        assert_eq!(path.score(&4), Value(3));
+        let pops = trace
+            .events()
+            .iter()
+            .filter(|e| matches!(e, TraceEvent::Pop(_)))
+            .count();
+        assert_eq!(pops, 4);
+    }
 }
@@ -2,6 +2,7 @@ use graphviz_rust::attributes::{EdgeAttributes, NodeAttributes};
 use graphviz_rust::dot_generator::*;
 use graphviz_rust::dot_structures::*;
 
+use crate::analyzer::trace::{emit, NoTrace, TraceEvent, Tracer};
 use crate::visualizer::dot::{DotProcessor, ToStringProcessor};
 use crate::DiGraph;
 use std::fmt::Debug;
@@ -81,6 +82,15 @@ where
     NId: Eq + Hash + Clone,
 {
     pub fn simple_fast<NL, EL>(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self::simple_fast_traced(graph, &mut NoTrace)
+    }
+
+    /// The same as `simple_fast` but reports the nodes processed on every pass of the fixpoint
+    /// and the updates of the immediate dominators to the tracer.
+    pub fn simple_fast_traced<NL, EL, T>(graph: &'a DiGraph<NId, NL, EL>, tracer: &mut T) -> Self
+    where
+        T: Tracer<NId>,
+    {
         let predecessors = graph.predecessors();
         let post_order_line = predecessors.post_order_line();
         let predecessors = predecessors.predecessors();
@@ -95,6 +105,7 @@ where
             changed = false;
             // reverse post order except start node => (0 .. len - 1).rev()
             for idx in (0..len - 1).rev() {
+                emit(tracer, || TraceEvent::Visit(post_order_line[idx].clone()));
                 let predecessors = post_order_idx_vec[idx].clone();
                 if !predecessors.is_empty() {
                     let mut new_idom = predecessors[0];
@@ -104,6 +115,10 @@ where
                         }
                     }
                     if dominators[idx] != new_idom {
+                        emit(tracer, || TraceEvent::Dominator {
+                            id: post_order_line[idx].clone(),
+                            idom: post_order_line[new_idom].clone(),
+                        });
                         dominators[idx] = new_idom;
                         changed = true;
                     }
@@ -182,6 +197,7 @@ mod tests {
     use std::collections::{HashMap, HashSet};
 
     use super::DominatorsHighlighter;
+    use crate::analyzer::trace::{Trace, TraceEvent};
 
     #[test]
     fn smoke_test() {
@@ -230,7 +246,7 @@ mod tests {
         assert_eq!(predecessors[&4], HashSet::from_iter(vec![&2, &3]));
 
         assert_eq!(post_order_indexes.len(), 5);
-        assert_eq!(post_order_indexes[4], vec![]);
+        assert_eq!(post_order_indexes[4], Vec::<usize>::new());
         assert_eq!(post_order_indexes[3], vec![4]);
         assert_eq!(post_order_indexes[2], vec![3]);
         assert_eq!(post_order_indexes[1], vec![3]);
@@ -251,4 +267,20 @@ mod tests {
             .to_dot_file("dots/dom_viz.svg", DominatorsHighlighter::new(dominators));
         assert!(r.is_ok())
     }
+
+    #[test]
+    fn traced_test() {
+        let graph = digraph!((usize,_,_) => [0,1,2,3,4] => {
+           0 => 1;
+           1 => [2,3];
+           [2,3] => 4;
+        });
+        let mut trace = Trace::new();
+        let doms = Dominators::simple_fast_traced(&graph, &mut trace);
+        assert_eq!(doms.idom(&4), Some(&1));
+        assert!(trace
+            .events()
+            .contains(&TraceEvent::Dominator { id: 4, idom: 1 }));
+        assert!(trace.events().contains(&TraceEvent::Visit(1)));
+    }
 }
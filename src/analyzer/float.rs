@@ -0,0 +1,416 @@
+//! Floating-point edge weights.
+//!
+//! `f32` and `f64` are only `PartialOrd` because of NaN, thus they cannot be the weights
+//! of the algorithms that need `Ord`. `Float` is a weight that is not NaN and is ordered totally,
+//! so it can be the score of any `on_edge_custom` like method:
+//! check the graph with `check_weights` and pass `Float::unchecked` as the score function.
+//! The weighted algorithms have the shortcuts doing exactly that
+//! (`on_float_edge`, `yen_float`, `shortest_float`, `find_float` and so on),
+//! they return `NaNWeight` or, if the algorithm can fail on its own, `FloatError`.
+//! The weights are checked before the algorithm starts, thus nothing is computed
+//! if an edge has NaN as the weight(the error tells which one).
+//!
+//! The sums of floats are rounded, thus the paths of the same length can get slightly different scores.
+//! The algorithms compare the scores exactly, the ties within a tolerance(`approx_eq`) are taken
+//! by `AllShortestPaths::on_float_edge_with_tolerance` and `Schedule::is_critical_with_tolerance`.
+use crate::analyzer::min_weight::Score;
+use crate::DiGraph;
+use std::cmp::Ordering;
+use std::fmt::{Debug, Display, Formatter};
+use std::hash::Hash;
+use std::ops::{Add, Mul, Sub};
+
+/// The primitive float types(`f32`, `f64`) that can be wrapped into `Float`.
+pub trait FloatWeight:
+    Copy
+    + PartialOrd
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Default
+    + Display
+    + Debug
+{
+    fn is_nan(self) -> bool;
+    fn abs(self) -> Self;
+    fn total_cmp(&self, other: &Self) -> Ordering;
+}
+
+macro_rules! float_weight {
+    ($($t:ty),*) => {
+        $(
+            impl FloatWeight for $t {
+                fn is_nan(self) -> bool {
+                    <$t>::is_nan(self)
+                }
+                fn abs(self) -> Self {
+                    <$t>::abs(self)
+                }
+                fn total_cmp(&self, other: &Self) -> Ordering {
+                    <$t>::total_cmp(self, other)
+                }
+            }
+        )*
+    };
+}
+
+float_weight!(f32, f64);
+
+/// The weight is NaN.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NotANumber;
+
+impl Display for NotANumber {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the weight is NaN")
+    }
+}
+
+/// The edge having NaN as the weight.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NaNWeight<NId> {
+    from: NId,
+    to: NId,
+}
+
+impl<NId> NaNWeight<NId> {
+    pub fn from(&self) -> &NId {
+        &self.from
+    }
+    pub fn to(&self) -> &NId {
+        &self.to
+    }
+}
+
+impl<NId: ToString> Display for NaNWeight<NId> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "the edge {} -> {} has NaN weight",
+            self.from.to_string(),
+            self.to.to_string()
+        )
+    }
+}
+
+/// The heuristic gives NaN for the node.
+#[derive(Debug, Clone, PartialEq)]
+pub struct NaNHeuristic<NId> {
+    node: NId,
+}
+
+impl<NId> NaNHeuristic<NId> {
+    pub fn node(&self) -> &NId {
+        &self.node
+    }
+}
+
+impl<NId: ToString> Display for NaNHeuristic<NId> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "the heuristic of {} is NaN", self.node.to_string())
+    }
+}
+
+/// The error of the float shortcuts of the algorithms that can fail on their own(e.g. on a cycle).
+#[derive(Debug, Clone, PartialEq)]
+pub enum FloatError<NId, E> {
+    /// The edge has NaN as the weight, the algorithm is not started.
+    NaN(NaNWeight<NId>),
+    /// The algorithm failed.
+    Failed(E),
+}
+
+impl<NId, E> From<NaNWeight<NId>> for FloatError<NId, E> {
+    fn from(err: NaNWeight<NId>) -> Self {
+        FloatError::NaN(err)
+    }
+}
+
+impl<NId: ToString, E: Display> Display for FloatError<NId, E> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FloatError::NaN(err) => Display::fmt(err, f),
+            FloatError::Failed(err) => Display::fmt(err, f),
+        }
+    }
+}
+
+/// The float weight ordered by `total_cmp`.
+/// The negative zero is turned into the zero, thus they are equal.
+#[derive(Debug, Clone, Copy, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(transparent)
+)]
+pub struct Float<F>(F);
+
+impl<F: FloatWeight> Float<F> {
+    pub fn new(value: F) -> Result<Self, NotANumber> {
+        if value.is_nan() {
+            Err(NotANumber)
+        } else {
+            Ok(Self::unchecked(value))
+        }
+    }
+
+    /// Wraps the value without the check, the caller makes sure it is not NaN(see `check_weights`).
+    /// Otherwise NaN is greater than the infinity.
+    pub fn unchecked(value: F) -> Self {
+        // -0.0 + 0.0 is 0.0
+        Float(value + F::default())
+    }
+
+    pub fn value(&self) -> F {
+        self.0
+    }
+
+    /// The values are equal if the difference is within the tolerance,
+    /// relative to the magnitude for the values greater than one.
+    pub fn approx_eq(&self, other: &Self, tolerance: F) -> bool {
+        let (lhs, rhs) = (self.0, other.0);
+        if lhs == rhs {
+            return true;
+        }
+        let larger = if lhs.abs() > rhs.abs() {
+            lhs.abs()
+        } else {
+            rhs.abs()
+        };
+        let relative = larger * tolerance;
+        let bound = if relative > tolerance {
+            relative
+        } else {
+            tolerance
+        };
+        (lhs - rhs).abs() <= bound
+    }
+
+    /// The same as `cmp` but the values within the tolerance are equal.
+    pub fn approx_cmp(&self, other: &Self, tolerance: F) -> Ordering {
+        if self.approx_eq(other, tolerance) {
+            Ordering::Equal
+        } else {
+            self.cmp(other)
+        }
+    }
+}
+
+impl<F: FloatWeight> Score<Float<F>> {
+    /// The scores are equal if both are the same variant and the values are within the tolerance.
+    pub fn approx_eq(&self, other: &Self, tolerance: F) -> bool {
+        match (self, other) {
+            (Score::Inf, Score::Inf) | (Score::Zero, Score::Zero) => true,
+            (Score::Value(lhs), Score::Value(rhs)) => lhs.approx_eq(rhs, tolerance),
+            _ => false,
+        }
+    }
+}
+
+impl<F: FloatWeight> PartialEq for Float<F> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<F: FloatWeight> Eq for Float<F> {}
+
+impl<F: FloatWeight> PartialOrd for Float<F> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<F: FloatWeight> Ord for Float<F> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0.total_cmp(&other.0)
+    }
+}
+
+impl<F: FloatWeight> Add for Float<F> {
+    type Output = Self;
+
+    fn add(self, rhs: Self) -> Self::Output {
+        Float::unchecked(self.0 + rhs.0)
+    }
+}
+
+impl<F: FloatWeight> Sub for Float<F> {
+    type Output = Self;
+
+    fn sub(self, rhs: Self) -> Self::Output {
+        Float::unchecked(self.0 - rhs.0)
+    }
+}
+
+impl<F: FloatWeight> Display for Float<F> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        Display::fmt(&self.0, f)
+    }
+}
+
+/// Checks that no edge of the graph has NaN as the weight.
+pub fn check_weights<NId, NL, F>(graph: &DiGraph<NId, NL, F>) -> Result<(), NaNWeight<NId>>
+where
+    NId: Eq + Hash + Clone,
+    F: FloatWeight,
+{
+    for (from, tos) in graph.edges.iter() {
+        if let Some((to, _)) = tos.iter().find(|(_, w)| w.is_nan()) {
+            return Err(NaNWeight {
+                from: from.clone(),
+                to: to.clone(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Checks that the heuristic gives no NaN for the nodes of the graph.
+pub(crate) fn check_heuristic<NId, NL, EL, F, H>(
+    graph: &DiGraph<NId, NL, EL>,
+    heuristic: H,
+) -> Result<(), NaNHeuristic<NId>>
+where
+    NId: Eq + Hash + Clone,
+    F: FloatWeight,
+    H: Fn(&NId) -> F,
+{
+    match graph.nodes.keys().find(|id| heuristic(id).is_nan()) {
+        Some(node) => Err(NaNHeuristic { node: node.clone() }),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::all_pairs::{FloydWarshall, Johnson};
+    use crate::analyzer::all_shortest::AllShortestPaths;
+    use crate::analyzer::astar::AStarPath;
+    use crate::analyzer::bellman_ford::BellmanFordPath;
+    use crate::analyzer::bidirectional::BidirectionalDijkstra;
+    use crate::analyzer::contraction::ContractionHierarchy;
+    use crate::analyzer::cpm::CriticalPathMethod;
+    use crate::analyzer::dag_path::DagPath;
+    use crate::analyzer::dijkstra::DijkstraPath;
+    use crate::analyzer::float::{check_weights, Float, FloatError, NaNWeight, NotANumber};
+    use crate::analyzer::k_shortest::KShortestPaths;
+    use crate::analyzer::landmarks::LandmarkSelection;
+    use crate::analyzer::min_weight::Score;
+    use crate::analyzer::mst::MinimumSpanningArborescence;
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+    use std::cmp::Ordering;
+
+    type Shortcut = fn(&DiGraph<usize, EmptyPayload, f64>) -> Option<NaNWeight<usize>>;
+
+    fn nan<T, E>(res: Result<T, FloatError<usize, E>>) -> Option<NaNWeight<usize>> {
+        match res {
+            Err(FloatError::NaN(err)) => Some(err),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn order_test() {
+        assert_eq!(Float::new(f64::NAN), Err(NotANumber));
+        let mut values: Vec<Float<f64>> = [2.5, -1.0, f64::INFINITY, 0.0, -0.0]
+            .into_iter()
+            .map(|v| Float::new(v).unwrap())
+            .collect();
+        values.sort();
+        let values: Vec<f64> = values.iter().map(Float::value).collect();
+        assert_eq!(values, vec![-1.0, 0.0, 0.0, 2.5, f64::INFINITY]);
+        assert_eq!(Float::new(-0.0), Float::new(0.0));
+        assert_eq!(
+            (Float::unchecked(1.5f32) + Float::unchecked(2.0)).to_string(),
+            "3.5"
+        );
+    }
+
+    #[test]
+    fn tolerance_test() {
+        let sum = Float::unchecked(0.1) + Float::unchecked(0.2);
+        let expected = Float::unchecked(0.3);
+        assert_ne!(sum, expected);
+        assert!(sum.approx_eq(&expected, 1e-9));
+        assert_eq!(sum.approx_cmp(&expected, 1e-9), Ordering::Equal);
+        assert_eq!(sum.approx_cmp(&expected, 0.0), Ordering::Greater);
+        // relative to the magnitude
+        assert!(Float::unchecked(1e12).approx_eq(&Float::unchecked(1e12 + 1.0), 1e-9));
+        assert!(!Float::unchecked(1.0).approx_eq(&Float::unchecked(1.1), 1e-9));
+
+        assert!(Score::Value(sum).approx_eq(&Score::Value(expected), 1e-9));
+        assert!(Score::<Float<f64>>::Inf.approx_eq(&Score::Inf, 1e-9));
+        assert!(!Score::Zero.approx_eq(&Score::Value(expected), 1e-9));
+    }
+
+    #[test]
+    fn check_weights_test() {
+        let mut graph = digraph!((usize,_,f64) => [1,2,3] => {
+           1 => (2,0.5);
+           2 => (3,1.5);
+        });
+        assert_eq!(check_weights(&graph), Ok(()));
+        graph.add_edge(3, 1, f64::NAN);
+        let err: NaNWeight<usize> = check_weights(&graph).unwrap_err();
+        assert_eq!((err.from(), err.to()), (&3, &1));
+        assert_eq!(err.to_string(), "the edge 3 -> 1 has NaN weight");
+    }
+
+    #[test]
+    fn nan_rejection_test() {
+        let graph = digraph!((usize,_,f64) => [1,2,3] => {
+           1 => [(2,0.5),(3,1.5)];
+           2 => (3,f64::NAN);
+        });
+        let shortcuts: Vec<(&str, Shortcut)> = vec![
+            ("dijkstra", |g| DijkstraPath::new(g).on_float_edge(1).err()),
+            ("astar", |g| {
+                nan(AStarPath::new(g).on_float_edge(1, 3, |_| 0.0))
+            }),
+            ("bellman_ford", |g| {
+                nan(BellmanFordPath::new(g).on_float_edge(1))
+            }),
+            ("floyd_warshall", |g| {
+                nan(FloydWarshall::new(g).on_float_edge())
+            }),
+            ("johnson", |g| nan(Johnson::new(g).on_float_edge())),
+            ("yen", |g| KShortestPaths::new(g).yen_float(1, 3, 2).err()),
+            ("walks", |g| {
+                KShortestPaths::new(g).walks_float(1, 3, 2).err()
+            }),
+            ("bidirectional", |g| {
+                BidirectionalDijkstra::new(g).on_float_edge(1, 3).err()
+            }),
+            ("landmarks", |g| {
+                LandmarkSelection::new(g).on_float_edge().err()
+            }),
+            ("contraction", |g| {
+                ContractionHierarchy::new(g).on_float_edge().err()
+            }),
+            ("all_shortest", |g| {
+                AllShortestPaths::new(g).on_float_edge(1).err()
+            }),
+            ("all_shortest_with_tolerance", |g| {
+                AllShortestPaths::new(g)
+                    .on_float_edge_with_tolerance(1, 1e-9)
+                    .err()
+            }),
+            ("dag_shortest", |g| nan(DagPath::new(g).shortest_float(1))),
+            ("dag_longest", |g| nan(DagPath::new(g).longest_float(1))),
+            ("cpm", |g| nan(CriticalPathMethod::new(g).on_float_edge())),
+            ("mst", |g| {
+                MinimumSpanningArborescence::new(g)
+                    .find_float()
+                    .map(|_| ())
+                    .err()
+            }),
+        ];
+        for (name, shortcut) in shortcuts {
+            let err = shortcut(&graph).unwrap_or_else(|| panic!("{} accepts NaN", name));
+            assert_eq!((err.from(), err.to()), (&2, &3), "{}", name);
+        }
+    }
+}
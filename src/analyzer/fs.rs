@@ -7,27 +7,68 @@ use crate::{
     analyzer::SearchRes,
     visualizer::dot::{DotProcessor, ToStringProcessor},
 };
-use std::collections::VecDeque;
+use std::collections::{HashMap, VecDeque};
 use std::fmt::Debug;
 use std::hash::Hash;
 
+use super::predecessors::ReverseIndex;
+use super::trace::{emit, NoTrace, TraceEvent, Tracer};
 use super::visit::{Visited, VisitedSet};
 use crate::DiGraph;
 
-struct DFS<'a, NId, NL, EL>
+/// The found node with the path from the start(both ends are included).
+#[derive(Debug, Clone, PartialEq)]
+pub struct Found<'a, NId> {
+    path: Vec<&'a NId>,
+}
+
+impl<'a, NId> Found<'a, NId> {
+    pub(crate) fn new(path: Vec<&'a NId>) -> Self {
+        Self { path }
+    }
+    pub fn node(&self) -> &'a NId {
+        self.path
+            .last()
+            .expect("the path contains at least the start")
+    }
+    pub fn path(&self) -> &Vec<&'a NId> {
+        &self.path
+    }
+    /// The number of edges from the start.
+    pub fn depth(&self) -> usize {
+        self.path.len() - 1
+    }
+}
+
+/// Depth-first search.
+/// The nodes are marked as visited when they are put onto the stack, thus every node is checked once.
+pub struct DFS<'a, NId, NL, EL>
 where
     NId: Eq + Hash,
 {
     graph: &'a DiGraph<NId, NL, EL>,
+    max_depth: Option<usize>,
 }
 
 impl<'a, NId, NL, EL> DFS<'a, NId, NL, EL>
 where
     NId: Eq + Hash + Clone,
 {
-    fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
-        Self { graph }
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self {
+            graph,
+            max_depth: None,
+        }
     }
+
+    /// The nodes deeper than the given depth(the number of edges from the start) are not checked.
+    pub fn with_max_depth(self, max_depth: usize) -> Self {
+        Self {
+            max_depth: Some(max_depth),
+            ..self
+        }
+    }
+
     pub fn search_by_eq(&self, start: &'a NId, target: &'a NId) -> Option<&'a NId> {
         self.search(start, |n| {
             if target == n {
@@ -37,50 +78,92 @@ where
             }
         })
     }
-    fn search<S>(&self, start: &'a NId, target: S) -> Option<&'a NId>
+    pub fn search<S>(&self, start: &'a NId, target: S) -> Option<&'a NId>
     where
         S: Fn(&'a NId) -> SearchRes,
     {
-        let mut visited = VisitedSet::default();
-        let mut q = vec![];
-
-        q.push(start);
-        visited.visit(start);
-        while let Some(node) = q.pop() {
-            match target(node) {
-                SearchRes::Next => {
-                    for nexts in self.graph.successors(node) {
-                        for s in nexts.keys() {
-                            if !visited.is_visited(s) {
-                                q.push(s);
-                                visited.visit(s);
-                            }
-                        }
-                    }
-                }
-                SearchRes::Skip => (),
-                SearchRes::Find => return Some(node),
-                SearchRes::Stop => return None,
-            }
-        }
+        self.search_traced(start, target, &mut NoTrace)
+    }
 
-        None
+    /// The same as `search` but reports push, pop and visit events to the tracer.
+    pub fn search_traced<S, T>(&self, start: &'a NId, target: S, tracer: &mut T) -> Option<&'a NId>
+    where
+        S: Fn(&'a NId) -> SearchRes,
+        T: Tracer<NId>,
+    {
+        let found = walk(
+            self.graph,
+            Line::Stack,
+            start,
+            self.max_depth,
+            false,
+            target,
+            tracer,
+        );
+        found.into_iter().next().map(|f| f.node())
+    }
+
+    /// Returns the first node(with the path) matching the predicate over the id and the payload.
+    /// The nodes that have no payload(do not exist in the graph) are not checked.
+    pub fn find<P>(&self, start: &'a NId, predicate: P) -> Option<Found<'a, NId>>
+    where
+        P: Fn(&'a NId, &'a NL) -> SearchRes,
+    {
+        self.find_with(start, predicate, false).into_iter().next()
+    }
+
+    /// Returns all nodes(with the paths) matching the predicate over the id and the payload.
+    pub fn find_all<P>(&self, start: &'a NId, predicate: P) -> Vec<Found<'a, NId>>
+    where
+        P: Fn(&'a NId, &'a NL) -> SearchRes,
+    {
+        self.find_with(start, predicate, true)
+    }
+
+    fn find_with<P>(&self, start: &'a NId, predicate: P, all: bool) -> Vec<Found<'a, NId>>
+    where
+        P: Fn(&'a NId, &'a NL) -> SearchRes,
+    {
+        let target = by_payload(self.graph, predicate);
+        walk(
+            self.graph,
+            Line::Stack,
+            start,
+            self.max_depth,
+            all,
+            target,
+            &mut NoTrace,
+        )
     }
 }
 
-struct BFS<'a, NId, NL, EL>
+/// Breadth-first search.
+/// The found paths are the shortest ones(in terms of the number of edges).
+pub struct BFS<'a, NId, NL, EL>
 where
     NId: Eq + Hash,
 {
     graph: &'a DiGraph<NId, NL, EL>,
+    max_depth: Option<usize>,
 }
 
 impl<'a, NId, NL, EL> BFS<'a, NId, NL, EL>
 where
     NId: Eq + Hash + Clone,
 {
-    fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
-        BFS { graph }
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        BFS {
+            graph,
+            max_depth: None,
+        }
+    }
+
+    /// The nodes deeper than the given depth(the number of edges from the start) are not checked.
+    pub fn with_max_depth(self, max_depth: usize) -> Self {
+        Self {
+            max_depth: Some(max_depth),
+            ..self
+        }
     }
 
     pub fn search_by_eq(&self, start: &'a NId, target: &'a NId) -> Option<&'a NId> {
@@ -92,35 +175,270 @@ where
             }
         })
     }
-    fn search<S>(&self, start: &'a NId, target: S) -> Option<&'a NId>
+    pub fn search<S>(&self, start: &'a NId, target: S) -> Option<&'a NId>
     where
         S: Fn(&'a NId) -> SearchRes,
     {
-        let mut visited = VisitedSet::default();
-        let mut q = VecDeque::new();
-
-        q.push_back(start);
-        visited.visit(start);
-        while let Some(node) = q.pop_front() {
-            match target(node) {
-                SearchRes::Next => {
-                    for nexts in self.graph.successors(node) {
-                        for s in nexts.keys() {
-                            if !visited.is_visited(s) {
-                                q.push_back(s);
-                                visited.visit(s);
-                            }
-                        }
+        self.search_traced(start, target, &mut NoTrace)
+    }
+
+    /// The same as `search` but reports push, pop and visit events to the tracer.
+    pub fn search_traced<S, T>(&self, start: &'a NId, target: S, tracer: &mut T) -> Option<&'a NId>
+    where
+        S: Fn(&'a NId) -> SearchRes,
+        T: Tracer<NId>,
+    {
+        let found = walk(
+            self.graph,
+            Line::Queue,
+            start,
+            self.max_depth,
+            false,
+            target,
+            tracer,
+        );
+        found.into_iter().next().map(|f| f.node())
+    }
+
+    /// Returns the first node(with the path) matching the predicate over the id and the payload.
+    /// The nodes that have no payload(do not exist in the graph) are not checked.
+    pub fn find<P>(&self, start: &'a NId, predicate: P) -> Option<Found<'a, NId>>
+    where
+        P: Fn(&'a NId, &'a NL) -> SearchRes,
+    {
+        self.find_with(start, predicate, false).into_iter().next()
+    }
+
+    /// Returns all nodes(with the paths) matching the predicate over the id and the payload.
+    pub fn find_all<P>(&self, start: &'a NId, predicate: P) -> Vec<Found<'a, NId>>
+    where
+        P: Fn(&'a NId, &'a NL) -> SearchRes,
+    {
+        self.find_with(start, predicate, true)
+    }
+
+    fn find_with<P>(&self, start: &'a NId, predicate: P, all: bool) -> Vec<Found<'a, NId>>
+    where
+        P: Fn(&'a NId, &'a NL) -> SearchRes,
+    {
+        let target = by_payload(self.graph, predicate);
+        walk(
+            self.graph,
+            Line::Queue,
+            start,
+            self.max_depth,
+            all,
+            target,
+            &mut NoTrace,
+        )
+    }
+}
+
+/// Bidirectional breadth-first search: the searches from the source(forward) and from the target
+/// (backward, along the incoming edges) go level by level and meet in the middle.
+/// It visits far fewer nodes than `BFS` for the s-t queries on large graphs.
+/// The reverse index is built once, thus the struct should be reused for many queries.
+pub struct BidirectionalBFS<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+    index: ReverseIndex<'a, NId, EL>,
+}
+
+impl<'a, NId, NL, EL> BidirectionalBFS<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self {
+            graph,
+            index: ReverseIndex::new(graph),
+        }
+    }
+
+    pub fn is_reachable(&self, source: &'a NId, target: &'a NId) -> bool {
+        self.shortest_path(source, target).is_some()
+    }
+
+    /// Returns the path with the minimal number of edges from the source to the target.
+    pub fn shortest_path(&self, source: &'a NId, target: &'a NId) -> Option<Found<'a, NId>> {
+        if source == target {
+            return Some(Found { path: vec![source] });
+        }
+        let mut fwd = Frontier::new(source);
+        let mut bwd = Frontier::new(target);
+
+        while !fwd.line.is_empty() && !bwd.line.is_empty() {
+            // the smaller frontier is expanded by one level
+            let meet = if fwd.line.len() <= bwd.line.len() {
+                fwd.expand(&bwd, |id| self.graph.successor_ids(id))
+            } else {
+                bwd.expand(&fwd, |id| self.index.predecessor_ids(id))
+            };
+            if let Some(meet) = meet {
+                let mut path = path(&fwd.parents, meet);
+                let mut tail = path_to_root(&bwd.parents, meet);
+                tail.remove(0);
+                path.extend(tail);
+                return Some(Found { path });
+            }
+        }
+        None
+    }
+}
+
+/// One side of the bidirectional search.
+struct Frontier<'a, NId> {
+    depth: HashMap<&'a NId, usize>,
+    parents: HashMap<&'a NId, &'a NId>,
+    line: Vec<&'a NId>,
+}
+
+impl<'a, NId> Frontier<'a, NId>
+where
+    NId: Eq + Hash,
+{
+    fn new(root: &'a NId) -> Self {
+        Self {
+            depth: HashMap::from([(root, 0)]),
+            parents: HashMap::new(),
+            line: vec![root],
+        }
+    }
+
+    /// Expands the whole level and returns the meeting node with the minimal total depth if any.
+    fn expand<F>(&mut self, other: &Frontier<'a, NId>, next: F) -> Option<&'a NId>
+    where
+        F: Fn(&NId) -> Vec<&'a NId>,
+    {
+        let mut best: Option<(usize, &NId)> = None;
+        let mut line = vec![];
+        for node in std::mem::take(&mut self.line) {
+            let d = self.depth[node] + 1;
+            for s in next(node) {
+                if self.depth.contains_key(s) {
+                    continue;
+                }
+                self.depth.insert(s, d);
+                self.parents.insert(s, node);
+                line.push(s);
+                if let Some(od) = other.depth.get(s) {
+                    if best.map(|(b, _)| d + od < b).unwrap_or(true) {
+                        best = Some((d + od, s));
                     }
                 }
-                SearchRes::Skip => (),
-                SearchRes::Find => return Some(node),
-                SearchRes::Stop => return None,
             }
         }
+        self.line = line;
+        best.map(|(_, id)| id)
+    }
+}
 
-        None
+#[derive(Clone, Copy, PartialEq)]
+enum Line {
+    Stack,
+    Queue,
+}
+
+fn by_payload<'a, NId, NL, EL, P>(
+    graph: &'a DiGraph<NId, NL, EL>,
+    predicate: P,
+) -> impl Fn(&'a NId) -> SearchRes
+where
+    NId: Eq + Hash,
+    P: Fn(&'a NId, &'a NL) -> SearchRes,
+{
+    move |id| match graph.nodes.get(id) {
+        Some(nl) => predicate(id, nl),
+        None => SearchRes::Next,
+    }
+}
+
+/// The common part of dfs and bfs: the only difference is the side the next node is taken from.
+fn walk<'a, NId, NL, EL, S, T>(
+    graph: &'a DiGraph<NId, NL, EL>,
+    kind: Line,
+    start: &'a NId,
+    max_depth: Option<usize>,
+    all: bool,
+    target: S,
+    tracer: &mut T,
+) -> Vec<Found<'a, NId>>
+where
+    NId: Eq + Hash + Clone,
+    S: Fn(&'a NId) -> SearchRes,
+    T: Tracer<NId>,
+{
+    let mut visited = VisitedSet::default();
+    let mut parents: HashMap<&NId, &NId> = HashMap::new();
+    let mut found = vec![];
+    let mut q = VecDeque::new();
+
+    q.push_back((start, 0));
+    visited.visit(start);
+    emit(tracer, || TraceEvent::Push(start.clone()));
+    loop {
+        let next = match kind {
+            Line::Stack => q.pop_back(),
+            Line::Queue => q.pop_front(),
+        };
+        let Some((node, depth)) = next else {
+            break;
+        };
+        emit(tracer, || TraceEvent::Pop(node.clone()));
+        emit(tracer, || TraceEvent::Visit(node.clone()));
+        let expand = match target(node) {
+            SearchRes::Next => true,
+            SearchRes::Skip => false,
+            SearchRes::Find => {
+                found.push(Found {
+                    path: path(&parents, node),
+                });
+                if !all {
+                    break;
+                }
+                true
+            }
+            SearchRes::Stop => break,
+        };
+        if expand && max_depth.map(|max| depth < max).unwrap_or(true) {
+            for nexts in graph.successors(node) {
+                for s in nexts.keys() {
+                    if !visited.is_visited(s) {
+                        q.push_back((s, depth + 1));
+                        visited.visit(s);
+                        parents.insert(s, node);
+                        emit(tracer, || TraceEvent::Push(s.clone()));
+                    }
+                }
+            }
+        }
+    }
+
+    found
+}
+
+fn path<'a, NId>(parents: &HashMap<&'a NId, &'a NId>, node: &'a NId) -> Vec<&'a NId>
+where
+    NId: Eq + Hash,
+{
+    let mut path = path_to_root(parents, node);
+    path.reverse();
+    path
+}
+
+fn path_to_root<'a, NId>(parents: &HashMap<&'a NId, &'a NId>, node: &'a NId) -> Vec<&'a NId>
+where
+    NId: Eq + Hash,
+{
+    let mut path = vec![node];
+    let mut cur = node;
+    while let Some(p) = parents.get(cur) {
+        path.push(p);
+        cur = p;
     }
+    path
 }
 
 struct SrcTrgHighlighter<'a, NId>
@@ -168,7 +486,9 @@ where
 
 #[cfg(test)]
 mod tests {
-    use crate::analyzer::fs::{SearchRes, SrcTrgHighlighter, BFS, DFS};
+    use crate::analyzer::fs::{SrcTrgHighlighter, BFS, DFS};
+    use crate::analyzer::trace::{Trace, TraceEvent};
+    use crate::analyzer::SearchRes;
     use crate::DiGraph;
     use crate::EmptyPayload;
     use crate::{digraph, extend_edges, extend_nodes};
@@ -254,4 +574,118 @@ mod tests {
             .visualize()
             .to_dot_file("dots/test.svg", SrcTrgHighlighter::new(&1, &10));
     }
+
+    #[test]
+    fn traced_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3,4] => {
+            1 => [2,3];
+            3 => 4;
+        });
+        let mut trace = Trace::new();
+        let res = BFS::new(&graph).search_traced(
+            &1,
+            |n| {
+                if n == &4 {
+                    SearchRes::Find
+                } else {
+                    SearchRes::Next
+                }
+            },
+            &mut trace,
+        );
+        assert_eq!(res, Some(&4));
+        let events = trace.events();
+        assert_eq!(events[0], TraceEvent::Push(1));
+        assert_eq!(events[1], TraceEvent::Pop(1));
+        assert_eq!(events.last(), Some(&TraceEvent::Visit(4)));
+    }
+
+    #[test]
+    fn find_test() {
+        let graph = digraph!((usize,&str,EmptyPayload) => [(1,"a"),(2,"b"),(3,"c"),(4,"b"),(5,"b")] => {
+            1 => [2,3];
+            3 => 4;
+            4 => 5;
+        });
+
+        let bfs = graph.analyze().bfs();
+        let found = bfs.find(&1, |_, nl| {
+            if *nl == "c" {
+                SearchRes::Find
+            } else {
+                SearchRes::Next
+            }
+        });
+        let found = found.unwrap();
+        assert_eq!(found.node(), &3);
+        assert_eq!(found.path(), &vec![&1, &3]);
+        assert_eq!(found.depth(), 1);
+
+        let by_b = |_: &usize, nl: &&str| {
+            if *nl == "b" {
+                SearchRes::Find
+            } else {
+                SearchRes::Next
+            }
+        };
+        let mut all: Vec<Vec<&usize>> = bfs
+            .find_all(&1, by_b)
+            .into_iter()
+            .map(|f| f.path().clone())
+            .collect();
+        all.sort();
+        assert_eq!(
+            all,
+            vec![vec![&1, &2], vec![&1, &3, &4], vec![&1, &3, &4, &5]]
+        );
+
+        let dfs = DFS::new(&graph).with_max_depth(2);
+        let mut all: Vec<&usize> = dfs.find_all(&1, by_b).iter().map(|f| f.node()).collect();
+        all.sort();
+        assert_eq!(all, vec![&2, &4]);
+
+        let skip_3 = |id: &usize, nl: &&str| match (id, *nl) {
+            (3, _) => SearchRes::Skip,
+            (_, "b") => SearchRes::Find,
+            _ => SearchRes::Next,
+        };
+        let all: Vec<&usize> = dfs.find_all(&1, skip_3).iter().map(|f| f.node()).collect();
+        assert_eq!(all, vec![&2]);
+
+        let stop_3 = |id: &usize, _: &&str| {
+            if *id == 3 {
+                SearchRes::Stop
+            } else {
+                SearchRes::Next
+            }
+        };
+        assert!(bfs.find(&1, stop_3).is_none());
+    }
+
+    #[test]
+    fn bidirectional_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3,4,5,6,7,8] => {
+            1 => [2,3];
+            2 => 4;
+            3 => 5;
+            4 => 6;
+            5 => 6;
+            6 => 7;
+            1 => 7;
+            8 => 1;
+        });
+        let search = graph.analyze().bidirectional_bfs();
+        assert_eq!(search.shortest_path(&1, &7).unwrap().path(), &vec![&1, &7]);
+
+        let found = search.shortest_path(&1, &6).unwrap();
+        assert_eq!(found.depth(), 3);
+        assert_eq!(found.path()[0], &1);
+        assert_eq!(found.node(), &6);
+
+        assert_eq!(search.shortest_path(&8, &6).unwrap().depth(), 4);
+        assert_eq!(search.shortest_path(&4, &4).unwrap().path(), &vec![&4]);
+        assert!(search.is_reachable(&2, &7));
+        assert!(!search.is_reachable(&7, &1));
+        assert!(search.shortest_path(&6, &2).is_none());
+    }
 }
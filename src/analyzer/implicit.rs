@@ -0,0 +1,342 @@
+//! Searches over the graphs that are defined by a successor function.
+//!
+//! The state spaces of puzzles or state machines are often too big to be materialised as `DiGraph`,
+//! thus `ImplicitGraph` generates the successors(with the payloads of the edges) on demand.
+//! The generated states are kept only while the search is running and they are identified by value,
+//! thus the states need to be `Clone + Eq + Hash`.
+use crate::analyzer::min_weight::{MinIndexed, Score};
+use crate::analyzer::visit::{OwnedVisitedSet, Visited};
+use std::collections::{BinaryHeap, HashMap, VecDeque};
+use std::convert::identity;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::ops::Add;
+
+/// The found path with the cost.
+#[derive(Debug, Clone)]
+pub struct ImplicitPath<S, ScoreV> {
+    states: Vec<S>,
+    cost: Score<ScoreV>,
+}
+
+impl<S, ScoreV> ImplicitPath<S, ScoreV> {
+    /// The states from the start to the goal(both ends are included).
+    pub fn states(&self) -> &Vec<S> {
+        &self.states
+    }
+    pub fn into_states(self) -> Vec<S> {
+        self.states
+    }
+    pub fn cost(&self) -> &Score<ScoreV> {
+        &self.cost
+    }
+}
+
+/// The graph given by the function that returns the successors of a state with the edge payloads.
+///
+/// # Example
+/// ```rust
+/// use digraph_rs::analyzer::implicit::ImplicitGraph;
+/// // the states are numbers, the moves are +1 and *2
+/// let graph = ImplicitGraph::new(|n: &usize| vec![(n + 1, 1), (n * 2, 1)]);
+/// let path = graph.bfs(1, |n| *n == 10).unwrap();
+/// assert_eq!(path, vec![1, 2, 4, 5, 10]);
+/// ```
+pub struct ImplicitGraph<S, EL, F> {
+    successors: F,
+    max_depth: Option<usize>,
+    _payload: PhantomData<fn(&S) -> EL>,
+}
+
+impl<S, EL, F, I> ImplicitGraph<S, EL, F>
+where
+    S: Clone + Eq + Hash,
+    F: Fn(&S) -> I,
+    I: IntoIterator<Item = (S, EL)>,
+{
+    pub fn new(successors: F) -> Self {
+        Self {
+            successors,
+            max_depth: None,
+            _payload: PhantomData,
+        }
+    }
+
+    /// The states deeper than the given depth(the number of moves from the start)
+    /// are not generated by bfs and dfs.
+    pub fn with_max_depth(self, max_depth: usize) -> Self {
+        Self {
+            max_depth: Some(max_depth),
+            ..self
+        }
+    }
+
+    pub fn successors(&self, state: &S) -> I {
+        (self.successors)(state)
+    }
+
+    /// Breadth-first search. The found path has the minimal number of moves.
+    pub fn bfs<G>(&self, start: S, goal: G) -> Option<Vec<S>>
+    where
+        G: Fn(&S) -> bool,
+    {
+        self.bfs_with(start, goal, &mut OwnedVisitedSet::default())
+    }
+
+    /// The same as `bfs` but with the given tracking of the visited states.
+    pub fn bfs_with<G, V>(&self, start: S, goal: G, visited: &mut V) -> Option<Vec<S>>
+    where
+        G: Fn(&S) -> bool,
+        V: for<'s> Visited<'s, S>,
+    {
+        self.walk(start, goal, visited, true)
+    }
+
+    /// Depth-first search. The states are marked as visited when they are put onto the stack.
+    pub fn dfs<G>(&self, start: S, goal: G) -> Option<Vec<S>>
+    where
+        G: Fn(&S) -> bool,
+    {
+        self.dfs_with(start, goal, &mut OwnedVisitedSet::default())
+    }
+
+    /// The same as `dfs` but with the given tracking of the visited states.
+    pub fn dfs_with<G, V>(&self, start: S, goal: G, visited: &mut V) -> Option<Vec<S>>
+    where
+        G: Fn(&S) -> bool,
+        V: for<'s> Visited<'s, S>,
+    {
+        self.walk(start, goal, visited, false)
+    }
+
+    /// Dijkstra's algorithm where the edge payloads are turned into the weights.
+    pub fn dijkstra_custom<G, E, ScoreV>(
+        &self,
+        start: S,
+        goal: G,
+        edge_w: E,
+    ) -> Option<ImplicitPath<S, ScoreV>>
+    where
+        G: Fn(&S) -> bool,
+        E: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone,
+    {
+        self.best_first(start, goal, |_| Score::Zero, edge_w)
+    }
+
+    /// A* search with the same signature of the heuristic as `AStarPath`.
+    /// The heuristic should be consistent since the settled states are not reopened.
+    pub fn astar_custom<G, H, E, ScoreV>(
+        &self,
+        start: S,
+        goal: G,
+        heuristic: H,
+        edge_w: E,
+    ) -> Option<ImplicitPath<S, ScoreV>>
+    where
+        G: Fn(&S) -> bool,
+        H: Fn(&S) -> ScoreV,
+        E: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone,
+    {
+        self.best_first(start, goal, |s| Score::Value(heuristic(s)), edge_w)
+    }
+
+    /// The common part of bfs and dfs. The states are kept in an arena and the parents are indexes.
+    fn walk<G, V>(&self, start: S, goal: G, visited: &mut V, bfs: bool) -> Option<Vec<S>>
+    where
+        G: Fn(&S) -> bool,
+        V: for<'s> Visited<'s, S>,
+    {
+        let mut states: Vec<(S, Option<usize>, usize)> = vec![];
+        let mut line = VecDeque::from([0]);
+        visited.visit(&start);
+        states.push((start, None, 0));
+
+        loop {
+            let next = if bfs {
+                line.pop_front()
+            } else {
+                line.pop_back()
+            };
+            let idx = next?;
+            let (state, _, depth) = &states[idx];
+            if goal(state) {
+                return Some(path(&states, idx));
+            }
+            let depth = *depth;
+            if self.max_depth.map(|max| depth < max).unwrap_or(true) {
+                for (s, _) in self.successors(state) {
+                    if visited.visit(&s) {
+                        line.push_back(states.len());
+                        states.push((s, Some(idx), depth + 1));
+                    }
+                }
+            }
+        }
+    }
+
+    /// The common part of dijkstra and A*. The stale entries of the heap are skipped.
+    fn best_first<G, H, E, ScoreV>(
+        &self,
+        start: S,
+        goal: G,
+        heuristic: H,
+        edge_w: E,
+    ) -> Option<ImplicitPath<S, ScoreV>>
+    where
+        G: Fn(&S) -> bool,
+        H: Fn(&S) -> Score<ScoreV>,
+        E: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone,
+    {
+        let mut settled = OwnedVisitedSet::default();
+        let mut index: HashMap<S, usize> = HashMap::new();
+        let mut states: Vec<(S, Option<usize>, Score<ScoreV>)> = vec![];
+        let mut heap = BinaryHeap::new();
+
+        heap.push(MinIndexed(0, heuristic(&start)));
+        index.insert(start.clone(), 0);
+        states.push((start, None, Score::Zero));
+
+        while let Some(MinIndexed(idx, _)) = heap.pop() {
+            let (state, _, score) = &states[idx];
+            if !settled.visit(state) {
+                continue;
+            }
+            if goal(state) {
+                let cost = score.clone();
+                let states = path(&states, idx);
+                return Some(ImplicitPath { states, cost });
+            }
+            let score = score.clone();
+            for (s, el) in self.successors(state) {
+                if settled.is_visited(&s) {
+                    continue;
+                }
+                let alt = score.add_score_v(edge_w(el));
+                let estimation = alt.clone() + heuristic(&s);
+                match index.get(&s) {
+                    Some(&i) if states[i].2 <= alt => (),
+                    Some(&i) => {
+                        states[i].1 = Some(idx);
+                        states[i].2 = alt;
+                        heap.push(MinIndexed(i, estimation));
+                    }
+                    None => {
+                        index.insert(s.clone(), states.len());
+                        heap.push(MinIndexed(states.len(), estimation));
+                        states.push((s, Some(idx), alt));
+                    }
+                }
+            }
+        }
+        None
+    }
+}
+
+impl<S, EL, F, I> ImplicitGraph<S, EL, F>
+where
+    S: Clone + Eq + Hash,
+    F: Fn(&S) -> I,
+    I: IntoIterator<Item = (S, EL)>,
+    EL: Ord + Add<Output = EL> + Clone,
+{
+    /// Dijkstra's algorithm where the edge payloads are the weights.
+    pub fn dijkstra<G>(&self, start: S, goal: G) -> Option<ImplicitPath<S, EL>>
+    where
+        G: Fn(&S) -> bool,
+    {
+        self.dijkstra_custom(start, goal, identity)
+    }
+
+    /// A* search where the edge payloads are the weights.
+    pub fn astar<G, H>(&self, start: S, goal: G, heuristic: H) -> Option<ImplicitPath<S, EL>>
+    where
+        G: Fn(&S) -> bool,
+        H: Fn(&S) -> EL,
+    {
+        self.astar_custom(start, goal, heuristic, identity)
+    }
+}
+
+/// Restores the path to the state in the arena following the parents.
+fn path<S, X>(arena: &[(S, Option<usize>, X)], idx: usize) -> Vec<S>
+where
+    S: Clone,
+{
+    let mut path = vec![];
+    let mut cur = Some(idx);
+    while let Some(i) = cur {
+        path.push(arena[i].0.clone());
+        cur = arena[i].1;
+    }
+    path.reverse();
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::implicit::ImplicitGraph;
+    use crate::analyzer::min_weight::Score;
+    use crate::analyzer::visit::OwnedVisitedSet;
+
+    /// The water jugs of 3 and 5 liters: fill, empty or pour one into another.
+    fn jugs(s: &(u8, u8)) -> Vec<((u8, u8), usize)> {
+        let (a, b) = *s;
+        let to_b = a.min(5 - b);
+        let to_a = b.min(3 - a);
+        vec![
+            ((3, b), 1),
+            ((a, 5), 1),
+            ((0, b), 1),
+            ((a, 0), 1),
+            ((a - to_b, b + to_b), 1),
+            ((a + to_a, b - to_a), 1),
+        ]
+    }
+
+    #[test]
+    fn fs_test() {
+        let graph = ImplicitGraph::new(jugs);
+        let path = graph.bfs((0, 0), |s| s.1 == 4).unwrap();
+        assert_eq!(path.len(), 7);
+        assert_eq!(path[0], (0, 0));
+        assert_eq!(path[6].1, 4);
+        for w in path.windows(2) {
+            assert!(jugs(&w[0]).iter().any(|(s, _)| *s == w[1]));
+        }
+
+        let path = graph.dfs((0, 0), |s| s.1 == 4).unwrap();
+        assert_eq!(path.last().unwrap().1, 4);
+
+        let mut visited = OwnedVisitedSet::default();
+        assert_eq!(graph.bfs_with((0, 0), |s| *s == (1, 1), &mut visited), None);
+        assert_eq!(visited.len(), 16);
+
+        let graph = ImplicitGraph::new(jugs).with_max_depth(5);
+        assert_eq!(graph.bfs((0, 0), |s| s.1 == 4), None);
+    }
+
+    #[test]
+    fn best_first_test() {
+        // the states are numbers, +1 costs 1 and *2 costs 2
+        let graph = ImplicitGraph::new(|n: &usize| vec![(n + 1, 1), (n * 2, 2)]);
+        let path = graph.dijkstra(1, |n| *n == 12).unwrap();
+        assert_eq!(path.cost(), &Score::Value(6));
+        assert_eq!(path.states(), &vec![1, 2, 3, 6, 12]);
+
+        let path = graph
+            .astar(1, |n| *n == 12, |n| 12usize.saturating_sub(*n).min(1))
+            .unwrap();
+        assert_eq!(path.cost(), &Score::Value(6));
+
+        let path = graph.dijkstra_custom(1, |n| *n == 12, |_| 1).unwrap();
+        assert_eq!(path.cost(), &Score::Value(4));
+        assert_eq!(path.into_states(), vec![1, 2, 3, 6, 12]);
+
+        let path = graph.dijkstra(5, |n| *n == 5).unwrap();
+        assert_eq!(path.cost(), &Score::Zero);
+        assert_eq!(path.states(), &vec![5]);
+    }
+}
@@ -0,0 +1,326 @@
+//! K shortest paths between two nodes.
+//!
+//! Yen's algorithm finds the k shortest loopless(simple) paths:
+//! every next path deviates from one of the found paths at some spur node,
+//! the deviation is the shortest path(found by `DijkstraPath`) from the spur node to the target
+//! that avoids the edges already taken by the found paths with the same root part.
+//!
+//! The k shortest walks(the nodes can repeat) are found by the best-first search
+//! that lets every node be settled up to k times.
+//! It is simpler than Eppstein's algorithm and takes `O(k m log(km))` that is enough for a small k.
+//!
+//! Both require non-negative weights and return the paths in the order of the total score.
+use crate::analyzer::dijkstra::DijkstraPath;
+use crate::analyzer::float::{check_weights, Float, FloatWeight, NaNWeight};
+use crate::analyzer::min_weight::{MinIndexed, Score};
+use crate::DiGraph;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::convert::identity;
+use std::hash::Hash;
+use std::ops::Add;
+
+/// The path(both ends are included) with the total score.
+#[derive(Debug, Clone)]
+pub struct ScoredPath<NId, ScoreV> {
+    path: Vec<NId>,
+    score: Score<ScoreV>,
+}
+
+impl<NId, ScoreV> ScoredPath<NId, ScoreV> {
+    pub fn path(&self) -> &Vec<NId> {
+        &self.path
+    }
+    pub fn score(&self) -> &Score<ScoreV> {
+        &self.score
+    }
+}
+
+#[derive(Debug)]
+pub struct KShortestPaths<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+}
+
+impl<'a, NId, NL, EL> KShortestPaths<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self { graph }
+    }
+}
+
+impl<'a, NId, NL, EL> KShortestPaths<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Ord + Add<Output = EL> + Clone,
+{
+    pub fn yen(&self, start: NId, target: NId, k: usize) -> Vec<ScoredPath<NId, EL>> {
+        self.yen_custom(start, target, k, identity)
+    }
+
+    pub fn walks(&self, start: NId, target: NId, k: usize) -> Vec<ScoredPath<NId, EL>> {
+        self.walks_custom(start, target, k, identity)
+    }
+}
+
+impl<'a, NId, NL, EL> KShortestPaths<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: FloatWeight,
+{
+    /// The float weights version of `yen`(see `crate::analyzer::float`).
+    pub fn yen_float(
+        &self,
+        start: NId,
+        target: NId,
+        k: usize,
+    ) -> Result<Vec<ScoredPath<NId, Float<EL>>>, NaNWeight<NId>> {
+        check_weights(self.graph)?;
+        Ok(self.yen_custom(start, target, k, Float::unchecked))
+    }
+
+    /// The float weights version of `walks`(see `crate::analyzer::float`).
+    pub fn walks_float(
+        &self,
+        start: NId,
+        target: NId,
+        k: usize,
+    ) -> Result<Vec<ScoredPath<NId, Float<EL>>>, NaNWeight<NId>> {
+        check_weights(self.graph)?;
+        Ok(self.walks_custom(start, target, k, Float::unchecked))
+    }
+}
+
+impl<'a, NId, NL, EL> KShortestPaths<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Clone,
+{
+    /// Yen's algorithm: at most k loopless paths from the start to the target.
+    pub fn yen_custom<ScoreV, F>(
+        &self,
+        start: NId,
+        target: NId,
+        k: usize,
+        to_score: F,
+    ) -> Vec<ScoredPath<NId, ScoreV>>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone,
+    {
+        if k == 0 || !self.graph.nodes.contains_key(&start) {
+            return vec![];
+        }
+        if start == target {
+            return vec![ScoredPath {
+                path: vec![start],
+                score: Score::Zero,
+            }];
+        }
+        let Some(first) = DijkstraPath::new(self.graph)
+            .on_edge_custom(start.clone(), &to_score)
+            .trail(&target)
+        else {
+            return vec![];
+        };
+
+        let mut found = vec![self.scored(first, &to_score)];
+        let mut candidates: Vec<ScoredPath<NId, ScoreV>> = vec![];
+        let mut seen: HashSet<Vec<NId>> = HashSet::from([found[0].path.clone()]);
+
+        while found.len() < k {
+            let last = found
+                .last()
+                .expect("at least one path is found")
+                .path
+                .clone();
+            for i in 0..last.len() - 1 {
+                let root = &last[..=i];
+                let spur = &last[i];
+                let removed_edges: HashSet<(&NId, &NId)> = found
+                    .iter()
+                    .filter(|p| p.path.len() > i + 1 && &p.path[..=i] == root)
+                    .map(|p| (&p.path[i], &p.path[i + 1]))
+                    .collect();
+                let removed_nodes: HashSet<&NId> = root[..i].iter().collect();
+                let allowed = |from: &NId, to: &NId| {
+                    !removed_nodes.contains(to) && !removed_edges.contains(&(from, to))
+                };
+                let Some(spur_path) = DijkstraPath::new(self.graph)
+                    .on_edge_filtered(spur.clone(), &to_score, allowed)
+                    .trail(&target)
+                else {
+                    continue;
+                };
+                let mut path = root[..i].to_vec();
+                path.extend(spur_path);
+                if seen.insert(path.clone()) {
+                    candidates.push(self.scored(path, &to_score));
+                }
+            }
+            // the cheapest candidate goes next, the ties are resolved by the number of edges
+            let best = candidates.iter().enumerate().min_by(|(_, a), (_, b)| {
+                a.score
+                    .partial_cmp(&b.score)
+                    .unwrap_or(Ordering::Equal)
+                    .then(a.path.len().cmp(&b.path.len()))
+            });
+            match best.map(|(idx, _)| idx) {
+                Some(idx) => found.push(candidates.swap_remove(idx)),
+                None => break,
+            }
+        }
+        found
+    }
+
+    /// At most k shortest walks from the start to the target. The walks can have cycles
+    /// (the target can be passed through as well), thus there are k walks unless
+    /// the target is reachable in a finite number of ways.
+    pub fn walks_custom<ScoreV, F>(
+        &self,
+        start: NId,
+        target: NId,
+        k: usize,
+        to_score: F,
+    ) -> Vec<ScoredPath<NId, ScoreV>>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone,
+    {
+        let Some((start, _)) = self.graph.nodes.get_key_value(&start) else {
+            return vec![];
+        };
+        let mut walks = vec![];
+        let mut settled: HashMap<&NId, usize> = HashMap::new();
+        // the walks are kept as a tree: the node, the parent walk and the score
+        let mut labels: Vec<(&NId, Option<usize>, Score<ScoreV>)> =
+            vec![(start, None, Score::Zero)];
+        let mut heap = BinaryHeap::from([MinIndexed(0, Score::Zero)]);
+
+        while let Some(MinIndexed(idx, _)) = heap.pop() {
+            if walks.len() >= k {
+                break;
+            }
+            let (node, _, score) = labels[idx].clone();
+            let count = settled.entry(node).or_default();
+            *count += 1;
+            if *count > k {
+                continue;
+            }
+            if node == &target {
+                walks.push(ScoredPath {
+                    path: walk(&labels, idx),
+                    score: score.clone(),
+                });
+            }
+            for (to, el) in self.graph.edges.get(node).into_iter().flatten() {
+                if self.graph.nodes.contains_key(to) {
+                    let alt = score.add_score_v(to_score(el.clone()));
+                    heap.push(MinIndexed(labels.len(), alt.clone()));
+                    labels.push((to, Some(idx), alt));
+                }
+            }
+        }
+        walks
+    }
+
+    fn scored<ScoreV, F>(&self, path: Vec<NId>, to_score: &F) -> ScoredPath<NId, ScoreV>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Clone,
+    {
+        let score = path.windows(2).fold(Score::Zero, |score, w| {
+            let el = self
+                .graph
+                .edges
+                .get(&w[0])
+                .and_then(|ss| ss.get(&w[1]))
+                .expect("the edge exists");
+            score.add_score_v(to_score(el.clone()))
+        });
+        ScoredPath { path, score }
+    }
+}
+
+fn walk<NId: Clone, X>(labels: &[(&NId, Option<usize>, X)], idx: usize) -> Vec<NId> {
+    let mut walk = vec![];
+    let mut cur = Some(idx);
+    while let Some(i) = cur {
+        walk.push(labels[i].0.clone());
+        cur = labels[i].1;
+    }
+    walk.reverse();
+    walk
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::k_shortest::KShortestPaths;
+    use crate::analyzer::min_weight::Score;
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+
+    #[test]
+    fn yen_test() {
+        // the example from the wikipedia page: C=1, D=2, E=3, F=4, G=5, H=6
+        let graph = digraph!((usize,_,usize) => [1,2,3,4,5,6] => {
+           1 => [(2,3),(3,2)];
+           2 => (4,4);
+           3 => [(2,1),(4,2),(5,3)];
+           4 => [(5,2),(6,1)];
+           5 => (6,2);
+        });
+        let paths = KShortestPaths::new(&graph).yen(1, 6, 3);
+        let res: Vec<(Vec<usize>, Score<usize>)> = paths
+            .iter()
+            .map(|p| (p.path().clone(), p.score().clone()))
+            .collect();
+        assert_eq!(
+            res,
+            vec![
+                (vec![1, 3, 4, 6], Score::Value(5)),
+                (vec![1, 3, 5, 6], Score::Value(7)),
+                (vec![1, 2, 4, 6], Score::Value(8)),
+            ]
+        );
+
+        let paths = KShortestPaths::new(&graph).yen(1, 6, 100);
+        assert_eq!(paths.len(), 7);
+        assert!(paths.windows(2).all(|w| w[0].score() <= w[1].score()));
+
+        assert!(KShortestPaths::new(&graph).yen(6, 1, 3).is_empty());
+        assert_eq!(KShortestPaths::new(&graph).yen(1, 1, 3).len(), 1);
+    }
+
+    #[test]
+    fn walks_test() {
+        let graph = digraph!((usize,_,usize) => [1,2,3] => {
+           1 => (2,1);
+           2 => [(1,1),(3,5)];
+        });
+        let walks = KShortestPaths::new(&graph).walks(1, 3, 3);
+        let res: Vec<(Vec<usize>, Score<usize>)> = walks
+            .iter()
+            .map(|p| (p.path().clone(), p.score().clone()))
+            .collect();
+        assert_eq!(
+            res,
+            vec![
+                (vec![1, 2, 3], Score::Value(6)),
+                (vec![1, 2, 1, 2, 3], Score::Value(8)),
+                (vec![1, 2, 1, 2, 1, 2, 3], Score::Value(10)),
+            ]
+        );
+        let paths = KShortestPaths::new(&graph).yen(1, 3, 3);
+        assert_eq!(paths.len(), 1);
+
+        let walks = KShortestPaths::new(&graph).walks(1, 1, 2);
+        assert_eq!(walks[0].path(), &vec![1]);
+        assert_eq!(walks[1].path(), &vec![1, 2, 1]);
+    }
+}
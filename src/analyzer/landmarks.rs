@@ -0,0 +1,355 @@
+//! ALT(A*, landmarks, triangle inequality) preprocessing.
+//!
+//! For a few landmarks the distances from and to every node are computed once.
+//! By the triangle inequality `d(v, t) >= d(L, t) - d(L, v)` and `d(v, t) >= d(v, L) - d(t, L)`,
+//! the maximum over all landmarks is an admissible and consistent heuristic for A*
+//! that stays valid for any target, thus the preprocessing pays off for many queries on the same graph.
+//!
+//! The landmarks can be given explicitly, the rest of them are selected by the farthest rule:
+//! every next landmark is the node with the maximal round trip distance to the closest selected landmark.
+//!
+//! # Example
+//! ```rust
+//! use digraph_rs::analyzer::astar::AStarPath;
+//! use digraph_rs::analyzer::landmarks::LandmarkSelection;
+//! use digraph_rs::{digraph, extend_edges, extend_nodes, DiGraph, EmptyPayload};
+//!
+//! let graph = digraph!((usize,_,usize) => [1,2,3,4] => {
+//!    1 => [(2,1),(3,4)];
+//!    2 => (3,1);
+//!    3 => (4,1);
+//! });
+//! let landmarks = LandmarkSelection::new(&graph).with_count(2).on_edge();
+//! let res = AStarPath::new(&graph).on_edge(1, 4, landmarks.heuristic(&4));
+//! assert_eq!(res.path(), vec![1, 2, 3, 4]);
+//! ```
+use crate::analyzer::float::{check_weights, Float, FloatWeight, NaNWeight};
+use crate::analyzer::min_weight::{MinWeight, Score};
+use crate::analyzer::predecessors::ReverseIndex;
+use crate::DiGraph;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::convert::identity;
+use std::hash::Hash;
+use std::ops::{Add, Sub};
+
+/// The distances from and to the landmarks. It does not borrow the graph
+/// and can be reused for the queries until the graph is changed.
+#[derive(Debug, Clone)]
+pub struct Landmarks<NId, ScoreV>
+where
+    NId: Eq + Hash,
+{
+    landmarks: Vec<NId>,
+    from: Vec<HashMap<NId, ScoreV>>,
+    to: Vec<HashMap<NId, ScoreV>>,
+}
+
+impl<NId, ScoreV> Landmarks<NId, ScoreV>
+where
+    NId: Eq + Hash,
+    ScoreV: Ord + Sub<Output = ScoreV> + Clone + Default,
+{
+    pub fn landmarks(&self) -> &Vec<NId> {
+        &self.landmarks
+    }
+
+    /// The lower bound of the distance from one node to another.
+    pub fn lower_bound(&self, from: &NId, to: &NId) -> ScoreV {
+        let mut bound = ScoreV::default();
+        for (d_from, d_to) in self.from.iter().zip(self.to.iter()) {
+            // d(L, to) - d(L, from)
+            if let (Some(l_to), Some(l_from)) = (d_from.get(to), d_from.get(from)) {
+                if l_to > l_from && l_to.clone() - l_from.clone() > bound {
+                    bound = l_to.clone() - l_from.clone();
+                }
+            }
+            // d(from, L) - d(to, L)
+            if let (Some(from_l), Some(to_l)) = (d_to.get(from), d_to.get(to)) {
+                if from_l > to_l && from_l.clone() - to_l.clone() > bound {
+                    bound = from_l.clone() - to_l.clone();
+                }
+            }
+        }
+        bound
+    }
+
+    /// The heuristic for `AStarPath::on_edge_custom` and the other A* searches.
+    pub fn heuristic<'s>(&'s self, target: &'s NId) -> impl Fn(&NId) -> ScoreV + 's {
+        move |id| self.lower_bound(id, target)
+    }
+}
+
+#[derive(Debug)]
+pub struct LandmarkSelection<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+    landmarks: Vec<NId>,
+    count: usize,
+}
+
+impl<'a, NId, NL, EL> LandmarkSelection<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    /// By default 4 landmarks are selected starting from the start of the graph.
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self {
+            graph,
+            landmarks: vec![],
+            count: 4,
+        }
+    }
+
+    /// The given landmarks go first, the missing ones are selected by the farthest rule.
+    /// The nodes that are not in the graph are ignored.
+    pub fn with_landmarks(self, landmarks: Vec<NId>) -> Self {
+        Self { landmarks, ..self }
+    }
+
+    /// The total number of the landmarks(at most the number of the nodes).
+    pub fn with_count(self, count: usize) -> Self {
+        Self { count, ..self }
+    }
+}
+
+impl<'a, NId, NL, EL> LandmarkSelection<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Ord + Add<Output = EL> + Sub<Output = EL> + Clone + Default,
+{
+    pub fn on_edge(&self) -> Landmarks<NId, EL> {
+        self.on_edge_custom(identity)
+    }
+}
+
+impl<'a, NId, NL, EL> LandmarkSelection<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: FloatWeight,
+{
+    /// The float weights version of `on_edge`(see `crate::analyzer::float`).
+    pub fn on_float_edge(&self) -> Result<Landmarks<NId, Float<EL>>, NaNWeight<NId>> {
+        check_weights(self.graph)?;
+        Ok(self.on_edge_custom(Float::unchecked))
+    }
+}
+
+impl<'a, NId, NL, EL> LandmarkSelection<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Clone,
+{
+    /// Computes the distances for the landmarks. The weights need to be non-negative.
+    pub fn on_edge_custom<ScoreV, F>(&self, to_score: F) -> Landmarks<NId, ScoreV>
+    where
+        F: Fn(EL) -> ScoreV,
+        ScoreV: Ord + Add<Output = ScoreV> + Sub<Output = ScoreV> + Clone + Default,
+    {
+        let reverse = ReverseIndex::new(self.graph);
+        let forward = |id: &NId| -> Vec<(&'a NId, ScoreV)> {
+            self.graph
+                .edges
+                .get(id)
+                .into_iter()
+                .flatten()
+                .filter(|(to, _)| self.graph.nodes.contains_key(to))
+                .map(|(to, el)| (to, to_score(el.clone())))
+                .collect()
+        };
+        let backward = |id: &NId| -> Vec<(&'a NId, ScoreV)> {
+            reverse
+                .by_node(id)
+                .into_iter()
+                .flatten()
+                .map(|(from, el)| (*from, to_score((*el).clone())))
+                .collect()
+        };
+
+        let given: Vec<&NId> = self
+            .landmarks
+            .iter()
+            .filter_map(|id| self.graph.nodes.get_key_value(id).map(|(id, _)| id))
+            .collect();
+        let total = self.count.max(given.len()).min(self.graph.nodes.len());
+        let mut given = given.into_iter();
+        let mut chosen: Vec<&NId> = vec![];
+        let mut from = vec![];
+        let mut to = vec![];
+        // the round trip distance to the closest selected landmark
+        let mut closest: HashMap<&NId, Score<ScoreV>> = HashMap::new();
+
+        while chosen.len() < total {
+            let next = given.next().or_else(|| {
+                if chosen.is_empty() {
+                    self.graph.start.as_ref().or(self.graph.nodes.keys().next())
+                } else {
+                    farthest(self.graph.nodes.keys(), &chosen, &closest)
+                }
+            });
+            let Some(landmark) = next else {
+                break;
+            };
+            if chosen.contains(&landmark) {
+                continue;
+            }
+            let d_from = distances(landmark, forward);
+            let d_to = distances(landmark, backward);
+            for id in self.graph.nodes.keys() {
+                let round_trip = match (d_from.get(id), d_to.get(id)) {
+                    (Some(a), Some(b)) => Score::Value(a.clone() + b.clone()),
+                    _ => Score::Inf,
+                };
+                match closest.get(id) {
+                    Some(d) if d <= &round_trip => (),
+                    _ => {
+                        closest.insert(id, round_trip);
+                    }
+                }
+            }
+            chosen.push(landmark);
+            from.push(owned(d_from));
+            to.push(owned(d_to));
+        }
+
+        Landmarks {
+            landmarks: chosen.into_iter().cloned().collect(),
+            from,
+            to,
+        }
+    }
+}
+
+/// The node that is the farthest from the selected landmarks.
+fn farthest<'a, NId, ScoreV, I>(
+    nodes: I,
+    chosen: &[&'a NId],
+    closest: &HashMap<&'a NId, Score<ScoreV>>,
+) -> Option<&'a NId>
+where
+    NId: Eq + Hash,
+    ScoreV: Ord,
+    I: Iterator<Item = &'a NId>,
+{
+    let chosen: HashSet<&NId> = chosen.iter().cloned().collect();
+    let mut best: Option<(&NId, &Score<ScoreV>)> = None;
+    for id in nodes.filter(|id| !chosen.contains(id)) {
+        let Some(d) = closest.get(id) else {
+            continue;
+        };
+        match best {
+            Some((_, b)) if b >= d => (),
+            _ => best = Some((id, d)),
+        }
+    }
+    best.map(|(id, _)| id)
+}
+
+/// Dijkstra's algorithm over the given successors. The unreachable nodes are absent.
+fn distances<'a, NId, ScoreV, N>(source: &'a NId, next: N) -> HashMap<&'a NId, ScoreV>
+where
+    NId: Eq + Hash,
+    ScoreV: Ord + Add<Output = ScoreV> + Clone + Default,
+    N: Fn(&NId) -> Vec<(&'a NId, ScoreV)>,
+{
+    let mut distance: HashMap<&NId, Score<ScoreV>> = HashMap::from([(source, Score::Zero)]);
+    let mut settled = HashMap::new();
+    let mut heap = BinaryHeap::from([MinWeight(source, Score::Zero)]);
+
+    while let Some(MinWeight(id, score)) = heap.pop() {
+        if settled.contains_key(id) {
+            continue;
+        }
+        for (to, w) in next(id) {
+            let alt = score.add_score_v(w);
+            match distance.get(to) {
+                Some(d) if d <= &alt => (),
+                _ => {
+                    distance.insert(to, alt.clone());
+                    heap.push(MinWeight(to, alt));
+                }
+            }
+        }
+        let d = match score {
+            Score::Value(v) => v,
+            _ => ScoreV::default(),
+        };
+        settled.insert(id, d);
+    }
+    settled
+}
+
+fn owned<NId: Clone + Eq + Hash, V>(distances: HashMap<&NId, V>) -> HashMap<NId, V> {
+    distances
+        .into_iter()
+        .map(|(id, d)| (id.clone(), d))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::astar::AStarPath;
+    use crate::analyzer::bidirectional::tests::grid;
+    use crate::analyzer::dijkstra::DijkstraPath;
+    use crate::analyzer::landmarks::LandmarkSelection;
+    use crate::analyzer::min_weight::Score;
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+
+    #[test]
+    fn lower_bound_test() {
+        let graph = digraph!((usize,_,usize) => [1,2,3,4,5] => {
+           1 => (2,2);
+           2 => [(3,3),(1,1)];
+           3 => (4,4);
+           4 => (1,5);
+        });
+        let landmarks = LandmarkSelection::new(&graph)
+            .with_landmarks(vec![1, 42])
+            .with_count(1)
+            .on_edge();
+        assert_eq!(landmarks.landmarks(), &vec![1]);
+        // d(1, 4) - d(1, 2) = 9 - 2
+        assert_eq!(landmarks.lower_bound(&2, &4), 7);
+        // d(4, 1) - d(2, 1) = 5 - 1
+        assert_eq!(landmarks.lower_bound(&4, &2), 4);
+        // 5 is not connected
+        assert_eq!(landmarks.lower_bound(&5, &2), 0);
+
+        let landmarks = LandmarkSelection::new(&graph).with_count(10).on_edge();
+        assert_eq!(landmarks.landmarks().len(), 5);
+        assert_eq!(landmarks.landmarks()[0], 1);
+    }
+
+    #[test]
+    fn alt_test() {
+        let graph = grid();
+        let landmarks = LandmarkSelection::new(&graph).on_edge();
+        assert_eq!(landmarks.landmarks().len(), 4);
+        let astar = AStarPath::new(&graph);
+        for start in 0..36 {
+            let dijkstra = DijkstraPath::new(&graph).on_edge(start);
+            for target in 0..36 {
+                let lb = landmarks.lower_bound(&start, &target);
+                if let Score::Value(d) = dijkstra.score(&target) {
+                    assert!(lb <= d);
+                }
+                let res = astar.on_edge(start, target, landmarks.heuristic(&target));
+                assert_eq!(res.cost(), &dijkstra.score(&target));
+                assert_eq!(res.path().first(), Some(&start));
+                assert_eq!(res.path().last(), Some(&target));
+            }
+        }
+
+        let landmarks = LandmarkSelection::new(&graph)
+            .with_landmarks(vec![0, 35])
+            .on_edge_custom(|w| w as u64 * 10);
+        let res =
+            AStarPath::new(&graph)
+                .on_edge_custom(5, 30, landmarks.heuristic(&30), |w: usize| w as u64 * 10);
+        let dijkstra = DijkstraPath::new(&graph).on_edge_custom(5, |w| w as u64 * 10);
+        assert_eq!(res.cost(), &dijkstra.score(&30));
+    }
+}
@@ -2,6 +2,8 @@ use std::cmp::Ordering;
 use std::ops::Add;
 use Score::{Inf, Value, Zero};
 
+/// The score of a path in the min-plus sense: `Inf` is no path, `Zero` is the empty path.
+/// The other path problems are expressed by the semirings in `semiring`.
 #[derive(Debug, Clone)]
 pub enum Score<ScoreValue> {
     Inf,
@@ -123,3 +125,31 @@ impl<'a, Id, ScoreValue: Ord> Ord for MinWeight<'a, Id, ScoreValue> {
         }
     }
 }
+
+/// The same as `MinWeight` but for the values kept in an arena(a vector) and referred by the index.
+/// The minimal score goes first, the ties are resolved by the index(the earliest one goes first).
+pub(crate) struct MinIndexed<ScoreValue>(pub usize, pub Score<ScoreValue>);
+
+impl<ScoreValue: Ord> PartialEq for MinIndexed<ScoreValue> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<ScoreValue: Ord> Eq for MinIndexed<ScoreValue> {}
+
+impl<ScoreValue: Ord> PartialOrd for MinIndexed<ScoreValue> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<ScoreValue: Ord> Ord for MinIndexed<ScoreValue> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .1
+            .partial_cmp(&self.1)
+            .unwrap_or(Ordering::Equal)
+            .then(other.0.cmp(&self.0))
+    }
+}
@@ -4,6 +4,7 @@ use graphviz_rust::attributes::{color_name, EdgeAttributes, NodeAttributes};
 use graphviz_rust::dot_structures::Stmt;
 use crate::{DiGraph, Edge};
 use crate::analyzer::dijkstra::MinPathProcessor;
+use crate::analyzer::float::{check_weights, Float, FloatWeight, NaNWeight};
 use crate::visualizer::dot::{DotProcessor, ToStringProcessor};
 
 /// The Minimum Spanning Arborescence (MSA) is a concept in graph theory
@@ -16,7 +17,6 @@ use crate::visualizer::dot::{DotProcessor, ToStringProcessor};
 pub struct MinimumSpanningArborescence<'a, NId, NL, EL>
     where
         NId: Eq + Hash + Clone,
-        EL: Ord
 {
     graph: &'a DiGraph<NId, NL, EL>,
     forest: Vec<Vec<&'a NId>>,
@@ -26,11 +26,36 @@ impl<'a, NId, NL, EL> MinimumSpanningArborescence<'a, NId, NL, EL> where
     NId: Eq + Hash + Clone,
     EL: Ord
 {
+    /// Kruskal over the edges sorted by the weight(see `EdgeIteratorSorted`),
+    /// the edges with equal weights are taken in an arbitrary order,
+    /// thus the result can be any of the minimum arborescences if there are several.
     pub fn find(&'a mut self) -> Vec<Edge<'a, NId, EL>> {
+        self.kruskal(|e| e.payload)
+    }
+}
+
+impl<'a, NId, NL, EL> MinimumSpanningArborescence<'a, NId, NL, EL> where
+    NId: Eq + Hash + Clone,
+    EL: FloatWeight
+{
+    /// The float weights version of `find`(see `crate::analyzer::float`).
+    pub fn find_float(&'a mut self) -> Result<Vec<Edge<'a, NId, EL>>, NaNWeight<NId>> {
+        check_weights(self.graph)?;
+        Ok(self.kruskal(|e| Float::unchecked(*e.payload)))
+    }
+}
+
+impl<'a, NId, NL, EL> MinimumSpanningArborescence<'a, NId, NL, EL> where
+    NId: Eq + Hash + Clone,
+{
+    fn kruskal<K, F>(&'a mut self, key: F) -> Vec<Edge<'a, NId, EL>>
+        where
+            K: Ord,
+            F: Fn(&Edge<'a, NId, EL>) -> K,
+    {
         let mut msa = vec![];
-        let mut edges: Vec<Edge<NId, EL>> = self.graph.edges();
         self.fill_forest();
-        edges.sort_by_key(|e| e.payload);
+        let edges = self.graph.iter_edges_sorted_by_key(key);
 
         for e @ Edge { src, trg, .. } in edges {
             let src_stump = self.find_set(src);
@@ -154,4 +179,4 @@ mod tests {
         let edges = d.find();
         let _ = graph.visualize().to_dot_file("dots/msa.svg", MSAHighlighter::new(edges));
     }
-}
\ No newline at end of file
+}
@@ -53,10 +53,49 @@ where
     }
 }
 
+/// The full reverse index of the graph: for every node the map of its predecessors
+/// with the payloads of the edges. Unlike `Predecessors` it covers all edges,
+/// not only the ones reachable from the start.
+pub struct ReverseIndex<'a, NId, EL>
+where
+    NId: Eq + Hash,
+{
+    predecessors: HashMap<&'a NId, HashMap<&'a NId, &'a EL>>,
+}
+
+impl<'a, NId, EL> ReverseIndex<'a, NId, EL>
+where
+    NId: Eq + Hash,
+{
+    pub fn new<NL>(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        let mut predecessors: HashMap<&NId, HashMap<&NId, &EL>> = HashMap::new();
+        for (from, tos) in graph.edges.iter() {
+            for (to, el) in tos.iter() {
+                predecessors.entry(to).or_default().insert(from, el);
+            }
+        }
+        Self { predecessors }
+    }
+
+    /// Returns the predecessors of the node with the payloads of the incoming edges.
+    pub fn by_node(&self, id: &NId) -> Option<&HashMap<&'a NId, &'a EL>> {
+        self.predecessors.get(id)
+    }
+
+    /// Returns the predecessors ids of the node.
+    pub fn predecessor_ids(&self, id: &NId) -> Vec<&'a NId> {
+        self.predecessors
+            .get(id)
+            .map(|m| m.keys().cloned().collect())
+            .unwrap_or_default()
+    }
+}
+
 #[cfg(test)]
 pub mod tests {
-    use std::collections::HashSet;
+    use std::collections::{HashMap, HashSet};
 
+    use super::ReverseIndex;
     use crate::{digraph, extend_edges, extend_nodes, DiGraph, EmptyPayload};
 
     #[test]
@@ -88,4 +127,20 @@ pub mod tests {
         );
         assert_eq!(predecessors.by_node(&0), None);
     }
+
+    #[test]
+    fn reverse_index_test() {
+        let graph = digraph!((usize,_,usize) => [0,1,2,3] => {
+           0 => (1,5);
+           1 => (2,6);
+           3 => (2,7);
+        });
+        let index = ReverseIndex::new(&graph);
+        assert_eq!(
+            index.by_node(&2),
+            Some(&HashMap::from([(&1, &6), (&3, &7)]))
+        );
+        assert_eq!(index.predecessor_ids(&1), vec![&0]);
+        assert!(index.by_node(&0).is_none());
+    }
 }
@@ -11,6 +11,7 @@ use graphviz_rust::{
 };
 
 use crate::{
+    analyzer::trace::{emit, NoTrace, TraceEvent, Tracer},
     visualizer::dot::{DotProcessor, ToStringProcessor},
     DiGraph,
 };
@@ -41,21 +42,34 @@ where
     }
 
     pub fn process_graph(&mut self) -> Vec<Vec<&'a NId>> {
+        self.process_graph_traced(&mut NoTrace)
+    }
+
+    /// The same as `process_graph` but reports visit, push, pop and low link events to the tracer.
+    pub fn process_graph_traced<T>(&mut self, tracer: &mut T) -> Vec<Vec<&'a NId>>
+    where
+        T: Tracer<NId>,
+    {
         for id in self.graph.nodes.keys() {
             if !self.state.contains_key(id) {
-                self.process_node(id)
+                self.process_node(id, tracer)
             }
         }
         self.result.clone()
     }
 
-    fn process_node(&mut self, id: &'a NId) {
+    fn process_node<T>(&mut self, id: &'a NId, tracer: &mut T)
+    where
+        T: Tracer<NId>,
+    {
         let idx = Idx {
             low_link: self.idx,
             index: self.idx,
             on_stack: true,
         };
+        emit(tracer, || TraceEvent::Visit(id.clone()));
         self.stack.push(id);
+        emit(tracer, || TraceEvent::Push(id.clone()));
         self.state.insert(id, idx);
         self.idx += 1;
 
@@ -64,11 +78,13 @@ where
                 Some(idx) => {
                     if idx.on_stack {
                         self.set_low_link_or_idx(id, next);
+                        self.trace_low_link(id, tracer);
                     }
                 }
                 None => {
-                    self.process_node(next);
+                    self.process_node(next, tracer);
                     self.set_low_link(id, next);
+                    self.trace_low_link(id, tracer);
                 }
             }
         }
@@ -76,6 +92,7 @@ where
         if self.eq_idx_link(id) {
             let mut scc: Vec<_> = vec![];
             while let Some(curr) = self.stack.pop() {
+                emit(tracer, || TraceEvent::Pop(curr.clone()));
                 scc.push(curr);
                 self.set_on_stack_false(curr);
                 if curr == id {
@@ -86,6 +103,18 @@ where
         }
     }
 
+    fn trace_low_link<T>(&self, id: &'a NId, tracer: &mut T)
+    where
+        T: Tracer<NId>,
+    {
+        if let Some(idx) = self.state.get(id) {
+            emit(tracer, || TraceEvent::LowLink {
+                id: id.clone(),
+                low_link: idx.low_link,
+            });
+        }
+    }
+
     fn set_on_stack_false(&mut self, id: &'a NId) {
         self.state.entry(id).and_modify(|idx| {
             *idx = Idx {
@@ -227,6 +256,7 @@ mod tests {
 
     use super::TarjanSCC;
     use super::TarjanSCCVizProcessor;
+    use crate::analyzer::trace::{Trace, TraceEvent};
 
     #[test]
     fn simple_test() {
@@ -262,4 +292,35 @@ mod tests {
             .visualize()
             .to_dot_file("dots/graph_scc.svg", TarjanSCCVizProcessor::new(sccs));
     }
+
+    #[test]
+    fn traced_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3] => {
+           1 => 2;
+           2 => 3;
+           3 => 1;
+        });
+        let mut trace = Trace::new();
+        let sccs = TarjanSCC::new(&graph).process_graph_traced(&mut trace);
+        assert_eq!(sccs.len(), 1);
+
+        let events = trace.events();
+        assert_eq!(
+            events
+                .iter()
+                .filter(|e| matches!(e, TraceEvent::Push(_)))
+                .count(),
+            3
+        );
+        assert_eq!(
+            events
+                .iter()
+                .filter(|e| matches!(e, TraceEvent::Pop(_)))
+                .count(),
+            3
+        );
+        assert!(events
+            .iter()
+            .any(|e| matches!(e, TraceEvent::LowLink { low_link: 0, .. })));
+    }
 }
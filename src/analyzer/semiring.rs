@@ -0,0 +1,538 @@
+//! Path algebra over semirings.
+//!
+//! A path problem is given by a semiring: `times` extends a path by an edge,
+//! `plus` combines the alternative paths, `zero` is the value of no path and `one` is the value of the empty path.
+//! The min-plus semiring over `Score` gives the shortest paths(what `DijkstraPath` does),
+//! the others give the widest(bottleneck) paths, the most reliable paths, the reachability and the number of paths.
+//!
+//! Two solvers are generic over the semiring:
+//! - `SemiringPath::dijkstra` needs a selective semiring(`plus` picks one of the arguments)
+//!   where extending a path never makes it better(e.g. non-negative weights for min-plus, probabilities for max-times).
+//! - `SemiringPath::floyd` computes the values for all pairs. It needs the cycles not to improve the paths,
+//!   thus the counting semiring gives the correct numbers only for a DAG.
+use crate::analyzer::min_weight::Score;
+use crate::DiGraph;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+use std::hash::Hash;
+use std::marker::PhantomData;
+use std::ops::Add;
+
+pub trait Semiring {
+    type Value: Clone;
+    /// The identity of `plus`: no path.
+    fn zero(&self) -> Self::Value;
+    /// The identity of `times`: the empty path.
+    fn one(&self) -> Self::Value;
+    /// Combines two alternative paths.
+    fn plus(&self, lhs: &Self::Value, rhs: &Self::Value) -> Self::Value;
+    /// Extends a path.
+    fn times(&self, lhs: &Self::Value, rhs: &Self::Value) -> Self::Value;
+}
+
+/// The semiring where `plus` picks the better argument.
+pub trait Selective: Semiring {
+    /// Returns true if `plus(lhs, rhs)` is `lhs` and not `rhs`.
+    fn better(&self, lhs: &Self::Value, rhs: &Self::Value) -> bool;
+}
+
+/// The shortest paths: `plus` is min, `times` is the sum, `zero` is `Score::Inf`, `one` is `Score::Zero`.
+#[derive(Debug, Clone, Copy)]
+pub struct MinPlus<V>(PhantomData<V>);
+
+/// The widest(bottleneck) paths: `plus` is max, `times` is min,
+/// `zero` is `Score::Zero`(no capacity) and `one` is `Score::Inf`(unlimited capacity).
+#[derive(Debug, Clone, Copy)]
+pub struct MaxMin<V>(PhantomData<V>);
+
+/// The most reliable paths for the probabilities in `[0, 1]`: `plus` is max, `times` is the product.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct MaxTimes;
+
+/// The reachability: `plus` is or, `times` is and.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Boolean;
+
+/// The number of paths: `plus` is the sum, `times` is the product(both saturate at `usize::MAX`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Counting;
+
+impl<V> MinPlus<V> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<V> Default for MinPlus<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> MaxMin<V> {
+    pub fn new() -> Self {
+        Self(PhantomData)
+    }
+}
+
+impl<V> Default for MaxMin<V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<V> Semiring for MinPlus<V>
+where
+    V: Ord + Add<Output = V> + Clone,
+{
+    type Value = Score<V>;
+
+    fn zero(&self) -> Score<V> {
+        Score::Inf
+    }
+    fn one(&self) -> Score<V> {
+        Score::Zero
+    }
+    fn plus(&self, lhs: &Score<V>, rhs: &Score<V>) -> Score<V> {
+        if self.better(rhs, lhs) {
+            rhs.clone()
+        } else {
+            lhs.clone()
+        }
+    }
+    fn times(&self, lhs: &Score<V>, rhs: &Score<V>) -> Score<V> {
+        lhs.clone() + rhs.clone()
+    }
+}
+
+impl<V> Selective for MinPlus<V>
+where
+    V: Ord + Add<Output = V> + Clone,
+{
+    fn better(&self, lhs: &Score<V>, rhs: &Score<V>) -> bool {
+        lhs < rhs
+    }
+}
+
+impl<V> MaxMin<V>
+where
+    V: Ord,
+{
+    /// `Score::Zero < Score::Value < Score::Inf`(`Score` does not compare `Value` with `Zero`).
+    fn less(lhs: &Score<V>, rhs: &Score<V>) -> bool {
+        match (lhs, rhs) {
+            (_, Score::Zero) | (Score::Inf, _) => false,
+            (Score::Zero, _) | (_, Score::Inf) => true,
+            (Score::Value(l), Score::Value(r)) => l < r,
+        }
+    }
+}
+
+impl<V> Semiring for MaxMin<V>
+where
+    V: Ord + Clone,
+{
+    type Value = Score<V>;
+
+    fn zero(&self) -> Score<V> {
+        Score::Zero
+    }
+    fn one(&self) -> Score<V> {
+        Score::Inf
+    }
+    fn plus(&self, lhs: &Score<V>, rhs: &Score<V>) -> Score<V> {
+        if Self::less(lhs, rhs) {
+            rhs.clone()
+        } else {
+            lhs.clone()
+        }
+    }
+    fn times(&self, lhs: &Score<V>, rhs: &Score<V>) -> Score<V> {
+        if Self::less(rhs, lhs) {
+            rhs.clone()
+        } else {
+            lhs.clone()
+        }
+    }
+}
+
+impl<V> Selective for MaxMin<V>
+where
+    V: Ord + Clone,
+{
+    fn better(&self, lhs: &Score<V>, rhs: &Score<V>) -> bool {
+        Self::less(rhs, lhs)
+    }
+}
+
+impl Semiring for MaxTimes {
+    type Value = f64;
+
+    fn zero(&self) -> f64 {
+        0.0
+    }
+    fn one(&self) -> f64 {
+        1.0
+    }
+    fn plus(&self, lhs: &f64, rhs: &f64) -> f64 {
+        lhs.max(*rhs)
+    }
+    fn times(&self, lhs: &f64, rhs: &f64) -> f64 {
+        lhs * rhs
+    }
+}
+
+impl Selective for MaxTimes {
+    fn better(&self, lhs: &f64, rhs: &f64) -> bool {
+        lhs > rhs
+    }
+}
+
+impl Semiring for Boolean {
+    type Value = bool;
+
+    fn zero(&self) -> bool {
+        false
+    }
+    fn one(&self) -> bool {
+        true
+    }
+    fn plus(&self, lhs: &bool, rhs: &bool) -> bool {
+        *lhs || *rhs
+    }
+    fn times(&self, lhs: &bool, rhs: &bool) -> bool {
+        *lhs && *rhs
+    }
+}
+
+impl Selective for Boolean {
+    fn better(&self, lhs: &bool, rhs: &bool) -> bool {
+        *lhs && !*rhs
+    }
+}
+
+impl Semiring for Counting {
+    type Value = usize;
+
+    fn zero(&self) -> usize {
+        0
+    }
+    fn one(&self) -> usize {
+        1
+    }
+    fn plus(&self, lhs: &usize, rhs: &usize) -> usize {
+        lhs.saturating_add(*rhs)
+    }
+    fn times(&self, lhs: &usize, rhs: &usize) -> usize {
+        lhs.saturating_mul(*rhs)
+    }
+}
+
+/// The values of the paths from the start with one of the best predecessors.
+#[derive(Debug, Clone)]
+pub struct SemiringPaths<NId, V>
+where
+    NId: Eq + Hash,
+{
+    start: NId,
+    zero: V,
+    values: HashMap<NId, V>,
+    parents: HashMap<NId, NId>,
+}
+
+impl<NId, V> SemiringPaths<NId, V>
+where
+    NId: Eq + Hash + Clone,
+    V: Clone,
+{
+    /// The value of the best path to the node, `zero` of the semiring if the node is not reachable.
+    pub fn value(&self, to: &NId) -> V {
+        self.values.get(to).cloned().unwrap_or(self.zero.clone())
+    }
+
+    /// The best path from the start to the node(both ends are included).
+    pub fn trail(&self, to: &NId) -> Option<Vec<NId>> {
+        if !self.values.contains_key(to) {
+            return None;
+        }
+        let mut trail = vec![to.clone()];
+        let mut cur = to;
+        while cur != &self.start {
+            cur = self.parents.get(cur)?;
+            trail.push(cur.clone());
+        }
+        trail.reverse();
+        Some(trail)
+    }
+}
+
+/// The values of the paths for all pairs of the nodes.
+#[derive(Debug, Clone)]
+pub struct SemiringMatrix<NId, V>
+where
+    NId: Eq + Hash,
+{
+    index: HashMap<NId, usize>,
+    zero: V,
+    values: Vec<Vec<V>>,
+}
+
+impl<NId, V> SemiringMatrix<NId, V>
+where
+    NId: Eq + Hash,
+    V: Clone,
+{
+    /// The value of the paths from one node to another, `zero` of the semiring for the unknown nodes.
+    pub fn value(&self, from: &NId, to: &NId) -> V {
+        match (self.index.get(from), self.index.get(to)) {
+            (Some(&i), Some(&j)) => self.values[i][j].clone(),
+            _ => self.zero.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct SemiringPath<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+}
+
+impl<'a, NId, NL, EL> SemiringPath<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+    EL: Clone,
+{
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self { graph }
+    }
+
+    /// The generalized Dijkstra: the node with the best value is settled first.
+    pub fn dijkstra<S, F>(
+        &self,
+        semiring: S,
+        start: NId,
+        to_value: F,
+    ) -> SemiringPaths<NId, S::Value>
+    where
+        S: Selective,
+        F: Fn(EL) -> S::Value,
+    {
+        let mut values: HashMap<&NId, S::Value> = HashMap::new();
+        let mut parents: HashMap<NId, NId> = HashMap::new();
+        let mut settled: HashSet<&NId> = HashSet::new();
+        let mut heap = BinaryHeap::new();
+        if let Some((id, _)) = self.graph.nodes.get_key_value(&start) {
+            values.insert(id, semiring.one());
+            heap.push(Label {
+                semiring: &semiring,
+                value: semiring.one(),
+                node: id,
+            });
+        }
+
+        while let Some(Label { value, node, .. }) = heap.pop() {
+            if !settled.insert(node) {
+                continue;
+            }
+            for (to, el) in self.graph.edges.get(node).into_iter().flatten() {
+                if settled.contains(to) || !self.graph.nodes.contains_key(to) {
+                    continue;
+                }
+                let alt = semiring.times(&value, &to_value(el.clone()));
+                let improves = match values.get(to) {
+                    Some(current) => semiring.better(&alt, current),
+                    None => semiring.better(&alt, &semiring.zero()),
+                };
+                if improves {
+                    values.insert(to, alt.clone());
+                    parents.insert(to.clone(), node.clone());
+                    heap.push(Label {
+                        semiring: &semiring,
+                        value: alt,
+                        node: to,
+                    });
+                }
+            }
+        }
+
+        SemiringPaths {
+            start,
+            zero: semiring.zero(),
+            values: values.into_iter().map(|(id, v)| (id.clone(), v)).collect(),
+            parents,
+        }
+    }
+
+    /// The generalized Floyd-Warshall for all pairs.
+    pub fn floyd<S, F>(&self, semiring: S, to_value: F) -> SemiringMatrix<NId, S::Value>
+    where
+        S: Semiring,
+        F: Fn(EL) -> S::Value,
+    {
+        let index: HashMap<NId, usize> = self
+            .graph
+            .nodes
+            .keys()
+            .enumerate()
+            .map(|(i, id)| (id.clone(), i))
+            .collect();
+        let n = index.len();
+        let mut values = vec![vec![semiring.zero(); n]; n];
+        for (i, row) in values.iter_mut().enumerate() {
+            row[i] = semiring.one();
+        }
+        for (from, tos) in self.graph.edges.iter() {
+            let Some(&i) = index.get(from) else {
+                continue;
+            };
+            for (to, el) in tos.iter() {
+                if let Some(&j) = index.get(to) {
+                    values[i][j] = semiring.plus(&values[i][j], &to_value(el.clone()));
+                }
+            }
+        }
+        for k in 0..n {
+            for i in 0..n {
+                if i == k {
+                    continue;
+                }
+                for j in 0..n {
+                    if j == k {
+                        continue;
+                    }
+                    let through = semiring.times(&values[i][k], &values[k][j]);
+                    values[i][j] = semiring.plus(&values[i][j], &through);
+                }
+            }
+        }
+
+        SemiringMatrix {
+            index,
+            zero: semiring.zero(),
+            values,
+        }
+    }
+}
+
+/// The entry of the heap: the best value goes first, the ties are equal.
+struct Label<'s, 'a, S: Semiring, NId> {
+    semiring: &'s S,
+    value: S::Value,
+    node: &'a NId,
+}
+
+impl<'s, 'a, S: Selective, NId> PartialEq for Label<'s, 'a, S, NId> {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl<'s, 'a, S: Selective, NId> Eq for Label<'s, 'a, S, NId> {}
+
+impl<'s, 'a, S: Selective, NId> PartialOrd for Label<'s, 'a, S, NId> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl<'s, 'a, S: Selective, NId> Ord for Label<'s, 'a, S, NId> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        if self.semiring.better(&self.value, &other.value) {
+            Ordering::Greater
+        } else if self.semiring.better(&other.value, &self.value) {
+            Ordering::Less
+        } else {
+            Ordering::Equal
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::dijkstra::DijkstraPath;
+    use crate::analyzer::min_weight::Score;
+    use crate::analyzer::semiring::{Boolean, Counting, MaxMin, MaxTimes, MinPlus, SemiringPath};
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+
+    fn graph() -> DiGraph<usize, EmptyPayload, usize> {
+        digraph!((usize,_,usize) => [1,2,3,4,5,6] => {
+           1 => [(2,7),(3,9),(6,14)];
+           2 => [(3,10),(4,15)];
+           3 => [(4,11),(6,2)];
+           4 => (5,6);
+           6 => (5,9);
+        })
+    }
+
+    #[test]
+    fn min_plus_test() {
+        let graph = graph();
+        let paths = SemiringPath::new(&graph).dijkstra(MinPlus::new(), 1, Score::Value);
+        let dijkstra = DijkstraPath::new(&graph).on_edge(1);
+        for id in 1..=6 {
+            assert_eq!(paths.value(&id), dijkstra.score(&id));
+        }
+        assert_eq!(paths.trail(&5), Some(vec![1, 3, 6, 5]));
+        assert_eq!(paths.value(&42), Score::Inf);
+
+        let all = SemiringPath::new(&graph).floyd(MinPlus::new(), Score::Value);
+        assert_eq!(all.value(&1, &5), Score::Value(20));
+        assert_eq!(all.value(&2, &6), Score::Value(12));
+        assert_eq!(all.value(&5, &1), Score::Inf);
+        assert_eq!(all.value(&3, &3), Score::Zero);
+    }
+
+    #[test]
+    fn max_min_test() {
+        // the widest path from 1 to 5 goes through 6 with the bottleneck 9
+        let graph = graph();
+        let paths = SemiringPath::new(&graph).dijkstra(MaxMin::new(), 1, Score::Value);
+        assert_eq!(paths.value(&5), Score::Value(9));
+        assert_eq!(paths.trail(&5), Some(vec![1, 6, 5]));
+        assert_eq!(paths.value(&4), Score::Value(9));
+        assert_eq!(paths.trail(&4), Some(vec![1, 3, 4]));
+        assert_eq!(paths.value(&1), Score::Inf);
+
+        let all = SemiringPath::new(&graph).floyd(MaxMin::new(), Score::Value);
+        assert_eq!(all.value(&1, &5), Score::Value(9));
+        assert_eq!(all.value(&2, &4), Score::Value(15));
+        assert_eq!(all.value(&5, &1), Score::Zero);
+    }
+
+    #[test]
+    fn max_times_test() {
+        let graph = digraph!((usize,_,f64) => [1,2,3,4] => {
+           1 => [(2,0.9),(3,0.5)];
+           2 => (4,0.5);
+           3 => (4,0.99);
+        });
+        let paths = SemiringPath::new(&graph).dijkstra(MaxTimes, 1, |p| p);
+        assert_eq!(paths.trail(&4), Some(vec![1, 3, 4]));
+        assert!((paths.value(&4) - 0.495).abs() < 1e-9);
+
+        let all = SemiringPath::new(&graph).floyd(MaxTimes, |p| p);
+        assert!((all.value(&1, &4) - 0.495).abs() < 1e-9);
+        assert_eq!(all.value(&4, &1), 0.0);
+    }
+
+    #[test]
+    fn boolean_counting_test() {
+        let graph = graph();
+        let reach = SemiringPath::new(&graph).dijkstra(Boolean, 2, |_| true);
+        assert!(reach.value(&5));
+        assert!(!reach.value(&1));
+        let reach = SemiringPath::new(&graph).floyd(Boolean, |_| true);
+        assert!(reach.value(&1, &5));
+        assert!(!reach.value(&6, &4));
+
+        // 1 -> 5: (1,2,3,4,5), (1,2,3,6,5), (1,2,4,5), (1,3,4,5), (1,3,6,5), (1,6,5)
+        let counts = SemiringPath::new(&graph).floyd(Counting, |_| 1);
+        assert_eq!(counts.value(&1, &5), 6);
+        assert_eq!(counts.value(&1, &1), 1);
+        assert_eq!(counts.value(&5, &1), 0);
+    }
+}
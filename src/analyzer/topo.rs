@@ -0,0 +1,349 @@
+//! Topological sort.
+//!
+//! All variants return the order of all nodes of the graph or, if the graph has a cycle,
+//! the error `Cycle` holding a concrete cycle as a witness.
+//! The edges leading to the nodes that do not exist in the graph are ignored.
+use crate::analyzer::dfs_visitor::{depth_first_search, Control, DfsEvent};
+use crate::analyzer::predecessors::ReverseIndex;
+use crate::DiGraph;
+use std::cmp::{Ordering, Reverse};
+use std::collections::{BinaryHeap, HashMap, HashSet, VecDeque};
+use std::fmt::{Display, Formatter};
+use std::hash::Hash;
+
+/// The cycle found in the graph: every node has an edge to the next one
+/// and the last node has an edge to the first one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cycle<'a, NId> {
+    nodes: Vec<&'a NId>,
+}
+
+impl<'a, NId> Cycle<'a, NId> {
+    pub fn nodes(&self) -> &Vec<&'a NId> {
+        &self.nodes
+    }
+}
+
+impl<'a, NId: ToString> Display for Cycle<'a, NId> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let nodes: Vec<String> = self.nodes.iter().map(|n| n.to_string()).collect();
+        write!(f, "the graph has a cycle: {}", nodes.join(" -> "))
+    }
+}
+
+pub struct TopologicalSort<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+}
+
+impl<'a, NId, NL, EL> TopologicalSort<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+{
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self { graph }
+    }
+
+    /// Kahn's algorithm: the nodes without incoming edges are taken one by one.
+    pub fn kahn(&self) -> Result<Vec<&'a NId>, Cycle<'a, NId>> {
+        self.iter().collect()
+    }
+
+    /// The lazy variant of Kahn's algorithm.
+    /// The iterator yields the nodes and, if the graph has a cycle, the error at the end.
+    pub fn iter(&self) -> TopologicalIter<'a, NId, NL, EL> {
+        TopologicalIter::new(self.graph)
+    }
+
+    /// The reversed post-order of the depth-first search.
+    pub fn dfs(&self) -> Result<Vec<&'a NId>, Cycle<'a, NId>>
+    where
+        NId: Clone,
+    {
+        let roots: Vec<NId> = self.graph.nodes.keys().cloned().collect();
+        let mut path: Vec<&NId> = vec![];
+        let mut order = vec![];
+        let mut cycle = None;
+        depth_first_search(self.graph, &roots, &mut |e| {
+            match e {
+                DfsEvent::Discover { id, .. } => path.push(id),
+                DfsEvent::Finish { id, .. } => {
+                    path.pop();
+                    order.push(id);
+                }
+                DfsEvent::BackEdge { to, .. } => {
+                    let pos = path.iter().position(|n| *n == to).unwrap_or_default();
+                    cycle = Some(Cycle {
+                        nodes: path[pos..].to_vec(),
+                    });
+                    return Control::Stop;
+                }
+                DfsEvent::TreeEdge { to, .. } if !self.graph.nodes.contains_key(to) => {
+                    return Control::Prune
+                }
+                _ => (),
+            }
+            Control::Continue
+        });
+        match cycle {
+            Some(cycle) => Err(cycle),
+            None => {
+                order.reverse();
+                Ok(order)
+            }
+        }
+    }
+
+    /// Kahn's algorithm that always takes the smallest available node according to the comparator,
+    /// thus the order is unique(the lexicographically smallest one).
+    pub fn stable_by<F>(&self, cmp: F) -> Result<Vec<&'a NId>, Cycle<'a, NId>>
+    where
+        F: Fn(&NId, &NId) -> Ordering,
+    {
+        let mut ids: Vec<&NId> = self.graph.nodes.keys().collect();
+        ids.sort_by(|a, b| cmp(a, b));
+        let rank: HashMap<&NId, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+
+        let mut in_degree = in_degree(self.graph);
+        let mut heap: BinaryHeap<Reverse<usize>> = ids
+            .iter()
+            .enumerate()
+            .filter(|(_, id)| in_degree[*id] == 0)
+            .map(|(i, _)| Reverse(i))
+            .collect();
+        let mut order = vec![];
+        while let Some(Reverse(i)) = heap.pop() {
+            let id = ids[i];
+            order.push(id);
+            for s in successors(self.graph, id) {
+                let d = in_degree.get_mut(s).expect("the node exists");
+                *d -= 1;
+                if *d == 0 {
+                    heap.push(Reverse(rank[s]));
+                }
+            }
+        }
+        if order.len() == ids.len() {
+            Ok(order)
+        } else {
+            Err(find_cycle(self.graph, &in_degree))
+        }
+    }
+
+    /// The lexicographically smallest order.
+    pub fn stable(&self) -> Result<Vec<&'a NId>, Cycle<'a, NId>>
+    where
+        NId: Ord,
+    {
+        self.stable_by(|a, b| a.cmp(b))
+    }
+}
+
+/// The lazy Kahn's algorithm(see `TopologicalSort::iter`).
+pub struct TopologicalIter<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+    in_degree: HashMap<&'a NId, usize>,
+    line: VecDeque<&'a NId>,
+    done: bool,
+}
+
+impl<'a, NId, NL, EL> TopologicalIter<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+{
+    fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        let in_degree = in_degree(graph);
+        let line = in_degree
+            .iter()
+            .filter(|(_, d)| **d == 0)
+            .map(|(id, _)| *id)
+            .collect();
+        Self {
+            graph,
+            in_degree,
+            line,
+            done: false,
+        }
+    }
+}
+
+impl<'a, NId, NL, EL> Iterator for TopologicalIter<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+{
+    type Item = Result<&'a NId, Cycle<'a, NId>>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.line.pop_front() {
+            Some(id) => {
+                self.in_degree.remove(id);
+                for s in successors(self.graph, id) {
+                    let d = self.in_degree.get_mut(s).expect("the node exists");
+                    *d -= 1;
+                    if *d == 0 {
+                        self.line.push_back(s);
+                    }
+                }
+                Some(Ok(id))
+            }
+            None => {
+                self.done = true;
+                if self.in_degree.is_empty() {
+                    None
+                } else {
+                    Some(Err(find_cycle(self.graph, &self.in_degree)))
+                }
+            }
+        }
+    }
+}
+
+/// The successors that exist in the graph.
+fn successors<'a, NId, NL, EL>(
+    graph: &'a DiGraph<NId, NL, EL>,
+    id: &NId,
+) -> impl Iterator<Item = &'a NId>
+where
+    NId: Eq + Hash,
+{
+    graph
+        .edges
+        .get(id)
+        .into_iter()
+        .flat_map(|ss| ss.keys())
+        .filter(|s| graph.nodes.contains_key(*s))
+}
+
+fn in_degree<NId, NL, EL>(graph: &DiGraph<NId, NL, EL>) -> HashMap<&NId, usize>
+where
+    NId: Eq + Hash,
+{
+    let mut in_degree: HashMap<&NId, usize> = graph.nodes.keys().map(|id| (id, 0)).collect();
+    for id in graph.nodes.keys() {
+        for s in successors(graph, id) {
+            *in_degree.get_mut(s).expect("the node exists") += 1;
+        }
+    }
+    in_degree
+}
+
+/// Every node left after Kahn's algorithm has a predecessor that is left as well,
+/// thus going backwards from any of them we end up on a cycle.
+fn find_cycle<'a, NId, NL, EL>(
+    graph: &'a DiGraph<NId, NL, EL>,
+    left: &HashMap<&'a NId, usize>,
+) -> Cycle<'a, NId>
+where
+    NId: Eq + Hash,
+{
+    let index = ReverseIndex::new(graph);
+    let is_left = |id: &NId| left.get(id).map(|d| *d > 0).unwrap_or(false);
+    let mut cur = *left
+        .iter()
+        .find(|(_, d)| **d > 0)
+        .map(|(id, _)| id)
+        .expect("the graph has a cycle");
+    let mut path = vec![];
+    let mut seen = HashSet::new();
+    while seen.insert(cur) {
+        path.push(cur);
+        cur = index
+            .predecessor_ids(cur)
+            .into_iter()
+            .find(|p| is_left(p))
+            .expect("the left node has a left predecessor");
+    }
+    let pos = path.iter().position(|n| *n == cur).unwrap_or_default();
+    let mut nodes = path.split_off(pos);
+    nodes.reverse();
+    Cycle { nodes }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::topo::{Cycle, TopologicalSort};
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+    use std::collections::HashMap;
+
+    fn assert_order(graph: &DiGraph<usize, EmptyPayload, EmptyPayload>, order: &[&usize]) {
+        assert_eq!(order.len(), graph.iter().count());
+        let pos: HashMap<&usize, usize> =
+            order.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        for e in graph.iter_edges() {
+            assert!(pos[e.src()] < pos[e.trg()]);
+        }
+    }
+
+    fn assert_cycle(graph: &DiGraph<usize, EmptyPayload, EmptyPayload>, cycle: &Cycle<usize>) {
+        let nodes = cycle.nodes();
+        assert!(!nodes.is_empty());
+        for i in 0..nodes.len() {
+            let next = nodes[(i + 1) % nodes.len()];
+            assert!(graph.edge(nodes[i], next).is_some());
+        }
+    }
+
+    #[test]
+    fn sort_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3,4,5,6] => {
+            1 => [2,3];
+            2 => 4;
+            3 => 4;
+            5 => 6;
+            6 => 4;
+        });
+        let topo = TopologicalSort::new(&graph);
+        assert_order(&graph, &topo.kahn().unwrap());
+        assert_order(&graph, &topo.dfs().unwrap());
+        assert_eq!(topo.stable().unwrap(), vec![&1, &2, &3, &5, &6, &4]);
+        assert_eq!(
+            topo.stable_by(|a, b| b.cmp(a)).unwrap(),
+            vec![&5, &6, &1, &3, &2, &4]
+        );
+
+        let mut iter = graph.analyze().topological_sort().iter();
+        let first = iter.next().unwrap().unwrap();
+        assert!(first == &1 || first == &5);
+        assert_eq!(iter.count(), 5);
+    }
+
+    #[test]
+    fn cycle_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3,4,5] => {
+            1 => 2;
+            2 => 3;
+            3 => 4;
+            4 => 2;
+            4 => 5;
+        });
+        let topo = TopologicalSort::new(&graph);
+        for res in [topo.kahn(), topo.dfs(), topo.stable()] {
+            let cycle = res.unwrap_err();
+            assert_eq!(cycle.nodes().len(), 3);
+            assert_cycle(&graph, &cycle);
+        }
+
+        let res: Vec<_> = topo.iter().collect();
+        assert_eq!(res[0], Ok(&1));
+        assert_eq!(res.len(), 2);
+        assert!(res[1].is_err());
+
+        let graph = digraph!((usize,_,_) => [1,2] => {
+            1 => 2;
+            2 => 2;
+        });
+        let cycle = TopologicalSort::new(&graph).kahn().unwrap_err();
+        assert_eq!(cycle.nodes(), &vec![&2]);
+        assert_eq!(cycle.to_string(), "the graph has a cycle: 2");
+    }
+}
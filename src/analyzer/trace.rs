@@ -0,0 +1,397 @@
+//! Opt-in tracing of the algorithms for educational purposes.
+//!
+//! An algorithm that supports tracing has a `*_traced` counterpart that accepts a `Tracer`.
+//! The tracer `Trace` records the events which can be turned into a sequence of frames
+//! (dot, svg) or into an animated html page with `TraceAnimation`.
+use crate::visualizer::dot::{dot_string, DotProcessor, ToStringProcessor};
+use crate::visualizer::{html, vis_to_file, vis_to_svg};
+use crate::DiGraph;
+use graphviz_rust::attributes::{color_name, EdgeAttributes, GraphAttributes, NodeAttributes};
+use graphviz_rust::dot_structures::{Graph, Stmt};
+use std::collections::HashMap;
+use std::hash::Hash;
+
+/// An event emitted by a traced algorithm.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TraceEvent<NId> {
+    /// The node is processed(discovered, settled or checked against a target)
+    Visit(NId),
+    /// The node is put into a queue, a stack or a heap
+    Push(NId),
+    /// The node is taken from a queue, a stack or a heap
+    Pop(NId),
+    /// The edge improves the score of the target node.
+    Relax { from: NId, to: NId, score: String },
+    /// The low link of the node is updated(Tarjan)
+    LowLink { id: NId, low_link: usize },
+    /// The immediate dominator of the node is updated
+    Dominator { id: NId, idom: NId },
+}
+
+impl<NId: ToString> TraceEvent<NId> {
+    pub fn describe(&self) -> String {
+        match self {
+            TraceEvent::Visit(id) => format!("visit {}", id.to_string()),
+            TraceEvent::Push(id) => format!("push {}", id.to_string()),
+            TraceEvent::Pop(id) => format!("pop {}", id.to_string()),
+            TraceEvent::Relax { from, to, score } => {
+                format!(
+                    "relax {} -> {} = {}",
+                    from.to_string(),
+                    to.to_string(),
+                    score
+                )
+            }
+            TraceEvent::LowLink { id, low_link } => {
+                format!("low link {} = {}", id.to_string(), low_link)
+            }
+            TraceEvent::Dominator { id, idom } => {
+                format!("idom {} = {}", id.to_string(), idom.to_string())
+            }
+        }
+    }
+}
+
+/// The hook receiving the events from the traced algorithms.
+pub trait Tracer<NId> {
+    fn event(&mut self, event: TraceEvent<NId>);
+
+    /// If it is false the events are not even created.
+    fn is_on(&self) -> bool {
+        true
+    }
+}
+
+/// The tracer that ignores everything. The algorithms use it when tracing is not requested.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct NoTrace;
+
+impl<NId> Tracer<NId> for NoTrace {
+    fn event(&mut self, _event: TraceEvent<NId>) {}
+
+    fn is_on(&self) -> bool {
+        false
+    }
+}
+
+/// The tracer that records all events.
+#[derive(Debug, Clone)]
+pub struct Trace<NId> {
+    events: Vec<TraceEvent<NId>>,
+}
+
+impl<NId> Default for Trace<NId> {
+    fn default() -> Self {
+        Self { events: vec![] }
+    }
+}
+
+impl<NId> Trace<NId> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn events(&self) -> &Vec<TraceEvent<NId>> {
+        &self.events
+    }
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+}
+
+impl<NId> Tracer<NId> for Trace<NId> {
+    fn event(&mut self, event: TraceEvent<NId>) {
+        self.events.push(event)
+    }
+}
+
+/// Emits the event lazily, only if the tracer is on.
+pub(crate) fn emit<NId, T, F>(tracer: &mut T, event: F)
+where
+    T: Tracer<NId>,
+    F: FnOnce() -> TraceEvent<NId>,
+{
+    if tracer.is_on() {
+        tracer.event(event())
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NodeState {
+    Pushed,
+    Current,
+    Done,
+}
+
+/// The state of the graph after a given number of events.
+#[derive(Debug, Clone)]
+pub struct TraceFrame<NId>
+where
+    NId: Eq + Hash,
+{
+    step: usize,
+    caption: String,
+    states: HashMap<NId, NodeState>,
+    labels: HashMap<NId, String>,
+    edge: Option<(NId, NId)>,
+}
+
+impl<NId> TraceFrame<NId>
+where
+    NId: Eq + Hash + Clone + ToString,
+{
+    fn initial() -> Self {
+        Self {
+            step: 0,
+            caption: "start".to_string(),
+            states: HashMap::new(),
+            labels: HashMap::new(),
+            edge: None,
+        }
+    }
+
+    fn current(&mut self, id: &NId) {
+        for state in self.states.values_mut() {
+            if *state == NodeState::Current {
+                *state = NodeState::Done;
+            }
+        }
+        self.states.insert(id.clone(), NodeState::Current);
+    }
+
+    fn apply(&self, event: &TraceEvent<NId>) -> Self {
+        let mut next = self.clone();
+        next.step += 1;
+        next.caption = format!("{}: {}", next.step, event.describe());
+        next.edge = None;
+        match event {
+            TraceEvent::Visit(id) | TraceEvent::Pop(id) => next.current(id),
+            TraceEvent::Push(id) => {
+                next.states.insert(id.clone(), NodeState::Pushed);
+            }
+            TraceEvent::Relax { from, to, score } => {
+                next.labels.insert(to.clone(), score.clone());
+                next.edge = Some((from.clone(), to.clone()));
+            }
+            TraceEvent::LowLink { id, low_link } => {
+                next.labels.insert(id.clone(), format!("low {}", low_link));
+            }
+            TraceEvent::Dominator { id, idom } => {
+                next.labels
+                    .insert(id.clone(), format!("idom {}", idom.to_string()));
+            }
+        }
+        next
+    }
+
+    pub fn step(&self) -> usize {
+        self.step
+    }
+    pub fn caption(&self) -> &str {
+        &self.caption
+    }
+}
+
+/// Renders a frame: pushed nodes are blue, the current node is orange, processed nodes are grey.
+/// The labels(scores, low links, dominators) are shown as xlabels
+/// and the last relaxed edge is red.
+pub struct TraceFrameProcessor<'f, NId>
+where
+    NId: Eq + Hash,
+{
+    frame: &'f TraceFrame<NId>,
+    delegate: ToStringProcessor,
+}
+
+impl<'f, NId> TraceFrameProcessor<'f, NId>
+where
+    NId: Eq + Hash,
+{
+    pub fn new(frame: &'f TraceFrame<NId>) -> Self {
+        Self {
+            frame,
+            delegate: ToStringProcessor {},
+        }
+    }
+}
+
+impl<'a, 'f, NId, NL, EL> DotProcessor<'a, NId, NL, EL> for TraceFrameProcessor<'f, NId>
+where
+    NId: Eq + Hash + ToString,
+    NL: ToString,
+    EL: ToString,
+{
+    fn node(&self, id: &'a NId, nl: &'a NL) -> Stmt {
+        let mut attrs = vec![];
+        if let Some(state) = self.frame.states.get(id) {
+            let color = match state {
+                NodeState::Pushed => color_name::lightblue,
+                NodeState::Current => color_name::orange,
+                NodeState::Done => color_name::lightgrey,
+            };
+            attrs.push(NodeAttributes::style("filled".to_string()));
+            attrs.push(NodeAttributes::fillcolor(color));
+        }
+        if let Some(label) = self.frame.labels.get(id) {
+            attrs.push(NodeAttributes::xlabel(dot_string(label)));
+        }
+        self.delegate.node_with_attrs(id, nl, attrs)
+    }
+
+    fn edge(&self, from: &'a NId, to: &'a NId, el: &'a EL) -> Stmt {
+        match &self.frame.edge {
+            Some((f, t)) if f == from && t == to => self.delegate.edge_with_attrs(
+                from,
+                to,
+                el,
+                vec![
+                    EdgeAttributes::color(color_name::red),
+                    EdgeAttributes::penwidth(2.0),
+                ],
+            ),
+            _ => (&self.delegate as &dyn DotProcessor<NId, NL, EL>).edge(from, to, el),
+        }
+    }
+}
+
+/// Turns a trace into a sequence of frames.
+/// The first frame is the graph before any event and every next frame reflects one event.
+pub struct TraceAnimation<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+    frames: Vec<TraceFrame<NId>>,
+}
+
+impl<'a, NId, NL, EL> TraceAnimation<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone + ToString,
+    NL: ToString,
+    EL: ToString,
+{
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>, trace: &Trace<NId>) -> Self {
+        let mut frames = vec![TraceFrame::initial()];
+        for event in trace.events() {
+            let next = frames
+                .last()
+                .expect("the initial frame exists")
+                .apply(event);
+            frames.push(next);
+        }
+        Self { graph, frames }
+    }
+
+    pub fn frames(&self) -> &Vec<TraceFrame<NId>> {
+        &self.frames
+    }
+
+    /// Returns a dot graph for every frame.
+    pub fn to_dots(&self) -> Vec<Graph> {
+        self.frames
+            .iter()
+            .map(|frame| {
+                let mut dot = self
+                    .graph
+                    .visualize()
+                    .to_dot(TraceFrameProcessor::new(frame));
+                dot.add_stmt(Stmt::Attribute(GraphAttributes::label(dot_string(
+                    &frame.caption,
+                ))));
+                dot
+            })
+            .collect()
+    }
+
+    /// Writes the frames into the numbered files `{prefix}_0000.{ext}`, `{prefix}_0001.{ext}`, ..
+    /// The extension should be either svg or dot.
+    /// Returns the paths of the written files.
+    pub fn to_frame_files(&self, prefix: &str, ext: &str) -> std::io::Result<Vec<String>> {
+        let mut paths = vec![];
+        for (idx, dot) in self.to_dots().into_iter().enumerate() {
+            let path = format!("{}_{:04}.{}", prefix, idx, ext);
+            vis_to_file(dot, path.clone())?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    /// Writes a standalone html page that plays the frames.
+    /// Returns the content of the page.
+    pub fn to_html(&self, path: &str) -> std::io::Result<String> {
+        let mut svgs = vec![];
+        for dot in self.to_dots() {
+            svgs.push(vis_to_svg(dot)?);
+        }
+        let captions: Vec<&str> = self.frames.iter().map(|f| f.caption()).collect();
+        let page = html::animation_page(path, &svgs, &captions);
+        std::fs::write(path, &page)?;
+        Ok(page)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::analyzer::trace::{NoTrace, Trace, TraceAnimation, TraceEvent, Tracer};
+    use crate::visualizer::vis;
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+
+    #[test]
+    fn frames_test() {
+        let graph = digraph!((usize,_,usize) => [1,2,3] => {
+           1 => [(2,1),(3,4)];
+           2 => (3,1);
+        });
+        let mut trace = Trace::new();
+        trace.event(TraceEvent::Pop(1));
+        trace.event(TraceEvent::Relax {
+            from: 1,
+            to: 2,
+            score: "1".to_string(),
+        });
+        trace.event(TraceEvent::Push(2));
+        trace.event(TraceEvent::Pop(2));
+
+        let animation = TraceAnimation::new(&graph, &trace);
+        assert_eq!(animation.frames().len(), 5);
+        assert_eq!(animation.frames()[2].caption(), "2: relax 1 -> 2 = 1");
+
+        let dots: Vec<String> = animation.to_dots().into_iter().map(vis).collect();
+        assert_eq!(dots.len(), 5);
+        assert!(dots[1].contains("fillcolor=orange"));
+        assert!(dots[2].contains("color=red"));
+        assert!(dots[2].contains("xlabel=\"1\""));
+        assert!(dots[4].contains("fillcolor=lightgrey"));
+    }
+
+    #[test]
+    fn escaped_labels_test() {
+        let graph = digraph!((usize,_,usize) => [1,2] => {
+           1 => (2,1);
+        });
+        let mut trace = Trace::new();
+        trace.event(TraceEvent::Relax {
+            from: 1,
+            to: 2,
+            score: "a \"b\" \\ c".to_string(),
+        });
+        let dots: Vec<String> = TraceAnimation::new(&graph, &trace)
+            .to_dots()
+            .into_iter()
+            .map(vis)
+            .collect();
+        assert!(dots[1].contains(r#"xlabel="a \"b\" \\ c""#));
+        assert!(dots[1].contains(r#"label="1: relax 1 -> 2 = a \"b\" \\ c""#));
+    }
+
+    #[test]
+    fn no_trace_test() {
+        let mut tracer = NoTrace;
+        assert!(!Tracer::<usize>::is_on(&tracer));
+        tracer.event(TraceEvent::Visit(1));
+    }
+}
@@ -38,3 +38,53 @@ where
         self.visited.contains(v)
     }
 }
+
+/// The visited set that owns the values(clones them on visit).
+/// It suits the values that are created on the fly(e.g. the states of `ImplicitGraph`)
+/// and do not outlive the search.
+#[derive(Debug)]
+pub struct OwnedVisitedSet<T>
+where
+    T: Hash + Eq,
+{
+    visited: HashSet<T>,
+}
+
+impl<T> Default for OwnedVisitedSet<T>
+where
+    T: Hash + Eq,
+{
+    fn default() -> Self {
+        Self {
+            visited: Default::default(),
+        }
+    }
+}
+
+impl<T> OwnedVisitedSet<T>
+where
+    T: Hash + Eq,
+{
+    pub fn len(&self) -> usize {
+        self.visited.len()
+    }
+    pub fn is_empty(&self) -> bool {
+        self.visited.is_empty()
+    }
+}
+
+impl<'a, T> Visited<'a, T> for OwnedVisitedSet<T>
+where
+    T: Hash + Eq + Clone,
+{
+    fn visit(&mut self, v: &'a T) -> bool {
+        if self.visited.contains(v) {
+            false
+        } else {
+            self.visited.insert(v.clone())
+        }
+    }
+    fn is_visited(&self, v: &'a T) -> bool {
+        self.visited.contains(v)
+    }
+}
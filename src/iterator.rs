@@ -1,9 +1,13 @@
+pub mod walk;
+
 use crate::{
+    analyzer::predecessors::ReverseIndex,
     analyzer::visit::{Visited, VisitedSet},
-    DiGraph,
+    DiGraph, Edge,
 };
 use std::{
-    collections::{hash_map::Iter, HashSet, VecDeque},
+    cmp::Ordering,
+    collections::{hash_map::Iter, BinaryHeap, HashMap, VecDeque},
     hash::Hash,
 };
 
@@ -36,6 +40,56 @@ where
     }
 }
 
+/// The queue of the nodes to start(restart) a traversal from.
+/// The nodes are taken in the given order and the visited ones are skipped.
+struct Roots<'a, NId> {
+    pending: Vec<&'a NId>,
+}
+
+impl<'a, NId> Roots<'a, NId>
+where
+    NId: Eq + Hash,
+{
+    fn empty() -> Self {
+        Self { pending: vec![] }
+    }
+
+    /// The roots that exist in the graph.
+    fn new<NL, EL>(graph: &'a DiGraph<NId, NL, EL>, roots: &[NId]) -> Self {
+        let mut pending: Vec<&NId> = roots
+            .iter()
+            .filter_map(|id| graph.nodes.get_key_value(id).map(|(k, _)| k))
+            .collect();
+        pending.reverse();
+        Self { pending }
+    }
+
+    /// The start node goes first and then all other nodes.
+    fn forest<NL, EL>(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        let start = graph
+            .start
+            .as_ref()
+            .and_then(|s| graph.nodes.get_key_value(s).map(|(k, _)| k));
+        let mut pending: Vec<&NId> = start
+            .into_iter()
+            .chain(graph.nodes.keys().filter(|id| Some(*id) != start))
+            .collect();
+        pending.reverse();
+        Self { pending }
+    }
+
+    fn next<V: Visited<'a, NId>>(&mut self, visited: &V) -> Option<&'a NId> {
+        while let Some(id) = self.pending.pop() {
+            if !visited.is_visited(id) {
+                return Some(id);
+            }
+        }
+        None
+    }
+}
+
+/// Depth-first iterator.
+/// The nodes are marked as visited when they are put onto the stack, thus every node is yielded once.
 pub struct NodeIteratorDF<'a, NId, NL, EL>
 where
     NId: Eq + Hash,
@@ -43,12 +97,14 @@ where
     graph: &'a DiGraph<NId, NL, EL>,
     visited: VisitedSet<'a, NId>,
     line: Vec<&'a NId>,
+    roots: Roots<'a, NId>,
 }
 
 impl<'a, NId, NL, EL> NodeIteratorDF<'a, NId, NL, EL>
 where
     NId: Eq + Hash + Clone,
 {
+    /// Starts from the start node of the graph.
     pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
         let mut visited = VisitedSet::default();
         let line = graph
@@ -63,7 +119,28 @@ where
         Self {
             graph,
             line,
+            visited,
+            roots: Roots::empty(),
+        }
+    }
+
+    /// Goes through the nodes reachable from the roots(one by one, in the given order).
+    /// The roots that do not exist in the graph are skipped.
+    pub fn from_roots(graph: &'a DiGraph<NId, NL, EL>, roots: &[NId]) -> Self {
+        Self::with_roots(graph, Roots::new(graph, roots))
+    }
+
+    /// Goes through all nodes restarting from every unvisited node(the start node goes first).
+    pub fn forest(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self::with_roots(graph, Roots::forest(graph))
+    }
+
+    fn with_roots(graph: &'a DiGraph<NId, NL, EL>, roots: Roots<'a, NId>) -> Self {
+        Self {
+            graph,
+            line: vec![],
             visited: Default::default(),
+            roots,
         }
     }
 }
@@ -74,6 +151,12 @@ where
     type Item = (&'a NId, &'a NL);
 
     fn next(&mut self) -> Option<Self::Item> {
+        if self.line.is_empty() {
+            if let Some(root) = self.roots.next(&self.visited) {
+                self.visited.visit(root);
+                self.line.push(root);
+            }
+        }
         if let Some(node) = self.line.pop() {
             for nexts in self.graph.successors(node) {
                 for s in nexts.keys() {
@@ -94,13 +177,18 @@ where
 {
     graph: &'a DiGraph<NId, NL, EL>,
     visited: VisitedSet<'a, NId>,
-    line: VecDeque<&'a NId>,
+    line: VecDeque<Step<'a, NId, EL>>,
+    roots: Roots<'a, NId>,
 }
 
+/// The node in the bfs queue: the depth and the edge(parent, payload) the node has been reached by.
+type Step<'a, NId, EL> = (&'a NId, usize, Option<(&'a NId, &'a EL)>);
+
 impl<'a, NId, NL, EL> NodeIteratorBF<'a, NId, NL, EL>
 where
     NId: Eq + Hash + Clone,
 {
+    /// Starts from the start node of the graph.
     pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
         let mut visited = VisitedSet::default();
         let line = graph
@@ -108,7 +196,7 @@ where
             .as_ref()
             .map(|s| {
                 visited.visit(s);
-                VecDeque::from_iter(vec![s])
+                VecDeque::from_iter(vec![(s, 0, None)])
             })
             .unwrap_or_else(|| VecDeque::new());
 
@@ -116,7 +204,66 @@ where
             graph,
             line,
             visited,
+            roots: Roots::empty(),
+        }
+    }
+
+    /// Goes through the nodes reachable from the roots.
+    /// All roots are put into the queue at once(multi-source bfs),
+    /// the roots that do not exist in the graph are skipped.
+    pub fn from_roots(graph: &'a DiGraph<NId, NL, EL>, roots: &[NId]) -> Self {
+        let mut visited = VisitedSet::default();
+        let mut line = VecDeque::new();
+        let mut roots = Roots::new(graph, roots);
+        while let Some(root) = roots.next(&visited) {
+            visited.visit(root);
+            line.push_back((root, 0, None));
+        }
+        Self {
+            graph,
+            line,
+            visited,
+            roots,
+        }
+    }
+
+    /// Goes through all nodes restarting from every unvisited node(the start node goes first).
+    pub fn forest(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self {
+            graph,
+            line: VecDeque::new(),
+            visited: Default::default(),
+            roots: Roots::forest(graph),
+        }
+    }
+
+    /// Turns the iterator into the one that yields the edges of the bfs tree.
+    pub fn edges(self) -> EdgeIteratorBF<'a, NId, NL, EL> {
+        EdgeIteratorBF { delegate: self }
+    }
+
+    /// Turns the iterator into the one that yields the depth and the parent edge as well.
+    pub fn leveled(self) -> NodeIteratorBFLeveled<'a, NId, NL, EL> {
+        NodeIteratorBFLeveled { delegate: self }
+    }
+
+    fn step(&mut self) -> Option<Step<'a, NId, EL>> {
+        if self.line.is_empty() {
+            if let Some(root) = self.roots.next(&self.visited) {
+                self.visited.visit(root);
+                self.line.push_back((root, 0, None));
+            }
+        }
+        let step = self.line.pop_front()?;
+        let (node, depth, _) = step;
+        for nexts in self.graph.successors(node) {
+            for (s, el) in nexts.iter() {
+                if self.visited.visit(s) {
+                    self.line.push_back((s, depth + 1, Some((node, el))));
+                }
+            }
         }
+        Some(step)
     }
 }
 
@@ -127,18 +274,30 @@ where
     type Item = (&'a NId, &'a NL);
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(node) = self.line.pop_front() {
-            for nexts in self.graph.successors(node) {
-                for s in nexts.keys() {
-                    if self.visited.visit(s) {
-                        self.line.push_back(s);
-                    }
-                }
-            }
-            self.graph.node_by_id(node)
-        } else {
-            None
-        }
+        let (node, _, _) = self.step()?;
+        self.graph.node_by_id(node)
+    }
+}
+
+/// Breadth-first iterator yielding `(depth, id, payload, parent edge)`.
+/// The parent edge is `(parent id, edge payload)` and it is absent for the roots(depth 0).
+pub struct NodeIteratorBFLeveled<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+{
+    delegate: NodeIteratorBF<'a, NId, NL, EL>,
+}
+
+impl<'a, NId, NL, EL> Iterator for NodeIteratorBFLeveled<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    type Item = (usize, &'a NId, &'a NL, Option<(&'a NId, &'a EL)>);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (node, depth, parent) = self.delegate.step()?;
+        let (id, nl) = self.delegate.graph.node_by_id(node)?;
+        Some((depth, id, nl, parent))
     }
 }
 
@@ -150,12 +309,14 @@ where
     visited: VisitedSet<'a, NId>,
     processed: VisitedSet<'a, NId>,
     buffer: Vec<&'a NId>,
+    roots: Roots<'a, NId>,
 }
 
 impl<'a, NId, NL, EL> NodeIteratorDFPostOrder<'a, NId, NL, EL>
 where
     NId: Eq + Hash + Clone,
 {
+    /// Starts from the start node of the graph.
     pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
         let line = graph
             .start()
@@ -168,6 +329,28 @@ where
             buffer: line,
             visited: Default::default(),
             processed: Default::default(),
+            roots: Roots::empty(),
+        }
+    }
+
+    /// Goes through the nodes reachable from the roots(one by one, in the given order).
+    /// The roots that do not exist in the graph are skipped.
+    pub fn from_roots(graph: &'a DiGraph<NId, NL, EL>, roots: &[NId]) -> Self {
+        Self::with_roots(graph, Roots::new(graph, roots))
+    }
+
+    /// Goes through all nodes restarting from every unvisited node(the start node goes first).
+    pub fn forest(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self::with_roots(graph, Roots::forest(graph))
+    }
+
+    fn with_roots(graph: &'a DiGraph<NId, NL, EL>, roots: Roots<'a, NId>) -> Self {
+        Self {
+            graph,
+            buffer: vec![],
+            visited: Default::default(),
+            processed: Default::default(),
+            roots,
         }
     }
 }
@@ -179,7 +362,12 @@ where
     type Item = (&'a NId, &'a NL);
 
     fn next(&mut self) -> Option<Self::Item> {
-        while let Some(&id) = self.buffer.last() {
+        loop {
+            if self.buffer.is_empty() {
+                let root = self.roots.next(&self.visited)?;
+                self.buffer.push(root);
+            }
+            let id = *self.buffer.last().expect("the buffer is not empty");
             if self.visited.visit(id) {
                 for ss in self.graph.successors(id) {
                     for s in ss.keys() {
@@ -195,7 +383,322 @@ where
                 }
             }
         }
-        None
+    }
+}
+
+/// Breadth-first iterator going backwards, along the incoming edges(from a node to its predecessors).
+/// The reverse index is built once when the iterator is created.
+pub struct NodeIteratorReverseBF<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+    index: ReverseIndex<'a, NId, EL>,
+    visited: VisitedSet<'a, NId>,
+    line: VecDeque<&'a NId>,
+}
+
+impl<'a, NId, NL, EL> NodeIteratorReverseBF<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    /// Starts from the roots(all at once), the roots that do not exist in the graph are skipped.
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>, roots: &[NId]) -> Self {
+        let mut visited = VisitedSet::default();
+        let mut line = VecDeque::new();
+        let mut roots = Roots::new(graph, roots);
+        while let Some(root) = roots.next(&visited) {
+            visited.visit(root);
+            line.push_back(root);
+        }
+        Self {
+            graph,
+            index: ReverseIndex::new(graph),
+            visited,
+            line,
+        }
+    }
+}
+
+impl<'a, NId, NL, EL> Iterator for NodeIteratorReverseBF<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    type Item = (&'a NId, &'a NL);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.line.pop_front()?;
+        for p in self.index.predecessor_ids(node) {
+            if self.visited.visit(p) {
+                self.line.push_back(p);
+            }
+        }
+        self.graph.node_by_id(node)
+    }
+}
+
+/// Depth-first iterator going backwards, along the incoming edges(from a node to its predecessors).
+/// The reverse index is built once when the iterator is created.
+pub struct NodeIteratorReverseDF<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+    index: ReverseIndex<'a, NId, EL>,
+    visited: VisitedSet<'a, NId>,
+    line: Vec<&'a NId>,
+    roots: Roots<'a, NId>,
+}
+
+impl<'a, NId, NL, EL> NodeIteratorReverseDF<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    /// Starts from the roots(one by one, in the given order),
+    /// the roots that do not exist in the graph are skipped.
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>, roots: &[NId]) -> Self {
+        Self {
+            graph,
+            index: ReverseIndex::new(graph),
+            visited: Default::default(),
+            line: vec![],
+            roots: Roots::new(graph, roots),
+        }
+    }
+}
+
+impl<'a, NId, NL, EL> Iterator for NodeIteratorReverseDF<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    type Item = (&'a NId, &'a NL);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.line.is_empty() {
+            let root = self.roots.next(&self.visited)?;
+            self.visited.visit(root);
+            self.line.push(root);
+        }
+        let node = self.line.pop()?;
+        for p in self.index.predecessor_ids(node) {
+            if self.visited.visit(p) {
+                self.line.push(p);
+            }
+        }
+        self.graph.node_by_id(node)
+    }
+}
+
+/// Iterates over all edges of the graph without collecting them.
+pub struct EdgeIteratorPlain<'a, NId, EL>
+where
+    NId: Eq + Hash,
+{
+    outer: Iter<'a, NId, HashMap<NId, EL>>,
+    inner: Option<(&'a NId, Iter<'a, NId, EL>)>,
+}
+
+impl<'a, NId, EL> EdgeIteratorPlain<'a, NId, EL>
+where
+    NId: Eq + Hash,
+{
+    pub fn new<NL>(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self {
+            outer: graph.edges.iter(),
+            inner: None,
+        }
+    }
+}
+
+impl<'a, NId, EL> Iterator for EdgeIteratorPlain<'a, NId, EL>
+where
+    NId: Eq + Hash,
+{
+    type Item = Edge<'a, NId, EL>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some((src, trgs)) = self.inner.as_mut() {
+                if let Some((trg, el)) = trgs.next() {
+                    return Some(Edge::new(src, trg, el));
+                }
+            }
+            let (src, trgs) = self.outer.next()?;
+            self.inner = Some((src, trgs.iter()));
+        }
+    }
+}
+
+/// Iterates over all edges in the ascending order of the key.
+///
+/// The edges are heapified in `O(E)` and popped lazily in `O(log E)` each,
+/// thus taking only the first edges is cheaper than sorting all of them.
+/// The edges with equal keys go in an arbitrary order(the heap is not a stable sort).
+pub struct EdgeIteratorSorted<'a, NId, EL, K>
+where
+    NId: Eq + Hash,
+    K: Ord,
+{
+    heap: BinaryHeap<Keyed<'a, NId, EL, K>>,
+}
+
+struct Keyed<'a, NId: Eq + Hash, EL, K: Ord> {
+    key: K,
+    edge: Edge<'a, NId, EL>,
+}
+
+impl<'a, NId: Eq + Hash, EL, K: Ord> PartialEq for Keyed<'a, NId, EL, K> {
+    fn eq(&self, other: &Self) -> bool {
+        self.key == other.key
+    }
+}
+impl<'a, NId: Eq + Hash, EL, K: Ord> Eq for Keyed<'a, NId, EL, K> {}
+impl<'a, NId: Eq + Hash, EL, K: Ord> PartialOrd for Keyed<'a, NId, EL, K> {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+/// The reversed order turns the max heap into the min heap.
+impl<'a, NId: Eq + Hash, EL, K: Ord> Ord for Keyed<'a, NId, EL, K> {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.key.cmp(&self.key)
+    }
+}
+
+impl<'a, NId, EL, K> EdgeIteratorSorted<'a, NId, EL, K>
+where
+    NId: Eq + Hash,
+    K: Ord,
+{
+    pub fn new<NL, F>(graph: &'a DiGraph<NId, NL, EL>, key: F) -> Self
+    where
+        F: Fn(&Edge<'a, NId, EL>) -> K,
+    {
+        let heap = EdgeIteratorPlain::new(graph)
+            .map(|edge| Keyed {
+                key: key(&edge),
+                edge,
+            })
+            .collect();
+        Self { heap }
+    }
+}
+
+impl<'a, NId, EL, K> Iterator for EdgeIteratorSorted<'a, NId, EL, K>
+where
+    NId: Eq + Hash,
+    K: Ord,
+{
+    type Item = Edge<'a, NId, EL>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.heap.pop().map(|k| k.edge)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.heap.len(), Some(self.heap.len()))
+    }
+}
+
+/// Yields the edges of the bfs tree(the edges the nodes are discovered by) in the traversal order.
+pub struct EdgeIteratorBF<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+{
+    delegate: NodeIteratorBF<'a, NId, NL, EL>,
+}
+
+impl<'a, NId, NL, EL> Iterator for EdgeIteratorBF<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    type Item = Edge<'a, NId, EL>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (node, _, parent) = self.delegate.step()?;
+            if let Some((src, el)) = parent {
+                return Some(Edge::new(src, node, el));
+            }
+        }
+    }
+}
+
+/// Yields the edges of the dfs tree(the edges the nodes are discovered by) in the traversal order.
+///
+/// Unlike `NodeIteratorDF` the nodes are marked when they are entered,
+/// thus the edges form a real dfs tree.
+pub struct EdgeIteratorDF<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+    visited: VisitedSet<'a, NId>,
+    stack: Vec<(&'a NId, Option<Iter<'a, NId, EL>>)>,
+    roots: Roots<'a, NId>,
+}
+
+impl<'a, NId, NL, EL> EdgeIteratorDF<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    /// Starts from the start node of the graph.
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        let start: Vec<NId> = graph.start().iter().cloned().collect();
+        Self::from_roots(graph, &start)
+    }
+
+    /// Goes through the nodes reachable from the roots(one by one, in the given order).
+    pub fn from_roots(graph: &'a DiGraph<NId, NL, EL>, roots: &[NId]) -> Self {
+        Self::with_roots(graph, Roots::new(graph, roots))
+    }
+
+    /// Goes through all nodes restarting from every unvisited node(the start node goes first).
+    pub fn forest(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        Self::with_roots(graph, Roots::forest(graph))
+    }
+
+    fn with_roots(graph: &'a DiGraph<NId, NL, EL>, roots: Roots<'a, NId>) -> Self {
+        Self {
+            graph,
+            visited: Default::default(),
+            stack: vec![],
+            roots,
+        }
+    }
+
+    fn enter(&mut self, id: &'a NId) {
+        self.visited.visit(id);
+        self.stack
+            .push((id, self.graph.successors(id).map(|ss| ss.iter())));
+    }
+}
+
+impl<'a, NId, NL, EL> Iterator for EdgeIteratorDF<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Clone,
+{
+    type Item = Edge<'a, NId, EL>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if self.stack.is_empty() {
+                let root = self.roots.next(&self.visited)?;
+                self.enter(root);
+            }
+            let (src, succ) = self.stack.last_mut().expect("the stack is not empty");
+            let src = *src;
+            match succ.as_mut().and_then(|s| s.next()) {
+                Some((trg, el)) if !self.visited.is_visited(trg) => {
+                    self.enter(trg);
+                    return Some(Edge::new(src, trg, el));
+                }
+                Some(_) => (),
+                None => {
+                    self.stack.pop();
+                }
+            }
+        }
     }
 }
 
@@ -203,8 +706,10 @@ where
 pub mod tests {
     use crate::{digraph, extend_edges, extend_nodes, DiGraph, EmptyPayload};
 
+    use super::EdgeIteratorDF;
     use super::NodeIteratorBF;
     use super::NodeIteratorDF;
+    use std::collections::HashSet;
 
     #[test]
     fn simple_test() {
@@ -258,4 +763,185 @@ pub mod tests {
         assert!(vec![2usize, 3].contains(&res[2]));
         assert_eq!(res[3], 1);
     }
+
+    #[test]
+    fn df_cycle_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3] => {
+           1 => 2;
+           2 => 3;
+           3 => 1;
+        });
+        let res: Vec<usize> = graph.iter_df().map(|(id, _)| *id).collect();
+        assert_eq!(res, vec![1, 2, 3]);
+    }
+
+    #[test]
+    fn roots_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3,4,5,6] => {
+           1 => 2;
+           3 => 4;
+           4 => 3;
+           5 => 6;
+        });
+
+        let res: Vec<usize> = graph.iter_df_from(&[3, 5, 42]).map(|(id, _)| *id).collect();
+        assert_eq!(res, vec![3, 4, 5, 6]);
+
+        let res: Vec<usize> = graph.iter_bf_from(&[3, 5]).map(|(id, _)| *id).collect();
+        assert_eq!(res, vec![3, 5, 4, 6]);
+
+        let res: Vec<usize> = graph
+            .iter_df_post_from(&[5, 3])
+            .map(|(id, _)| *id)
+            .collect();
+        assert_eq!(res, vec![6, 5, 4, 3]);
+    }
+
+    #[test]
+    fn forest_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3,4,5,6] => {
+           1 => 2;
+           3 => 4;
+           4 => 3;
+           5 => [6,1];
+        });
+        assert_eq!(graph.iter_df().count(), 2);
+
+        for res in [
+            graph
+                .iter_df_forest()
+                .map(|(id, _)| *id)
+                .collect::<Vec<_>>(),
+            graph.iter_bf_forest().map(|(id, _)| *id).collect(),
+            graph.iter_df_post_forest().map(|(id, _)| *id).collect(),
+        ] {
+            assert_eq!(res.len(), 6);
+            let mut sorted = res.clone();
+            sorted.sort();
+            assert_eq!(sorted, vec![1, 2, 3, 4, 5, 6]);
+        }
+
+        let res: Vec<usize> = graph.iter_df_forest().map(|(id, _)| *id).collect();
+        assert_eq!(&res[..2], &[1, 2]);
+        let res: Vec<usize> = graph.iter_df_post_forest().map(|(id, _)| *id).collect();
+        assert_eq!(&res[..2], &[2, 1]);
+    }
+
+    #[test]
+    fn leveled_test() {
+        let graph = digraph!((usize,_,usize) => [1,2,3,4,5] => {
+           1 => [(2,10),(3,20)];
+           2 => (4,30);
+           3 => (4,40);
+           4 => (5,50);
+        });
+        let mut res: Vec<(usize, usize, Option<usize>)> = graph
+            .iter_bf()
+            .leveled()
+            .map(|(d, id, _, parent)| (d, *id, parent.map(|(p, _)| *p)))
+            .collect();
+        res.sort();
+        assert_eq!(res[..3], [(0, 1, None), (1, 2, Some(1)), (1, 3, Some(1))]);
+        assert!(res[3] == (2, 4, Some(2)) || res[3] == (2, 4, Some(3)));
+        assert_eq!(res[4], (3, 5, Some(4)));
+
+        let (_, _, _, parent) = graph
+            .iter_bf()
+            .leveled()
+            .find(|(_, id, _, _)| **id == 5)
+            .unwrap();
+        assert_eq!(parent, Some((&4, &50)));
+    }
+
+    #[test]
+    fn layers_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3,4,5,6,7] => {
+           1 => [2,3];
+           [2,3] => 4;
+           5 => 6;
+           6 => 4;
+        });
+        let mut layers = graph.bfs_layers(&[1, 5]);
+        layers.iter_mut().for_each(|l| l.sort());
+        assert_eq!(layers, vec![vec![&1, &5], vec![&2, &3, &6], vec![&4]]);
+        assert_eq!(graph.bfs_layers(&[7]), vec![vec![&7]]);
+        assert!(graph.bfs_layers(&[42]).is_empty());
+    }
+
+    #[test]
+    fn edges_test() {
+        let graph = digraph!((usize,_,usize) => [1,2,3,4] => {
+           1 => [(2,3),(3,1)];
+           2 => (4,2);
+           3 => (4,5);
+           4 => (1,4);
+        });
+        assert_eq!(graph.iter_edges().count(), 5);
+
+        let sorted: Vec<usize> = graph
+            .iter_edges_sorted_by_key(|e| *e.payload())
+            .map(|e| *e.payload())
+            .collect();
+        assert_eq!(sorted, vec![1, 2, 3, 4, 5]);
+        let first = graph
+            .iter_edges_sorted_by_key(|e| std::cmp::Reverse(*e.payload()))
+            .next()
+            .unwrap();
+        assert_eq!((first.src(), first.trg()), (&3, &4));
+
+        let bf: Vec<(usize, usize)> = graph
+            .iter_bf_edges()
+            .map(|e| (*e.src(), *e.trg()))
+            .collect();
+        assert_eq!(bf.len(), 3);
+        assert_eq!(bf[0].0, 1);
+        assert_eq!(bf[1].0, 1);
+        assert!(bf[2] == (2, 4) || bf[2] == (3, 4));
+
+        let df: Vec<(usize, usize)> = graph
+            .iter_df_edges()
+            .map(|e| (*e.src(), *e.trg()))
+            .collect();
+        assert_eq!(df.len(), 3);
+        assert_eq!(df[0].0, 1);
+        // the second edge continues from the target of the first one
+        assert_eq!(df[1].0, df[0].1);
+    }
+
+    #[test]
+    fn edges_forest_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3,4,5] => {
+           1 => 2;
+           3 => [4,5];
+           4 => 5;
+           5 => 3;
+        });
+        let df: Vec<(usize, usize)> = EdgeIteratorDF::from_roots(&graph, &[3])
+            .map(|e| (*e.src(), *e.trg()))
+            .collect();
+        assert!(df == vec![(3, 4), (4, 5)] || df == vec![(3, 5), (3, 4)]);
+        assert_eq!(EdgeIteratorDF::forest(&graph).count(), 3);
+        assert_eq!(NodeIteratorBF::forest(&graph).edges().count(), 3);
+    }
+
+    #[test]
+    fn reverse_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3,4,5,6] => {
+           1 => [2,3];
+           [2,3] => 4;
+           4 => 5;
+           6 => 5;
+        });
+        let res: Vec<usize> = graph.iter_bf_reverse(&[4]).map(|(id, _)| *id).collect();
+        assert_eq!(res[0], 4);
+        assert_eq!(res[3], 1);
+        let res: Vec<usize> = graph.iter_df_reverse(&[5]).map(|(id, _)| *id).collect();
+        assert_eq!(res.len(), 6);
+        assert_eq!(res[0], 5);
+
+        assert_eq!(graph.ancestors(&4), HashSet::from([&1, &2, &3]));
+        assert_eq!(graph.ancestors(&5), HashSet::from([&1, &2, &3, &4, &6]));
+        assert_eq!(graph.descendants(&2), HashSet::from([&4, &5]));
+        assert!(graph.descendants(&5).is_empty());
+    }
 }
@@ -0,0 +1,352 @@
+//! Seedable random walks over the graph.
+//!
+//! The walks are reproducible: the same graph, configuration and seed give the same walks.
+//! To achieve it the successors are ordered by id before every step, thus `NId: Ord` is required.
+use crate::DiGraph;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::hash::Hash;
+
+#[derive(Clone, Copy, Debug)]
+pub struct WalkCfg {
+    /// The maximum number of nodes in a walk(including the start).
+    pub length: usize,
+    /// The probability to jump back to the start before every step.
+    /// If it is positive, the walk jumps back to the start at a dead end as well, otherwise it stops.
+    pub restart_prob: f64,
+    /// The return parameter of node2vec: the weight of going back to the previous node is divided by p.
+    pub p: f64,
+    /// The in-out parameter of node2vec: the weight of going to a node that is not
+    /// a successor of the previous node is divided by q.
+    /// The walk is the plain(first-order) one when both p and q are 1.
+    pub q: f64,
+    pub seed: u64,
+}
+
+impl Default for WalkCfg {
+    fn default() -> Self {
+        Self {
+            length: 10,
+            restart_prob: 0.,
+            p: 1.,
+            q: 1.,
+            seed: 0,
+        }
+    }
+}
+
+/// The iterator over the nodes of a random walk starting from the given node.
+/// The transition probabilities are proportional to the weights of the edges(see `with_weight`),
+/// by default all edges have the same weight. The edges with non-positive or non-finite weights are never taken.
+pub struct RandomWalk<'a, NId, NL, EL, W = fn(&EL) -> f64>
+where
+    NId: Eq + Hash,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+    start: Option<&'a NId>,
+    current: Option<&'a NId>,
+    prev: Option<&'a NId>,
+    steps: usize,
+    cfg: WalkCfg,
+    weight: W,
+    rng: StdRng,
+}
+
+impl<'a, NId, NL, EL> RandomWalk<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + Ord,
+{
+    /// If the start does not exist in the graph, the walk is empty.
+    ///
+    /// # Panics
+    /// If `p` or `q` is not a finite positive number.
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>, start: &NId, cfg: WalkCfg) -> Self {
+        assert!(
+            cfg.p.is_finite() && cfg.p > 0.,
+            "the return parameter p must be finite and positive, got {}",
+            cfg.p
+        );
+        assert!(
+            cfg.q.is_finite() && cfg.q > 0.,
+            "the in-out parameter q must be finite and positive, got {}",
+            cfg.q
+        );
+        let start = graph.nodes.get_key_value(start).map(|(k, _)| k);
+        Self {
+            graph,
+            start,
+            current: None,
+            prev: None,
+            steps: 0,
+            cfg,
+            weight: |_| 1.,
+            rng: StdRng::seed_from_u64(cfg.seed),
+        }
+    }
+}
+
+impl<'a, NId, NL, EL, W> RandomWalk<'a, NId, NL, EL, W>
+where
+    NId: Eq + Hash + Ord,
+    W: Fn(&EL) -> f64,
+{
+    /// Sets the weights of the edges to bias the transitions.
+    pub fn with_weight<W2>(self, weight: W2) -> RandomWalk<'a, NId, NL, EL, W2>
+    where
+        W2: Fn(&EL) -> f64,
+    {
+        RandomWalk {
+            graph: self.graph,
+            start: self.start,
+            current: self.current,
+            prev: self.prev,
+            steps: self.steps,
+            cfg: self.cfg,
+            weight,
+            rng: self.rng,
+        }
+    }
+
+    fn restart(&mut self) -> Option<&'a NId> {
+        self.prev = None;
+        self.current = self.start;
+        self.current
+    }
+
+    fn step(&mut self, from: &'a NId) -> Option<&'a NId> {
+        let mut candidates: Vec<(&NId, f64)> = self
+            .graph
+            .edges
+            .get(from)
+            .map(|ss| {
+                ss.iter()
+                    .map(|(to, el)| (to, self.bias(to) * (self.weight)(el)))
+                    .filter(|(_, w)| w.is_finite() && *w > 0.)
+                    .collect()
+            })
+            .unwrap_or_default();
+        candidates.sort_by(|a, b| a.0.cmp(b.0));
+        let total: f64 = candidates.iter().map(|(_, w)| w).sum();
+        // the sum of the huge weights can overflow
+        if total <= 0. || !total.is_finite() {
+            return None;
+        }
+        let mut point = self.rng.gen_range(0.0..total);
+        for (to, w) in candidates.iter() {
+            if point < *w {
+                return Some(to);
+            }
+            point -= w;
+        }
+        candidates.last().map(|(to, _)| *to)
+    }
+
+    /// The second-order bias of node2vec for the transition to the node.
+    fn bias(&self, to: &NId) -> f64 {
+        match self.prev {
+            Some(prev) if prev == to => 1. / self.cfg.p,
+            Some(prev)
+                if self.graph.edges.get(prev).map(|ss| ss.contains_key(to)) == Some(true) =>
+            {
+                1.
+            }
+            Some(_) => 1. / self.cfg.q,
+            None => 1.,
+        }
+    }
+}
+
+impl<'a, NId, NL, EL, W> Iterator for RandomWalk<'a, NId, NL, EL, W>
+where
+    NId: Eq + Hash + Ord,
+    W: Fn(&EL) -> f64,
+{
+    type Item = &'a NId;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.steps >= self.cfg.length {
+            return None;
+        }
+        let next = match self.current {
+            None if self.steps == 0 => self.restart(),
+            None => None,
+            Some(_)
+                if self.cfg.restart_prob > 0.
+                    && self.rng.gen_bool(self.cfg.restart_prob.min(1.)) =>
+            {
+                self.restart()
+            }
+            Some(cur) => match self.step(cur) {
+                Some(next) => {
+                    self.prev = Some(cur);
+                    self.current = Some(next);
+                    Some(next)
+                }
+                None if self.cfg.restart_prob > 0. => self.restart(),
+                None => None,
+            },
+        };
+        match next {
+            Some(_) => self.steps += 1,
+            None => self.current = None,
+        }
+        next
+    }
+}
+
+/// Generates a corpus of walks: `walks_per_node` walks from every node(in the order of ids).
+/// Every walk gets its own seed derived from `cfg.seed`, thus the corpus is reproducible.
+///
+/// # Panics
+/// If `p` or `q` is not a finite positive number(see `RandomWalk::new`).
+pub fn walk_corpus<NId, NL, EL, W>(
+    graph: &DiGraph<NId, NL, EL>,
+    cfg: WalkCfg,
+    walks_per_node: usize,
+    weight: W,
+) -> Vec<Vec<&NId>>
+where
+    NId: Eq + Hash + Ord,
+    W: Fn(&EL) -> f64,
+{
+    let mut ids: Vec<&NId> = graph.nodes.keys().collect();
+    ids.sort();
+    let mut corpus = vec![];
+    for round in 0..walks_per_node {
+        for (idx, id) in ids.iter().enumerate() {
+            let seed = cfg.seed.wrapping_add((round * ids.len() + idx) as u64);
+            let walk = RandomWalk::new(graph, id, WalkCfg { seed, ..cfg }).with_weight(&weight);
+            corpus.push(walk.collect());
+        }
+    }
+    corpus
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::iterator::walk::{walk_corpus, RandomWalk, WalkCfg};
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+
+    #[test]
+    fn walk_test() {
+        let graph = digraph!((usize,_,usize) => [1,2,3,4] => {
+            1 => [(2,1),(3,0)];
+            2 => (4,1);
+            3 => (4,1);
+            4 => (1,1);
+        });
+        let cfg = WalkCfg {
+            length: 9,
+            ..Default::default()
+        };
+        let walk: Vec<&usize> = RandomWalk::new(&graph, &1, cfg)
+            .with_weight(|w: &usize| *w as f64)
+            .collect();
+        assert_eq!(walk, vec![&1, &2, &4, &1, &2, &4, &1, &2, &4]);
+
+        let lhs: Vec<&usize> = RandomWalk::new(&graph, &1, WalkCfg { seed: 42, ..cfg }).collect();
+        let rhs: Vec<&usize> = RandomWalk::new(&graph, &1, WalkCfg { seed: 42, ..cfg }).collect();
+        assert_eq!(lhs, rhs);
+        assert_eq!(lhs.len(), 9);
+
+        assert_eq!(RandomWalk::new(&graph, &42, cfg).count(), 0);
+    }
+
+    #[test]
+    fn dead_end_and_restart_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3] => {
+            1 => 2;
+            2 => 3;
+        });
+        let walk: Vec<&usize> = graph.iter_walk(&1, WalkCfg::default()).collect();
+        assert_eq!(walk, vec![&1, &2, &3]);
+
+        let cfg = WalkCfg {
+            length: 7,
+            restart_prob: 0.01,
+            ..Default::default()
+        };
+        let walk: Vec<&usize> = RandomWalk::new(&graph, &1, cfg).collect();
+        assert_eq!(walk.len(), 7);
+        assert!(walk.windows(2).all(|w| *w[1] == 1 || *w[1] == *w[0] + 1));
+    }
+
+    #[test]
+    fn node2vec_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3] => {
+            1 => 2;
+            2 => [1,3];
+            3 => 2;
+        });
+        // a huge return parameter makes going back very unlikely
+        let cfg = WalkCfg {
+            length: 3,
+            p: 1e9,
+            ..Default::default()
+        };
+        for seed in 0..20 {
+            let walk: Vec<&usize> = RandomWalk::new(&graph, &1, WalkCfg { seed, ..cfg }).collect();
+            assert_eq!(walk, vec![&1, &2, &3]);
+        }
+    }
+
+    #[test]
+    fn corpus_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3] => {
+            1 => 2;
+            2 => [1,3];
+            3 => 1;
+        });
+        let cfg = WalkCfg {
+            length: 5,
+            ..Default::default()
+        };
+        let corpus = walk_corpus(&graph, cfg, 2, |_| 1.);
+        assert_eq!(corpus.len(), 6);
+        assert!(corpus.iter().all(|w| w.len() == 5));
+        assert_eq!(corpus[0][0], &1);
+        assert_eq!(corpus[4][0], &2);
+        assert_eq!(corpus, walk_corpus(&graph, cfg, 2, |_| 1.));
+    }
+
+    #[test]
+    fn non_finite_weight_test() {
+        let graph = digraph!((usize,_,f64) => [1,2,3] => {
+            1 => [(2,f64::INFINITY),(3,1.)];
+            2 => (1,f64::NAN);
+            3 => (1,1.);
+        });
+        let walk: Vec<&usize> = RandomWalk::new(&graph, &1, WalkCfg::default())
+            .with_weight(|w: &f64| *w)
+            .collect();
+        assert_eq!(walk, vec![&1, &3, &1, &3, &1, &3, &1, &3, &1, &3]);
+    }
+
+    #[test]
+    #[should_panic(expected = "the return parameter p must be finite and positive")]
+    fn zero_p_test() {
+        let graph = digraph!((usize,_,_) => [1,2] => {
+            1 => 2;
+        });
+        let cfg = WalkCfg {
+            p: 0.,
+            ..Default::default()
+        };
+        let _ = RandomWalk::new(&graph, &1, cfg);
+    }
+
+    #[test]
+    #[should_panic(expected = "the in-out parameter q must be finite and positive")]
+    fn zero_q_test() {
+        let graph = digraph!((usize,_,_) => [1,2] => {
+            1 => 2;
+        });
+        let cfg = WalkCfg {
+            q: 0.,
+            ..Default::default()
+        };
+        let _ = walk_corpus(&graph, cfg, 1, |_| 1.);
+    }
+}
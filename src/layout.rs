@@ -0,0 +1,600 @@
+//! Layout algorithms producing the coordinates of nodes.
+//!
+//! The coordinates can be used by an external renderer or
+//! be pinned in dot with `PinnedProcessor`(see `DotGraphVisualizer::to_pinned_dot_file`).
+//!
+//! The nodes are ordered by id before the layout starts,
+//! thus the same graph and the same seed give the same layout.
+use crate::DiGraph;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::{HashMap, VecDeque};
+use std::f64::consts::PI;
+use std::hash::Hash;
+
+/// Fruchterman-Reingold force-directed model
+#[derive(Clone, Copy, Debug)]
+pub struct FRCfg {
+    pub iterations: usize,
+    /// A non-positive(or non-finite) size collapses the layout to the line along the other axis,
+    /// the nodes get the zero coordinate on that axis.
+    pub width: f64,
+    pub height: f64,
+    pub seed: u64,
+}
+
+impl Default for FRCfg {
+    fn default() -> Self {
+        Self {
+            iterations: 100,
+            width: 500.,
+            height: 500.,
+            seed: 0,
+        }
+    }
+}
+
+/// Kamada-Kawai spring model
+#[derive(Clone, Copy, Debug)]
+pub struct KKCfg {
+    pub iterations: usize,
+    /// A non-positive(or non-finite) size puts all the nodes at the origin.
+    pub size: f64,
+    pub epsilon: f64,
+    pub seed: u64,
+}
+
+impl Default for KKCfg {
+    fn default() -> Self {
+        Self {
+            iterations: 200,
+            size: 500.,
+            epsilon: 0.01,
+            seed: 0,
+        }
+    }
+}
+
+/// Layered(Sugiyama) model: the edges go downwards, layer by layer.
+#[derive(Clone, Copy, Debug)]
+pub struct LayeredCfg {
+    pub layer_gap: f64,
+    pub node_gap: f64,
+    /// the number of barycenter sweeps (down and up) to reduce crossings
+    pub sweeps: usize,
+}
+
+impl Default for LayeredCfg {
+    fn default() -> Self {
+        Self {
+            layer_gap: 100.,
+            node_gap: 100.,
+            sweeps: 4,
+        }
+    }
+}
+
+/// The nodes are placed on a circle.
+#[derive(Clone, Copy, Debug)]
+pub struct CircularCfg {
+    /// A non-positive(or non-finite) radius puts all the nodes at the origin.
+    pub radius: f64,
+}
+
+impl Default for CircularCfg {
+    fn default() -> Self {
+        Self { radius: 200. }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum LayoutCfg {
+    FR(FRCfg),
+    KK(KKCfg),
+    Layered(LayeredCfg),
+    Circular(CircularCfg),
+}
+
+impl Default for LayoutCfg {
+    fn default() -> Self {
+        LayoutCfg::FR(FRCfg::default())
+    }
+}
+
+#[derive(Default)]
+pub struct GraphLayout {
+    cfg: LayoutCfg,
+}
+
+impl GraphLayout {
+    pub fn new(cfg: LayoutCfg) -> Self {
+        Self { cfg }
+    }
+
+    /// Returns the coordinates for every node of the graph.
+    pub fn layout<NId, NL, EL>(&self, graph: &DiGraph<NId, NL, EL>) -> HashMap<NId, (f64, f64)>
+    where
+        NId: Clone + Eq + Hash + Ord,
+    {
+        let indexed = Indexed::new(graph);
+        let coords = match self.cfg {
+            LayoutCfg::FR(cfg) => fr_layout(&indexed, cfg),
+            LayoutCfg::KK(cfg) => kk_layout(&indexed, cfg),
+            LayoutCfg::Layered(cfg) => layered_layout(&indexed, cfg),
+            LayoutCfg::Circular(cfg) => circular_layout(indexed.ids.len(), cfg),
+        };
+        indexed.ids.into_iter().cloned().zip(coords).collect()
+    }
+}
+
+/// The graph where the nodes are replaced with their indexes in the sorted order.
+struct Indexed<'a, NId> {
+    ids: Vec<&'a NId>,
+    edges: Vec<(usize, usize)>,
+}
+
+impl<'a, NId> Indexed<'a, NId>
+where
+    NId: Eq + Hash + Ord,
+{
+    fn new<NL, EL>(graph: &'a DiGraph<NId, NL, EL>) -> Self {
+        let mut ids: Vec<&NId> = graph.nodes.keys().collect();
+        ids.sort();
+        let idx: HashMap<&NId, usize> = ids.iter().enumerate().map(|(i, id)| (*id, i)).collect();
+        let mut edges = vec![];
+        for (from, tos) in graph.edges.iter() {
+            for to in tos.keys() {
+                if let (Some(f), Some(t)) = (idx.get(from), idx.get(to)) {
+                    if f != t {
+                        edges.push((*f, *t));
+                    }
+                }
+            }
+        }
+        edges.sort();
+        Self { ids, edges }
+    }
+}
+
+impl<'a, NId> Indexed<'a, NId> {
+    fn undirected(&self) -> Vec<Vec<usize>> {
+        let mut adj = vec![vec![]; self.ids.len()];
+        for &(f, t) in self.edges.iter() {
+            adj[f].push(t);
+            adj[t].push(f);
+        }
+        adj
+    }
+}
+
+/// The size of the layout area, the negative and non-finite sizes are turned into the zero.
+fn area_size(size: f64) -> f64 {
+    if size.is_finite() {
+        size.max(0.)
+    } else {
+        0.
+    }
+}
+
+fn circular_layout(len: usize, cfg: CircularCfg) -> Vec<(f64, f64)> {
+    let r = area_size(cfg.radius);
+    (0..len)
+        .map(|i| {
+            let angle = 2. * PI * i as f64 / len as f64 - PI / 2.;
+            (r + r * angle.cos(), r + r * angle.sin())
+        })
+        .collect()
+}
+
+fn fr_layout<NId>(graph: &Indexed<NId>, cfg: FRCfg) -> Vec<(f64, f64)> {
+    let n = graph.ids.len();
+    if n == 0 {
+        return vec![];
+    }
+    let FRCfg {
+        iterations,
+        width,
+        height,
+        seed,
+    } = cfg;
+    let (width, height) = (area_size(width), area_size(height));
+    if width == 0. && height == 0. {
+        return vec![(0., 0.); n];
+    }
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut pos: Vec<(f64, f64)> = (0..n)
+        .map(|_| (rng.gen_range(0.0..=width), rng.gen_range(0.0..=height)))
+        .collect();
+
+    // the optimal distance: the side of the area per node or the length per node on the line
+    let k = if width > 0. && height > 0. {
+        (width * height / n as f64).sqrt()
+    } else {
+        width.max(height) / n as f64
+    };
+    let mut temperature = width.max(height) / 10.;
+    let cooling = temperature / (iterations as f64 + 1.);
+
+    for _ in 0..iterations {
+        let mut disp = vec![(0., 0.); n];
+        for i in 0..n {
+            for j in i + 1..n {
+                let (dx, dy) = (pos[i].0 - pos[j].0, pos[i].1 - pos[j].1);
+                let d = (dx * dx + dy * dy).sqrt().max(0.01);
+                let f = k * k / d;
+                disp[i].0 += dx / d * f;
+                disp[i].1 += dy / d * f;
+                disp[j].0 -= dx / d * f;
+                disp[j].1 -= dy / d * f;
+            }
+        }
+        for &(u, v) in graph.edges.iter() {
+            let (dx, dy) = (pos[u].0 - pos[v].0, pos[u].1 - pos[v].1);
+            let d = (dx * dx + dy * dy).sqrt().max(0.01);
+            let f = d * d / k;
+            disp[u].0 -= dx / d * f;
+            disp[u].1 -= dy / d * f;
+            disp[v].0 += dx / d * f;
+            disp[v].1 += dy / d * f;
+        }
+        for (p, (dx, dy)) in pos.iter_mut().zip(disp) {
+            let len = (dx * dx + dy * dy).sqrt();
+            if len > 0. {
+                let step = len.min(temperature);
+                p.0 = (p.0 + dx / len * step).clamp(0., width);
+                p.1 = (p.1 + dy / len * step).clamp(0., height);
+            }
+        }
+        temperature -= cooling;
+    }
+    pos
+}
+
+/// All pairs hop distances over the undirected graph.
+/// The unreachable pairs get the distance `max + 1`.
+fn hop_distances(adj: &[Vec<usize>]) -> Vec<Vec<f64>> {
+    let n = adj.len();
+    let mut dist = vec![vec![None; n]; n];
+    for (s, row) in dist.iter_mut().enumerate() {
+        let mut q = VecDeque::from([s]);
+        row[s] = Some(0usize);
+        while let Some(v) = q.pop_front() {
+            let dv = row[v].unwrap_or_default();
+            for &u in adj[v].iter() {
+                if row[u].is_none() {
+                    row[u] = Some(dv + 1);
+                    q.push_back(u);
+                }
+            }
+        }
+    }
+    let max = dist
+        .iter()
+        .flat_map(|row| row.iter().flatten())
+        .max()
+        .cloned()
+        .unwrap_or(0)
+        .max(1);
+    dist.into_iter()
+        .map(|row| {
+            row.into_iter()
+                .map(|d| d.unwrap_or(max + 1) as f64)
+                .collect()
+        })
+        .collect()
+}
+
+fn kk_layout<NId>(graph: &Indexed<NId>, cfg: KKCfg) -> Vec<(f64, f64)> {
+    let KKCfg {
+        iterations,
+        size,
+        epsilon,
+        seed,
+    } = cfg;
+    let size = area_size(size);
+    let n = graph.ids.len();
+    if n < 2 || size == 0. {
+        return vec![(size / 2., size / 2.); n];
+    }
+    let dist = hop_distances(&graph.undirected());
+    let max_dist = dist
+        .iter()
+        .flat_map(|row| row.iter())
+        .cloned()
+        .fold(1., f64::max);
+    let l = size / max_dist;
+
+    // the nodes start on a circle with a small jitter to break the symmetry
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut pos: Vec<(f64, f64)> = circular_layout(n, CircularCfg { radius: size / 2. })
+        .into_iter()
+        .map(|(x, y)| (x + rng.gen_range(-1.0..1.0), y + rng.gen_range(-1.0..1.0)))
+        .collect();
+
+    let gradient = |pos: &Vec<(f64, f64)>, m: usize| {
+        let (mut ex, mut ey, mut exx, mut eyy, mut exy) = (0., 0., 0., 0., 0.);
+        for i in (0..n).filter(|i| *i != m) {
+            let (dx, dy) = (pos[m].0 - pos[i].0, pos[m].1 - pos[i].1);
+            let d = (dx * dx + dy * dy).sqrt().max(1e-6);
+            let k = 1. / (dist[m][i] * dist[m][i]);
+            let lm = l * dist[m][i];
+            ex += k * (dx - lm * dx / d);
+            ey += k * (dy - lm * dy / d);
+            exx += k * (1. - lm * dy * dy / (d * d * d));
+            eyy += k * (1. - lm * dx * dx / (d * d * d));
+            exy += k * lm * dx * dy / (d * d * d);
+        }
+        (ex, ey, exx, eyy, exy)
+    };
+
+    for _ in 0..iterations {
+        let (m, delta) = (0..n)
+            .map(|m| {
+                let (ex, ey, ..) = gradient(&pos, m);
+                (m, (ex * ex + ey * ey).sqrt())
+            })
+            .fold((0, f64::MIN), |acc, e| if e.1 > acc.1 { e } else { acc });
+        if delta < epsilon {
+            break;
+        }
+        for _ in 0..50 {
+            let (ex, ey, exx, eyy, exy) = gradient(&pos, m);
+            if (ex * ex + ey * ey).sqrt() < epsilon {
+                break;
+            }
+            let det = exx * eyy - exy * exy;
+            if det.abs() < 1e-12 {
+                break;
+            }
+            pos[m].0 += (-ex * eyy + exy * ey) / det;
+            pos[m].1 += (-ey * exx + exy * ex) / det;
+        }
+    }
+    pos
+}
+
+fn layered_layout<NId>(graph: &Indexed<NId>, cfg: LayeredCfg) -> Vec<(f64, f64)> {
+    let n = graph.ids.len();
+    let mut out = vec![vec![]; n];
+    for &(f, t) in graph.edges.iter() {
+        out[f].push(t);
+    }
+
+    // removes cycles by reversing the back edges found by dfs
+    let mut state = vec![0u8; n];
+    let mut edges = vec![];
+    for root in 0..n {
+        if state[root] != 0 {
+            continue;
+        }
+        let mut stack = vec![(root, 0usize)];
+        state[root] = 1;
+        while let Some((v, i)) = stack.pop() {
+            if i < out[v].len() {
+                stack.push((v, i + 1));
+                let u = out[v][i];
+                match state[u] {
+                    0 => {
+                        edges.push((v, u));
+                        state[u] = 1;
+                        stack.push((u, 0));
+                    }
+                    1 => edges.push((u, v)),
+                    _ => edges.push((v, u)),
+                }
+            } else {
+                state[v] = 2;
+            }
+        }
+    }
+
+    // longest path layering
+    let mut in_degree = vec![0; n];
+    let mut succ = vec![vec![]; n];
+    for &(f, t) in edges.iter() {
+        in_degree[t] += 1;
+        succ[f].push(t);
+    }
+    let mut layer = vec![0usize; n];
+    let mut q: VecDeque<usize> = (0..n).filter(|v| in_degree[*v] == 0).collect();
+    while let Some(v) = q.pop_front() {
+        for &u in succ[v].iter() {
+            layer[u] = layer[u].max(layer[v] + 1);
+            in_degree[u] -= 1;
+            if in_degree[u] == 0 {
+                q.push_back(u);
+            }
+        }
+    }
+
+    // the long edges are split with dummy nodes so that every edge connects adjacent layers
+    let mut node_layer = layer.clone();
+    let mut proper = vec![];
+    for &(f, t) in edges.iter() {
+        let mut prev = f;
+        for l in layer[f] + 1..layer[t] {
+            node_layer.push(l);
+            let dummy = node_layer.len() - 1;
+            proper.push((prev, dummy));
+            prev = dummy;
+        }
+        proper.push((prev, t));
+    }
+    let total = node_layer.len();
+    let mut up = vec![vec![]; total];
+    let mut down = vec![vec![]; total];
+    for &(f, t) in proper.iter() {
+        down[f].push(t);
+        up[t].push(f);
+    }
+
+    let layers_len = node_layer.iter().max().map(|m| m + 1).unwrap_or(0);
+    let mut layers: Vec<Vec<usize>> = vec![vec![]; layers_len];
+    for (v, l) in node_layer.iter().enumerate() {
+        layers[*l].push(v);
+    }
+    let mut position = vec![0.; total];
+    for layer in layers.iter() {
+        for (i, v) in layer.iter().enumerate() {
+            position[*v] = i as f64;
+        }
+    }
+
+    // barycenter heuristic
+    let reorder = |layer: &mut Vec<usize>, position: &mut Vec<f64>, adj: &Vec<Vec<usize>>| {
+        let bary: Vec<(usize, f64)> = layer
+            .iter()
+            .map(|v| {
+                let ns = &adj[*v];
+                if ns.is_empty() {
+                    (*v, position[*v])
+                } else {
+                    (
+                        *v,
+                        ns.iter().map(|u| position[*u]).sum::<f64>() / ns.len() as f64,
+                    )
+                }
+            })
+            .collect();
+        let mut bary = bary;
+        bary.sort_by(|a, b| a.1.total_cmp(&b.1));
+        *layer = bary.into_iter().map(|(v, _)| v).collect();
+        for (i, v) in layer.iter().enumerate() {
+            position[*v] = i as f64;
+        }
+    };
+    for _ in 0..cfg.sweeps {
+        for layer in layers.iter_mut().skip(1) {
+            reorder(layer, &mut position, &up);
+        }
+        for layer in layers.iter_mut().rev().skip(1) {
+            reorder(layer, &mut position, &down);
+        }
+    }
+
+    let widest = layers.iter().map(|l| l.len()).max().unwrap_or(0) as f64;
+    let mut coords = vec![(0., 0.); total];
+    for (l, layer) in layers.iter().enumerate() {
+        let shift = (widest - layer.len() as f64) / 2.;
+        for (i, v) in layer.iter().enumerate() {
+            coords[*v] = ((i as f64 + shift) * cfg.node_gap, l as f64 * cfg.layer_gap);
+        }
+    }
+    coords.truncate(n);
+    coords
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::layout::{CircularCfg, FRCfg, GraphLayout, KKCfg, LayeredCfg, LayoutCfg};
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+
+    fn dist(a: (f64, f64), b: (f64, f64)) -> f64 {
+        ((a.0 - b.0).powi(2) + (a.1 - b.1).powi(2)).sqrt()
+    }
+
+    #[test]
+    fn fr_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3,4,5,6] => {
+           1 => [2,3];
+           [2,3] => 4;
+           5 => 6;
+        });
+        let cfg = FRCfg {
+            seed: 42,
+            ..Default::default()
+        };
+        let lhs = GraphLayout::new(LayoutCfg::FR(cfg)).layout(&graph);
+        let rhs = GraphLayout::new(LayoutCfg::FR(cfg)).layout(&graph);
+        assert_eq!(lhs, rhs);
+        assert_eq!(lhs.len(), 6);
+        for (x, y) in lhs.values() {
+            assert!((0. ..=500.).contains(x) && (0. ..=500.).contains(y));
+        }
+        let other = GraphLayout::new(LayoutCfg::FR(FRCfg { seed: 7, ..cfg })).layout(&graph);
+        assert_ne!(lhs, other);
+    }
+
+    #[test]
+    fn fr_degenerate_size_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3] => {
+           1 => [2,3];
+        });
+        let line = FRCfg {
+            width: 0.,
+            ..Default::default()
+        };
+        let coords = GraphLayout::new(LayoutCfg::FR(line)).layout(&graph);
+        for (x, y) in coords.values() {
+            assert_eq!(*x, 0.);
+            assert!((0. ..=500.).contains(y));
+        }
+        assert!(coords.values().any(|(_, y)| *y > 0.));
+
+        let point = FRCfg {
+            width: -1.,
+            height: f64::NAN,
+            ..Default::default()
+        };
+        let coords = GraphLayout::new(LayoutCfg::FR(point)).layout(&graph);
+        assert!(coords.values().all(|c| *c == (0., 0.)));
+    }
+
+    #[test]
+    fn kk_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3,4] => {
+           1 => 2;
+           2 => 3;
+           3 => 4;
+        });
+        let pos = GraphLayout::new(LayoutCfg::KK(KKCfg::default())).layout(&graph);
+        assert!(dist(pos[&1], pos[&2]) < dist(pos[&1], pos[&3]));
+        assert!(dist(pos[&1], pos[&3]) < dist(pos[&1], pos[&4]));
+    }
+
+    #[test]
+    fn kk_degenerate_size_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3] => {
+           1 => [2,3];
+        });
+        for size in [f64::NAN, f64::INFINITY, 0.] {
+            let cfg = KKCfg {
+                size,
+                ..Default::default()
+            };
+            let coords = GraphLayout::new(LayoutCfg::KK(cfg)).layout(&graph);
+            assert_eq!(coords.len(), 3);
+            assert!(coords.values().all(|c| *c == (0., 0.)));
+        }
+    }
+
+    #[test]
+    fn layered_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3,4,5] => {
+           1 => [2,3];
+           [2,3] => 4;
+           1 => 5;
+           4 => 1;
+        });
+        let pos = GraphLayout::new(LayoutCfg::Layered(LayeredCfg::default())).layout(&graph);
+        assert_eq!(pos[&1].1, 0.);
+        assert_eq!(pos[&2].1, 100.);
+        assert_eq!(pos[&3].1, 100.);
+        assert_eq!(pos[&4].1, 200.);
+        assert_eq!(pos[&5].1, 100.);
+        assert_ne!(pos[&2].0, pos[&3].0);
+    }
+
+    #[test]
+    fn circular_test() {
+        let graph = digraph!((usize,_,_) => [1,2,3,4] => { 1 => 2 });
+        let pos = GraphLayout::new(LayoutCfg::Circular(CircularCfg { radius: 10. })).layout(&graph);
+        for p in pos.values() {
+            assert!((dist(*p, (10., 10.)) - 10.).abs() < 1e-9);
+        }
+        assert!((pos[&1].0 - 10.).abs() < 1e-9);
+        assert!(pos[&1].1.abs() < 1e-9);
+    }
+}
@@ -38,6 +38,7 @@
 //!  - visualizer: the module allows visualizing the graph and some extra information in graphviz format
 //!  - generator: the module allows generating random graphs according to the different modules
 //!  - iterator: a set of iterators over the graph
+//!  - layout: the module allows computing the coordinates of nodes(force-directed, layered, circular)
 //! # Example with modules:
 //! ```rust
 //!  
@@ -86,6 +87,7 @@ pub mod analyzer;
 pub mod builder;
 pub mod generator;
 pub mod iterator;
+pub mod layout;
 pub mod visualizer;
 
 use crate::analyzer::GraphAnalyzer;
@@ -94,16 +96,21 @@ use crate::visualizer::{vis, vis_to_file};
 
 use self::visualizer::DotGraphVisualizer;
 use analyzer::dom::Dominators;
-use analyzer::predecessors::Predecessors;
+use analyzer::predecessors::{Predecessors, ReverseIndex};
 use analyzer::scc::TarjanSCC;
 use graphviz_rust::dot_generator::{graph, id, node};
 use graphviz_rust::dot_structures::{Graph, Id, Stmt};
-use iterator::{NodeIteratorBF, NodeIteratorDF, NodeIteratorDFPostOrder, NodeIteratorPlain};
+use iterator::walk::{RandomWalk, WalkCfg};
+use iterator::{
+    EdgeIteratorBF, EdgeIteratorDF, EdgeIteratorPlain, EdgeIteratorSorted, NodeIteratorBF,
+    NodeIteratorDF, NodeIteratorDFPostOrder, NodeIteratorPlain, NodeIteratorReverseBF,
+    NodeIteratorReverseDF,
+};
 use std::collections::{HashMap, HashSet};
 use std::fmt::{Debug, Error, Formatter};
 use std::hash::Hash;
 
-#[derive(PartialEq,Eq)]
+#[derive(PartialEq,Eq,Debug)]
 pub struct Edge<'a, NId, EL> where
     NId: Eq + Hash, {
     src: &'a NId,
@@ -116,8 +123,27 @@ impl<'a, NId, EL> Edge<'a, NId, EL> where
     pub fn new(src: &'a NId, trg: &'a NId, payload: &'a EL) -> Self {
         Self { src, trg, payload }
     }
+    pub fn src(&self) -> &'a NId {
+        self.src
+    }
+    pub fn trg(&self) -> &'a NId {
+        self.trg
+    }
+    pub fn payload(&self) -> &'a EL {
+        self.payload
+    }
+}
+
+impl<'a, NId, EL> Clone for Edge<'a, NId, EL> where
+    NId: Eq + Hash, {
+    fn clone(&self) -> Self {
+        *self
+    }
 }
 
+impl<'a, NId, EL> Copy for Edge<'a, NId, EL> where
+    NId: Eq + Hash, {}
+
 /// The base structure denoting a directed graph with a start in the first added node.
 ///  - NId: id of the node. should be unique and implement `Eq + Hash`
 ///  - NL: payload for node
@@ -132,6 +158,22 @@ pub struct DiGraph<NId, NL, EL>
     start: Option<NId>,
 }
 
+/// The nodes reachable from the start by at least one step.
+fn reachable<'a, NId, F>(start: &NId, next: F) -> HashSet<&'a NId>
+    where
+        NId: Eq + Hash,
+        F: Fn(&NId) -> Vec<&'a NId>,
+{
+    let mut res = HashSet::new();
+    let mut stack = next(start);
+    while let Some(id) = stack.pop() {
+        if res.insert(id) {
+            stack.extend(next(id));
+        }
+    }
+    res
+}
+
 impl DiGraph<usize, EmptyPayload, EmptyPayload> {
     /// Default empty payload graph
     pub fn empty() -> Self {
@@ -234,6 +276,45 @@ impl<NId, NL, EL> DiGraph<NId, NL, EL>
         NodeIteratorBF::new(&self)
     }
 
+    /// Depth-first traversal from the given roots(one after another).
+    pub fn iter_df_from(&self, roots: &[NId]) -> NodeIteratorDF<'_, NId, NL, EL> {
+        NodeIteratorDF::from_roots(self, roots)
+    }
+    /// Post-order depth-first traversal from the given roots(one after another).
+    pub fn iter_df_post_from(&self, roots: &[NId]) -> NodeIteratorDFPostOrder<'_, NId, NL, EL> {
+        NodeIteratorDFPostOrder::from_roots(self, roots)
+    }
+    /// Breadth-first traversal from the given roots(all at once).
+    pub fn iter_bf_from(&self, roots: &[NId]) -> NodeIteratorBF<'_, NId, NL, EL> {
+        NodeIteratorBF::from_roots(self, roots)
+    }
+
+    /// Splits the nodes reachable from the roots into the bfs layers:
+    /// the layer `i` contains the nodes at the distance(number of edges) `i` from the nearest root.
+    pub fn bfs_layers(&self, roots: &[NId]) -> Vec<Vec<&NId>> {
+        let mut layers: Vec<Vec<&NId>> = vec![];
+        for (depth, id, _, _) in self.iter_bf_from(roots).leveled() {
+            if layers.len() == depth {
+                layers.push(vec![]);
+            }
+            layers[depth].push(id);
+        }
+        layers
+    }
+
+    /// Depth-first traversal over all nodes, including the ones unreachable from the start.
+    pub fn iter_df_forest(&self) -> NodeIteratorDF<'_, NId, NL, EL> {
+        NodeIteratorDF::forest(self)
+    }
+    /// Post-order depth-first traversal over all nodes, including the ones unreachable from the start.
+    pub fn iter_df_post_forest(&self) -> NodeIteratorDFPostOrder<'_, NId, NL, EL> {
+        NodeIteratorDFPostOrder::forest(self)
+    }
+    /// Breadth-first traversal over all nodes, including the ones unreachable from the start.
+    pub fn iter_bf_forest(&self) -> NodeIteratorBF<'_, NId, NL, EL> {
+        NodeIteratorBF::forest(self)
+    }
+
     pub fn predecessors(&self) -> Predecessors<NId> {
         Predecessors::new(&self)
     }
@@ -244,6 +325,58 @@ impl<NId, NL, EL> DiGraph<NId, NL, EL>
         TarjanSCC::new(&self).process_graph()
     }
 
+    /// A seeded random walk from the given node(see `iterator::walk`).
+    pub fn iter_walk(&self, start: &NId, cfg: WalkCfg) -> RandomWalk<'_, NId, NL, EL>
+    where
+        NId: Ord,
+    {
+        RandomWalk::new(self, start, cfg)
+    }
+
+    /// Breadth-first traversal backwards(along the incoming edges) from the given roots.
+    pub fn iter_bf_reverse(&self, roots: &[NId]) -> NodeIteratorReverseBF<'_, NId, NL, EL> {
+        NodeIteratorReverseBF::new(self, roots)
+    }
+    /// Depth-first traversal backwards(along the incoming edges) from the given roots.
+    pub fn iter_df_reverse(&self, roots: &[NId]) -> NodeIteratorReverseDF<'_, NId, NL, EL> {
+        NodeIteratorReverseDF::new(self, roots)
+    }
+
+    /// Returns all nodes the given node is reachable from(the node itself is included only if it is on a cycle).
+    pub fn ancestors(&self, id: &NId) -> HashSet<&NId> {
+        let index = ReverseIndex::new(self);
+        reachable(id, |n| index.predecessor_ids(n))
+    }
+
+    /// Returns all nodes reachable from the given node(the node itself is included only if it is on a cycle).
+    pub fn descendants(&self, id: &NId) -> HashSet<&NId> {
+        reachable(id, |n| self.successor_ids(n))
+    }
+
+    /// Iterates over all edges lazily(in an arbitrary order).
+    pub fn iter_edges(&self) -> EdgeIteratorPlain<'_, NId, EL> {
+        EdgeIteratorPlain::new(self)
+    }
+
+    /// Iterates over all edges in the ascending order of the key(see `EdgeIteratorSorted`).
+    pub fn iter_edges_sorted_by_key<'a, K, F>(&'a self, key: F) -> EdgeIteratorSorted<'a, NId, EL, K>
+    where
+        K: Ord,
+        F: Fn(&Edge<'a, NId, EL>) -> K,
+    {
+        EdgeIteratorSorted::new(self, key)
+    }
+
+    /// The edges of the bfs tree from the start in the traversal order.
+    pub fn iter_bf_edges(&self) -> EdgeIteratorBF<'_, NId, NL, EL> {
+        NodeIteratorBF::new(self).edges()
+    }
+
+    /// The edges of the dfs tree from the start in the traversal order.
+    pub fn iter_df_edges(&self) -> EdgeIteratorDF<'_, NId, NL, EL> {
+        EdgeIteratorDF::new(self)
+    }
+
     /// Returns a list of edge references as a plain structure.
     pub fn edges(&self) -> Vec<Edge<NId, EL>> {
         let mut edges = vec![];
@@ -1,15 +1,19 @@
 pub mod dot;
+pub mod ego;
+pub mod html;
 
 use crate::{DiGraph, EmptyPayload};
 use graphviz_rust::attributes::{EdgeAttributes, NodeAttributes};
-use graphviz_rust::cmd::{CommandArg, Format};
+use graphviz_rust::cmd::{CommandArg, Format, Layout};
 use graphviz_rust::dot_generator::*;
 use graphviz_rust::dot_structures::*;
 use graphviz_rust::printer::{DotPrinter, PrinterContext};
 use graphviz_rust::{exec, exec_dot};
+use std::collections::HashMap;
 use std::hash::Hash;
 
-use self::dot::{DotProcessor, ToStringProcessor};
+use self::dot::{DotProcessor, PinnedProcessor, ToStringProcessor};
+use self::ego::{EgoCfg, EgoView};
 pub struct DotGraphVisualizer<'a, NId, NL, EL>
 where
     NId: Eq + Hash,
@@ -26,6 +30,24 @@ where
     pub fn str_to_dot_file(&self, path: &str) -> std::io::Result<String> {
         self.to_dot_file(path, ToStringProcessor {})
     }
+
+    /// Writes a standalone interactive html page(svg, pan/zoom, search, neighbors, tooltips).
+    /// Returns the content of the page.
+    pub fn to_html(&self, path: &str) -> std::io::Result<String> {
+        self.to_html_with(path, ToStringProcessor {})
+    }
+
+    /// The same as `to_html` but the svg is rendered with the given processor,
+    /// thus any highlighter can be used.
+    pub fn to_html_with<P>(&'a self, path: &str, processor: P) -> std::io::Result<String>
+    where
+        P: DotProcessor<'a, NId, NL, EL>,
+    {
+        let svg = vis_to_svg(self.to_dot(processor))?;
+        let page = html::page(path, &svg, &html::graph_json(self.graph));
+        std::fs::write(path, &page)?;
+        Ok(page)
+    }
 }
 
 impl<'a, NId, NL, EL> DotGraphVisualizer<'a, NId, NL, EL>
@@ -57,13 +79,76 @@ where
     {
         vis_to_file(self.to_dot(processor), path.to_string())
     }
+
+    /// Renders the graph with neato keeping the nodes at the given positions(see `layout::GraphLayout`).
+    pub fn to_pinned_dot_file<P>(
+        &'a self,
+        path: &str,
+        processor: P,
+        positions: &HashMap<NId, (f64, f64)>,
+    ) -> std::io::Result<String>
+    where
+        P: DotProcessor<'a, NId, NL, EL>,
+    {
+        let dot = self.to_dot(PinnedProcessor::new(positions, processor));
+        vis_to_file_with(
+            dot,
+            path.to_string(),
+            vec![
+                CommandArg::Layout(Layout::Neato),
+                CommandArg::Custom("-n".to_string()),
+            ],
+        )
+    }
+}
+
+impl<'a, NId, NL, EL> DotGraphVisualizer<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + ToString,
+{
+    /// Returns the k-hop neighborhood around the focus nodes(see `ego::EgoView`).
+    pub fn ego(&self, focus: &[NId], cfg: EgoCfg) -> EgoView<'a, NId, NL, EL> {
+        EgoView::new(self.graph, focus, cfg)
+    }
+
+    /// Renders only the neighborhood around the focus nodes collapsing the rest into summary nodes.
+    pub fn to_ego_dot_file<P>(
+        &self,
+        path: &str,
+        focus: &[NId],
+        cfg: EgoCfg,
+        processor: P,
+    ) -> std::io::Result<String>
+    where
+        P: DotProcessor<'a, NId, NL, EL>,
+    {
+        vis_to_file(self.ego(focus, cfg).to_dot(processor), path.to_string())
+    }
 }
 
 pub fn vis(dot_graph: Graph) -> String {
     dot_graph.print(&mut PrinterContext::default())
 }
 
+/// Renders the graph to svg and returns it as a string.
+pub fn vis_to_svg(dot_graph: Graph) -> std::io::Result<String> {
+    exec(
+        dot_graph,
+        &mut PrinterContext::default(),
+        vec![CommandArg::Format(Format::Svg)],
+    )
+}
+
 pub fn vis_to_file(dot_graph: Graph, path: String) -> std::io::Result<String> {
+    vis_to_file_with(dot_graph, path, vec![])
+}
+
+/// The same as `vis_to_file` but passes extra arguments(layout engine, etc) to the graphviz cmd.
+pub fn vis_to_file_with(
+    dot_graph: Graph,
+    path: String,
+    args: Vec<CommandArg>,
+) -> std::io::Result<String> {
     let ext = path
         .split(".")
         .last()
@@ -74,9 +159,8 @@ pub fn vis_to_file(dot_graph: Graph, path: String) -> std::io::Result<String> {
         "dot" => Format::Dot,
         _ => panic!("dot or svg"),
     };
-    exec(
-        dot_graph,
-        &mut PrinterContext::default(),
-        vec![CommandArg::Output(path), CommandArg::Format(format)],
-    )
+    let mut args = args;
+    args.push(CommandArg::Output(path));
+    args.push(CommandArg::Format(format));
+    exec(dot_graph, &mut PrinterContext::default(), args)
 }
@@ -3,6 +3,7 @@ use graphviz_rust::attributes::{EdgeAttributes, NodeAttributes};
 use graphviz_rust::dot_generator::*;
 use graphviz_rust::dot_structures::*;
 use graphviz_rust::printer::{DotPrinter};
+use std::collections::HashMap;
 use std::hash::Hash;
 
 /// The processor to visualize the nodes and edges of the graph to dot format
@@ -24,6 +25,12 @@ impl<T: ToString> ToStringOpt for T {
     }
 }
 
+/// Quotes a text to be placed into dot as an id or an attribute value,
+/// the quotes and the backslashes are escaped.
+pub fn dot_string(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
 pub struct ToStringProcessor;
 
 impl ToStringProcessor {
@@ -86,6 +93,43 @@ impl<'a, NId, NL, EL> DotProcessor<'a, NId, NL, EL> for ToStringProcessor
     }
 }
 
+/// Wraps another processor and pins the nodes to the given coordinates
+/// with the `pos` attribute(`x,y!`). The coordinates can be obtained from `layout::GraphLayout`.
+///
+/// **Note**: the `pos` attribute is respected by neato(fdp) only.
+pub struct PinnedProcessor<'p, NId, P> {
+    positions: &'p HashMap<NId, (f64, f64)>,
+    delegate: P,
+}
+
+impl<'p, NId, P> PinnedProcessor<'p, NId, P> {
+    pub fn new(positions: &'p HashMap<NId, (f64, f64)>, delegate: P) -> Self {
+        Self {
+            positions,
+            delegate,
+        }
+    }
+}
+
+impl<'a, 'p, NId, NL, EL, P> DotProcessor<'a, NId, NL, EL> for PinnedProcessor<'p, NId, P>
+where
+    NId: Eq + Hash,
+    P: DotProcessor<'a, NId, NL, EL>,
+{
+    fn node(&self, id: &'a NId, nl: &'a NL) -> Stmt {
+        let mut stmt = self.delegate.node(id, nl);
+        if let (Stmt::Node(node), Some((x, y))) = (&mut stmt, self.positions.get(id)) {
+            node.attributes
+                .push(NodeAttributes::pos(format!("\"{},{}!\"", x, y)));
+        }
+        stmt
+    }
+
+    fn edge(&self, from: &'a NId, to: &'a NId, el: &'a EL) -> Stmt {
+        self.delegate.edge(from, to, el)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use crate::visualizer::{vis, vis_to_file};
@@ -143,4 +187,18 @@ mod tests {
 
         println!("{:?}", dot)
     }
+
+    #[test]
+    fn pinned_test() {
+        use crate::visualizer::dot::{PinnedProcessor, ToStringProcessor};
+        use std::collections::HashMap;
+
+        let graph = digraph!(=> [1,2] => { 1 => 2 });
+        let positions = HashMap::from([(1, (0., 10.5)), (2, (20., 0.))]);
+        let dot = vis(graph
+            .visualize()
+            .to_dot(PinnedProcessor::new(&positions, ToStringProcessor)));
+        assert!(dot.contains("1[label=\"1\",pos=\"0,10.5!\"]"));
+        assert!(dot.contains("2[label=\"2\",pos=\"20,0!\"]"));
+    }
 }
@@ -0,0 +1,361 @@
+//! Neighborhood-focused(ego) rendering for large graphs.
+//!
+//! Only the k-hop neighborhood(in both directions) around the chosen nodes is rendered.
+//! The rest of the graph is collapsed into summary nodes: every rendered node that has hidden
+//! neighbors gets an extra node `+N` where N is the number of the hidden neighbors.
+//! The neighborhood can be capped by the degree or the PageRank of the nodes.
+//! The nodes and edges are rendered with the given `DotProcessor`.
+use crate::visualizer::dot::{dot_string, DotProcessor};
+use crate::DiGraph;
+use graphviz_rust::attributes::{arrowhead, shape, EdgeAttributes, NodeAttributes};
+use graphviz_rust::dot_generator::*;
+use graphviz_rust::dot_structures::*;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::hash::Hash;
+
+/// The way to cap the number of rendered nodes.
+/// The focus nodes are always rendered and they are counted in the limit.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum EgoCap {
+    /// Renders the whole neighborhood.
+    All,
+    /// Keeps the given number of nodes with the highest degree(in + out).
+    Degree(usize),
+    /// Keeps the given number of nodes with the highest PageRank(computed over the whole graph).
+    PageRank(usize),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub struct EgoCfg {
+    /// the number of hops from the focus nodes
+    pub hops: usize,
+    pub cap: EgoCap,
+}
+
+impl Default for EgoCfg {
+    fn default() -> Self {
+        Self {
+            hops: 1,
+            cap: EgoCap::All,
+        }
+    }
+}
+
+/// The part of the graph around the focus nodes.
+pub struct EgoView<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+{
+    graph: &'a DiGraph<NId, NL, EL>,
+    focus: HashSet<&'a NId>,
+    nodes: HashSet<&'a NId>,
+    hidden: HashMap<&'a NId, usize>,
+}
+
+impl<'a, NId, NL, EL> EgoView<'a, NId, NL, EL>
+where
+    NId: Eq + Hash,
+{
+    /// Builds the view around the focus nodes. The nodes that do not exist in the graph are ignored.
+    pub fn new(graph: &'a DiGraph<NId, NL, EL>, focus: &[NId], cfg: EgoCfg) -> Self {
+        let neighbors = undirected(graph);
+        let focus: HashSet<&NId> = focus
+            .iter()
+            .filter_map(|id| graph.nodes.get_key_value(id).map(|(k, _)| k))
+            .collect();
+
+        let mut distance: HashMap<&NId, usize> = focus.iter().map(|id| (*id, 0)).collect();
+        let mut queue: VecDeque<&NId> = focus.iter().cloned().collect();
+        while let Some(id) = queue.pop_front() {
+            let d = distance[id];
+            if d == cfg.hops {
+                continue;
+            }
+            for n in neighbors.get(id).into_iter().flatten() {
+                if !distance.contains_key(n) {
+                    distance.insert(n, d + 1);
+                    queue.push_back(n);
+                }
+            }
+        }
+
+        let nodes = match cfg.cap {
+            EgoCap::All => distance.into_keys().collect(),
+            EgoCap::Degree(limit) => {
+                let degree = |id: &&NId| neighbors.get(id).map(|ns| ns.len()).unwrap_or(0) as f64;
+                cap(&focus, distance, limit, degree)
+            }
+            EgoCap::PageRank(limit) => {
+                let rank = page_rank(graph);
+                cap(&focus, distance, limit, |id| {
+                    rank.get(id).cloned().unwrap_or_default()
+                })
+            }
+        };
+
+        let hidden = nodes
+            .iter()
+            .filter_map(|id| {
+                let count = neighbors
+                    .get(id)
+                    .map(|ns| ns.iter().filter(|n| !nodes.contains(*n)).count())
+                    .unwrap_or(0);
+                if count > 0 {
+                    Some((*id, count))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        Self {
+            graph,
+            focus,
+            nodes,
+            hidden,
+        }
+    }
+
+    pub fn focus(&self) -> &HashSet<&'a NId> {
+        &self.focus
+    }
+    /// The rendered nodes.
+    pub fn nodes(&self) -> &HashSet<&'a NId> {
+        &self.nodes
+    }
+    /// The number of the hidden neighbors for the rendered nodes.
+    pub fn hidden(&self) -> &HashMap<&'a NId, usize> {
+        &self.hidden
+    }
+}
+
+impl<'a, NId, NL, EL> EgoView<'a, NId, NL, EL>
+where
+    NId: Eq + Hash + ToString,
+{
+    /// Renders the neighborhood with the given processor.
+    /// The focus nodes are bold and the summary nodes are dashed boxes with the label `+N`.
+    pub fn to_dot<P>(&self, processor: P) -> Graph
+    where
+        P: DotProcessor<'a, NId, NL, EL>,
+    {
+        let mut dot = graph!(strict di id!("di_graph"));
+        for (id, pl) in self.graph.nodes.iter() {
+            if self.nodes.contains(id) {
+                let mut stmt = processor.node(id, pl);
+                if let (Stmt::Node(node), true) = (&mut stmt, self.focus.contains(id)) {
+                    node.attributes.push(NodeAttributes::penwidth(3.0));
+                }
+                dot.add_stmt(stmt);
+            }
+        }
+        for (from, to_map) in self.graph.edges.iter() {
+            if !self.nodes.contains(from) {
+                continue;
+            }
+            for (to, pl) in to_map.iter() {
+                if self.nodes.contains(to) {
+                    dot.add_stmt(processor.edge(from, to, pl))
+                }
+            }
+        }
+        let mut taken: HashSet<String> = self.graph.nodes.keys().map(|id| id.to_string()).collect();
+        for (id, count) in self.hidden.iter() {
+            let id = id.to_string();
+            // the summary id is not taken by a real node or another summary
+            let mut summary = format!("{}_hidden", id);
+            while !taken.insert(summary.clone()) {
+                summary.push('_');
+            }
+            let summary = dot_string(&summary);
+            dot.add_stmt(stmt!(node!(
+                summary.as_str(),
+                vec![
+                    NodeAttributes::label(format!("\"+{}\"", count)),
+                    NodeAttributes::shape(shape::box_),
+                    NodeAttributes::style("dashed".to_string())
+                ]
+            )));
+            dot.add_stmt(stmt!(
+                edge!(node_id!(id.as_str()) => node_id!(summary.as_str()), vec![
+                    EdgeAttributes::style("dashed".to_string()),
+                    EdgeAttributes::arrowhead(arrowhead::none)
+                ])
+            ));
+        }
+        dot
+    }
+}
+
+/// Keeps the focus nodes and the best nodes(according to the score) of the neighborhood.
+/// The ties are resolved by the distance to the focus.
+fn cap<'a, NId, F>(
+    focus: &HashSet<&'a NId>,
+    distance: HashMap<&'a NId, usize>,
+    limit: usize,
+    score: F,
+) -> HashSet<&'a NId>
+where
+    NId: Eq + Hash,
+    F: Fn(&&'a NId) -> f64,
+{
+    let mut candidates: Vec<(&NId, usize, f64)> = distance
+        .into_iter()
+        .filter(|(id, _)| !focus.contains(id))
+        .map(|(id, d)| (id, d, score(&id)))
+        .collect();
+    candidates.sort_by(|a, b| b.2.total_cmp(&a.2).then(a.1.cmp(&b.1)));
+    let rest = limit.saturating_sub(focus.len());
+    focus
+        .iter()
+        .cloned()
+        .chain(candidates.into_iter().take(rest).map(|(id, _, _)| id))
+        .collect()
+}
+
+fn undirected<NId, NL, EL>(graph: &DiGraph<NId, NL, EL>) -> HashMap<&NId, HashSet<&NId>>
+where
+    NId: Eq + Hash,
+{
+    let mut neighbors: HashMap<&NId, HashSet<&NId>> = HashMap::new();
+    for (from, tos) in graph.edges.iter() {
+        for to in tos.keys() {
+            if from != to {
+                neighbors.entry(from).or_default().insert(to);
+                neighbors.entry(to).or_default().insert(from);
+            }
+        }
+    }
+    neighbors
+}
+
+/// The power iteration with the damping factor 0.85.
+/// The rank of the sinks is spread evenly.
+fn page_rank<NId, NL, EL>(graph: &DiGraph<NId, NL, EL>) -> HashMap<&NId, f64>
+where
+    NId: Eq + Hash,
+{
+    let damping = 0.85;
+    let n = graph.nodes.len() as f64;
+    let mut rank: HashMap<&NId, f64> = graph.nodes.keys().map(|id| (id, 1. / n)).collect();
+    for _ in 0..50 {
+        let mut sink = 0.;
+        let mut next: HashMap<&NId, f64> = graph.nodes.keys().map(|id| (id, 0.)).collect();
+        for (id, r) in rank.iter() {
+            let out: Vec<&NId> = graph
+                .edges
+                .get(*id)
+                .map(|tos| tos.keys().filter(|to| next.contains_key(to)).collect())
+                .unwrap_or_default();
+            if out.is_empty() {
+                sink += r;
+            } else {
+                let share = r / out.len() as f64;
+                for to in out {
+                    *next.get_mut(to).expect("the node exists") += share;
+                }
+            }
+        }
+        for r in next.values_mut() {
+            *r = (1. - damping) / n + damping * (*r + sink / n);
+        }
+        rank = next;
+    }
+    rank
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::visualizer::dot::ToStringProcessor;
+    use crate::visualizer::ego::{page_rank, EgoCap, EgoCfg, EgoView};
+    use crate::visualizer::vis;
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+    use std::collections::{HashMap, HashSet};
+
+    #[test]
+    fn ego_test() {
+        let graph = digraph!(=> [1,2,3,4,5,6,7] => {
+            1 => [2,3];
+            2 => 4;
+            4 => 5;
+            6 => 1;
+            7 => 6;
+        });
+        let view = EgoView::new(&graph, &[1], EgoCfg::default());
+        assert_eq!(view.nodes(), &HashSet::from([&1, &2, &3, &6]));
+        assert_eq!(view.hidden(), &HashMap::from([(&2, 1), (&6, 1)]));
+
+        let dot = vis(view.to_dot(ToStringProcessor));
+        assert!(dot.contains("1[label=\"1\",penwidth=3]"));
+        assert!(dot.contains("6 -> 1"));
+        assert!(!dot.contains("4[label"));
+        assert!(dot.contains("\"2_hidden\"[label=\"+1\",shape=box,style=dashed]"));
+        assert!(dot.contains("2 -> \"2_hidden\""));
+
+        let view = EgoView::new(
+            &graph,
+            &[1],
+            EgoCfg {
+                hops: 2,
+                cap: EgoCap::All,
+            },
+        );
+        assert_eq!(view.nodes().len(), 6);
+    }
+
+    #[test]
+    fn summary_id_test() {
+        let graph = digraph!((&str,_,_) => ["a","a_hidden","q\"t","b"] => {
+            "a" => "b";
+            "q\"t" => "b";
+        });
+        let view = EgoView::new(
+            &graph,
+            &["a", "a_hidden", "q\"t"],
+            EgoCfg {
+                hops: 0,
+                cap: EgoCap::All,
+            },
+        );
+        let dot = vis(view.to_dot(ToStringProcessor));
+        assert!(dot.contains("\"a_hidden_\"[label=\"+1\",shape=box,style=dashed]"));
+        assert!(dot.contains("a -> \"a_hidden_\""));
+        assert!(dot.contains(r#""q\"t_hidden"[label="+1""#));
+    }
+
+    #[test]
+    fn ego_cap_test() {
+        let graph = digraph!(=> [1,2,3,4,5,6] => {
+            1 => [2,3,4];
+            3 => [5,6];
+            5 => 3;
+        });
+        let view = EgoView::new(
+            &graph,
+            &[1],
+            EgoCfg {
+                hops: 1,
+                cap: EgoCap::Degree(2),
+            },
+        );
+        assert_eq!(view.nodes(), &HashSet::from([&1, &3]));
+        assert_eq!(view.hidden(), &HashMap::from([(&1, 2), (&3, 2)]));
+
+        let rank = page_rank(&graph);
+        let sum: f64 = rank.values().sum();
+        assert!((sum - 1.).abs() < 1e-6);
+        assert!(rank[&3] > rank[&2]);
+
+        let view = EgoView::new(
+            &graph,
+            &[1],
+            EgoCfg {
+                hops: 1,
+                cap: EgoCap::PageRank(2),
+            },
+        );
+        assert_eq!(view.nodes(), &HashSet::from([&1, &3]));
+    }
+}
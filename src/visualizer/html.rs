@@ -0,0 +1,418 @@
+use crate::DiGraph;
+use std::collections::{BTreeMap, BTreeSet};
+use std::hash::Hash;
+
+/// Strips the xml prolog and the doctype that graphviz puts before the svg element
+/// so that the svg can be inlined into a html page.
+pub fn inline_svg(svg: &str) -> &str {
+    svg.find("<svg").map(|idx| &svg[idx..]).unwrap_or(svg)
+}
+
+/// Escapes a string to be placed into a json(javascript) string literal.
+pub fn json_str(value: &str) -> String {
+    let mut res = String::with_capacity(value.len() + 2);
+    res.push('"');
+    for c in value.chars() {
+        match c {
+            '"' => res.push_str("\\\""),
+            '\\' => res.push_str("\\\\"),
+            '\n' => res.push_str("\\n"),
+            '\r' => res.push_str("\\r"),
+            '\t' => res.push_str("\\t"),
+            // prevents closing the script tag from the inside of the data
+            '<' => res.push_str("\\u003c"),
+            '>' => res.push_str("\\u003e"),
+            c if (c as u32) < 0x20 => res.push_str(&format!("\\u{:04x}", c as u32)),
+            c => res.push(c),
+        }
+    }
+    res.push('"');
+    res
+}
+
+/// Escapes a text to be placed into html.
+pub fn html_text(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Builds a json object describing the nodes of the graph:
+/// `{"id":{"payload":"..","succ":[..],"pred":[..]}}`.
+/// The ids are the same strings that are used as node ids in dot.
+pub fn graph_json<NId, NL, EL>(graph: &DiGraph<NId, NL, EL>) -> String
+where
+    NId: Eq + Hash + ToString,
+    NL: ToString,
+{
+    let mut nodes: BTreeMap<String, (String, BTreeSet<String>, BTreeSet<String>)> = graph
+        .nodes
+        .iter()
+        .map(|(id, nl)| {
+            (
+                id.to_string(),
+                (nl.to_string(), BTreeSet::new(), BTreeSet::new()),
+            )
+        })
+        .collect();
+
+    for (from, tos) in graph.edges.iter() {
+        let from = from.to_string();
+        for to in tos.keys() {
+            let to = to.to_string();
+            if let Some((_, succ, _)) = nodes.get_mut(&from) {
+                succ.insert(to.clone());
+            }
+            if let Some((_, _, pred)) = nodes.get_mut(&to) {
+                pred.insert(from.clone());
+            }
+        }
+    }
+
+    let list = |ids: &BTreeSet<String>| {
+        ids.iter()
+            .map(|id| json_str(id))
+            .collect::<Vec<_>>()
+            .join(",")
+    };
+
+    let body = nodes
+        .iter()
+        .map(|(id, (payload, succ, pred))| {
+            format!(
+                "{}:{{\"payload\":{},\"succ\":[{}],\"pred\":[{}]}}",
+                json_str(id),
+                json_str(payload),
+                list(succ),
+                list(pred)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!("{{{}}}", body)
+}
+
+/// Assembles a standalone page with the inlined svg, the json description of the nodes
+/// and a script for pan/zoom, search by node id, highlighting neighbors on click and tooltips.
+/// The page does not reference any external resources.
+pub fn page(title: &str, svg: &str, graph_json: &str) -> String {
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+{style}
+</style>
+</head>
+<body>
+<div id="dg-toolbar">
+<input id="dg-search" placeholder="node id">
+<button id="dg-find">find</button>
+<button id="dg-reset">reset</button>
+<span id="dg-status"></span>
+</div>
+<div id="dg-canvas">
+{svg}
+</div>
+<div id="dg-tooltip"></div>
+<script>
+const DG_GRAPH = {graph};
+{script}
+</script>
+</body>
+</html>
+"#,
+        title = html_text(title),
+        style = STYLE,
+        svg = inline_svg(svg),
+        graph = graph_json,
+        script = SCRIPT
+    )
+}
+
+/// Assembles a standalone page that plays a sequence of svg frames
+/// with the buttons to step back and forth, to play and pause, and a slider.
+pub fn animation_page(title: &str, svgs: &[String], captions: &[&str]) -> String {
+    let frames = svgs
+        .iter()
+        .enumerate()
+        .map(|(idx, svg)| {
+            format!(
+                "<div class=\"dg-frame\" style=\"display:{}\">\n{}\n</div>",
+                if idx == 0 { "block" } else { "none" },
+                inline_svg(svg)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n");
+    let captions = captions
+        .iter()
+        .map(|c| json_str(c))
+        .collect::<Vec<_>>()
+        .join(",");
+
+    format!(
+        r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>{title}</title>
+<style>
+{style}
+</style>
+</head>
+<body>
+<div id="dg-toolbar">
+<button id="dg-prev">&lt;</button>
+<button id="dg-play">play</button>
+<button id="dg-next">&gt;</button>
+<input id="dg-slider" type="range" min="0" max="{max}" value="0">
+<span id="dg-status"></span>
+</div>
+<div id="dg-canvas">
+{frames}
+</div>
+<script>
+const DG_CAPTIONS = [{captions}];
+{script}
+</script>
+</body>
+</html>
+"#,
+        title = html_text(title),
+        style = STYLE,
+        max = svgs.len().saturating_sub(1),
+        frames = frames,
+        captions = captions,
+        script = ANIMATION_SCRIPT
+    )
+}
+
+pub(crate) const STYLE: &str = r#"body { margin: 0; font-family: sans-serif; }
+#dg-toolbar { position: fixed; top: 0; left: 0; right: 0; height: 32px; padding: 4px 8px;
+  background: #f4f4f4; border-bottom: 1px solid #ccc; z-index: 2; }
+#dg-canvas { position: absolute; top: 41px; left: 0; right: 0; bottom: 0; overflow: hidden; cursor: grab; }
+#dg-canvas svg, .dg-frame { width: 100%; height: 100%; }
+#dg-tooltip { position: fixed; display: none; padding: 4px 6px; font-size: 12px; white-space: pre;
+  background: #ffffe0; border: 1px solid #999; pointer-events: none; z-index: 3; }
+.dg-dim { opacity: 0.15; }
+.dg-focus ellipse, .dg-focus polygon, .dg-focus path { stroke: #ff6600; stroke-width: 3px; }
+.dg-found ellipse, .dg-found polygon { fill: #ffd27f; }"#;
+
+pub(crate) const SCRIPT: &str = r#"(function () {
+  const canvas = document.getElementById('dg-canvas');
+  const svg = canvas.querySelector('svg');
+  const tooltip = document.getElementById('dg-tooltip');
+  const status = document.getElementById('dg-status');
+  if (!svg) { return; }
+
+  const nodes = {};
+  const edges = [];
+  svg.querySelectorAll('g.node').forEach(function (g) {
+    const t = g.querySelector('title');
+    if (t) { nodes[t.textContent] = g; }
+  });
+  svg.querySelectorAll('g.edge').forEach(function (g) {
+    const t = g.querySelector('title');
+    if (!t) { return; }
+    const parts = t.textContent.split('->');
+    edges.push({ src: parts[0], trg: parts[1], g: g });
+  });
+
+  // pan and zoom over the view box
+  svg.removeAttribute('width');
+  svg.removeAttribute('height');
+  const vb = svg.viewBox.baseVal;
+  const initial = { x: vb.x, y: vb.y, width: vb.width, height: vb.height };
+  function toSvg(e) {
+    const r = svg.getBoundingClientRect();
+    return { x: vb.x + (e.clientX - r.left) / r.width * vb.width,
+             y: vb.y + (e.clientY - r.top) / r.height * vb.height };
+  }
+  canvas.addEventListener('wheel', function (e) {
+    e.preventDefault();
+    const k = e.deltaY > 0 ? 1.1 : 1 / 1.1;
+    const p = toSvg(e);
+    vb.x = p.x - (p.x - vb.x) * k;
+    vb.y = p.y - (p.y - vb.y) * k;
+    vb.width *= k;
+    vb.height *= k;
+  }, { passive: false });
+  let drag = null;
+  canvas.addEventListener('mousedown', function (e) { drag = { x: e.clientX, y: e.clientY, moved: false }; });
+  window.addEventListener('mouseup', function () { setTimeout(function () { drag = null; }, 0); });
+  window.addEventListener('mousemove', function (e) {
+    if (!drag) { return; }
+    const r = svg.getBoundingClientRect();
+    vb.x -= (e.clientX - drag.x) / r.width * vb.width;
+    vb.y -= (e.clientY - drag.y) / r.height * vb.height;
+    drag.moved = drag.moved || e.clientX !== drag.x || e.clientY !== drag.y;
+    drag.x = e.clientX;
+    drag.y = e.clientY;
+  });
+
+  function clear() {
+    Object.keys(nodes).forEach(function (id) { nodes[id].classList.remove('dg-dim', 'dg-focus', 'dg-found'); });
+    edges.forEach(function (e) { e.g.classList.remove('dg-dim', 'dg-focus'); });
+    status.textContent = '';
+  }
+  function neighbors(id) {
+    const res = new Set([id]);
+    const info = DG_GRAPH[id];
+    if (info) {
+      info.succ.forEach(function (s) { res.add(s); });
+      info.pred.forEach(function (p) { res.add(p); });
+    }
+    return res;
+  }
+  function highlight(id) {
+    clear();
+    const keep = neighbors(id);
+    Object.keys(nodes).forEach(function (n) { nodes[n].classList.add(keep.has(n) ? 'dg-focus' : 'dg-dim'); });
+    edges.forEach(function (e) { e.g.classList.add(e.src === id || e.trg === id ? 'dg-focus' : 'dg-dim'); });
+    status.textContent = id + ': ' + (keep.size - 1) + ' neighbor(s)';
+  }
+  function center(g) {
+    const b = g.getBBox();
+    vb.x = b.x + b.width / 2 - vb.width / 2;
+    vb.y = b.y + b.height / 2 - vb.height / 2;
+  }
+  function find() {
+    const q = document.getElementById('dg-search').value.trim();
+    if (!q) { return; }
+    const id = nodes[q] ? q : Object.keys(nodes).find(function (n) { return n.indexOf(q) >= 0; });
+    if (id === undefined) {
+      clear();
+      status.textContent = 'not found: ' + q;
+      return;
+    }
+    highlight(id);
+    nodes[id].classList.add('dg-found');
+    center(nodes[id]);
+  }
+
+  Object.keys(nodes).forEach(function (id) {
+    const g = nodes[id];
+    g.style.cursor = 'pointer';
+    g.addEventListener('click', function (e) {
+      e.stopPropagation();
+      if (!drag || !drag.moved) { highlight(id); }
+    });
+    g.addEventListener('mousemove', function (e) {
+      const info = DG_GRAPH[id];
+      let text = id;
+      if (info) {
+        if (info.payload) { text += '\n' + info.payload; }
+        text += '\nout: ' + info.succ.length + ', in: ' + info.pred.length;
+      }
+      tooltip.textContent = text;
+      tooltip.style.left = (e.clientX + 12) + 'px';
+      tooltip.style.top = (e.clientY + 12) + 'px';
+      tooltip.style.display = 'block';
+    });
+    g.addEventListener('mouseleave', function () { tooltip.style.display = 'none'; });
+  });
+  canvas.addEventListener('click', function () { if (!drag || !drag.moved) { clear(); } });
+  document.getElementById('dg-find').addEventListener('click', find);
+  document.getElementById('dg-search').addEventListener('keydown', function (e) { if (e.key === 'Enter') { find(); } });
+  document.getElementById('dg-reset').addEventListener('click', function () {
+    clear();
+    vb.x = initial.x;
+    vb.y = initial.y;
+    vb.width = initial.width;
+    vb.height = initial.height;
+  });
+})();"#;
+
+pub(crate) const ANIMATION_SCRIPT: &str = r#"(function () {
+  const frames = document.querySelectorAll('.dg-frame');
+  const slider = document.getElementById('dg-slider');
+  const status = document.getElementById('dg-status');
+  const play = document.getElementById('dg-play');
+  let current = 0;
+  let timer = null;
+  function show(idx) {
+    if (frames.length === 0) { return; }
+    current = Math.max(0, Math.min(frames.length - 1, idx));
+    frames.forEach(function (f, i) { f.style.display = i === current ? 'block' : 'none'; });
+    slider.value = current;
+    status.textContent = (current + 1) + '/' + frames.length + ' ' + (DG_CAPTIONS[current] || '');
+  }
+  function stop() {
+    if (timer) { clearInterval(timer); timer = null; }
+    play.textContent = 'play';
+  }
+  document.getElementById('dg-prev').addEventListener('click', function () { stop(); show(current - 1); });
+  document.getElementById('dg-next').addEventListener('click', function () { stop(); show(current + 1); });
+  slider.addEventListener('input', function () { stop(); show(parseInt(slider.value, 10)); });
+  play.addEventListener('click', function () {
+    if (timer) { stop(); return; }
+    if (current === frames.length - 1) { show(0); }
+    play.textContent = 'pause';
+    timer = setInterval(function () {
+      if (current >= frames.length - 1) { stop(); } else { show(current + 1); }
+    }, 800);
+  });
+  document.addEventListener('keydown', function (e) {
+    if (e.key === 'ArrowLeft') { stop(); show(current - 1); }
+    if (e.key === 'ArrowRight') { stop(); show(current + 1); }
+  });
+  show(0);
+})();"#;
+
+#[cfg(test)]
+mod tests {
+    use crate::visualizer::html::{animation_page, graph_json, inline_svg, json_str, page};
+    use crate::DiGraph;
+    use crate::EmptyPayload;
+    use crate::{digraph, extend_edges, extend_nodes};
+
+    #[test]
+    fn json_test() {
+        assert_eq!(json_str("a\"b"), "\"a\\\"b\"");
+        assert_eq!(json_str("</script>"), "\"\\u003c/script\\u003e\"");
+
+        let graph = digraph!((_,&str,_) => [(1,"a"),(2,"b"),(3,"c")] => {
+           1 => [2,3];
+           2 => 3;
+        });
+        assert_eq!(
+            graph_json(&graph),
+            r#"{"1":{"payload":"a","succ":["2","3"],"pred":[]},"2":{"payload":"b","succ":["3"],"pred":["1"]},"3":{"payload":"c","succ":[],"pred":["1","2"]}}"#
+        );
+    }
+
+    #[test]
+    fn page_test() {
+        let svg = r#"<?xml version="1.0"?><!DOCTYPE svg><svg viewBox="0 0 10 10"><g class="node"><title>1</title></g></svg>"#;
+        assert!(inline_svg(svg).starts_with("<svg"));
+
+        let graph = digraph!(=> [1,2] => { 1 => 2 });
+        let html = page("<graph>", svg, &graph_json(&graph));
+
+        assert!(html.contains("<title>&lt;graph&gt;</title>"));
+        assert!(html.contains("<svg viewBox=\"0 0 10 10\">"));
+        assert!(!html.contains("<?xml"));
+        assert!(html.contains("const DG_GRAPH = {\"1\""));
+        assert!(!html.contains("<script src"));
+        assert!(!html.contains("<link"));
+    }
+
+    #[test]
+    fn animation_page_test() {
+        let frames = vec![
+            "<?xml?><svg><g>0</g></svg>".to_string(),
+            "<svg><g>1</g></svg>".to_string(),
+        ];
+        let html = animation_page("trace", &frames, &["start", "1: visit 1"]);
+        assert!(html.contains("<div class=\"dg-frame\" style=\"display:block\">\n<svg><g>0</g>"));
+        assert!(html.contains("<div class=\"dg-frame\" style=\"display:none\">\n<svg><g>1</g>"));
+        assert!(html.contains("max=\"1\""));
+        assert!(html.contains("const DG_CAPTIONS = [\"start\",\"1: visit 1\"];"));
+    }
+}